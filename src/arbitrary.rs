@@ -42,6 +42,8 @@ mod impl_quickcheck {
     use alloc::boxed::Box;
     use alloc::vec::Vec;
     use core::hash::{BuildHasher, Hash};
+    use core::iter::successors;
+    use core::ops::Range;
     use quickcheck::{Arbitrary, Gen};
 
     impl<K, V, S> Arbitrary for IndexMap<K, V, S>
@@ -54,9 +56,32 @@ mod impl_quickcheck {
             Self::from_iter(Vec::arbitrary(g))
         }
 
+        /// Shrinks by removing entries like a `Vec` does, then by shrinking
+        /// each key and value in place, always keeping the remaining entries
+        /// in their order. Keys that would collide with another key in the
+        /// map are skipped.
         fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-            let vec = Vec::from_iter(self.clone());
-            Box::new(vec.shrink().map(Self::from_iter))
+            let map = self.clone();
+            let entries = (0..self.len()).flat_map(move |i| {
+                let (key, value) = map.get_index(i).unwrap();
+                let keys_map = map.clone();
+                let keys = key.shrink().filter_map(move |key| {
+                    let mut map = keys_map.clone();
+                    map.replace_key(i, key).ok()?;
+                    Some(map)
+                });
+                let map = map.clone();
+                let values = value.shrink().map(move |value| {
+                    let mut map = map.clone();
+                    map[i] = value;
+                    map
+                });
+                keys.chain(values)
+            });
+            let remove = |map: &mut Self, range| {
+                map.drain(range);
+            };
+            Box::new(removals(self.clone(), self.len(), remove).chain(entries))
         }
     }
 
@@ -69,9 +94,115 @@ mod impl_quickcheck {
             Self::from_iter(Vec::arbitrary(g))
         }
 
+        /// Shrinks by removing values like a `Vec` does, then by shrinking
+        /// each value in place, skipping any that would collide with another
+        /// value in the set.
         fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-            let vec = Vec::from_iter(self.clone());
-            Box::new(vec.shrink().map(Self::from_iter))
+            let set = self.clone();
+            let values = (0..self.len()).flat_map(move |i| {
+                let set = set.clone();
+                set[i].shrink().filter_map(move |value| {
+                    if set.contains(&value) {
+                        return None;
+                    }
+                    let mut set = set.clone();
+                    set.shift_insert(i, value);
+                    set.shift_remove_index(i + 1);
+                    Some(set)
+                })
+            });
+            let remove = |set: &mut Self, range| {
+                set.drain(range);
+            };
+            Box::new(removals(self.clone(), self.len(), remove).chain(values))
+        }
+    }
+
+    /// Yields an empty collection, then the collection without each half of
+    /// its entries, then without each quarter, and so on down to each single
+    /// entry, keeping the order of the others.
+    fn removals<C>(
+        collection: C,
+        len: usize,
+        remove: fn(&mut C, Range<usize>),
+    ) -> impl Iterator<Item = C>
+    where
+        C: Clone + Default + 'static,
+    {
+        let empty = (len > 0).then(C::default);
+        let chunk_lens = successors(Some(len / 2), |&n| Some(n / 2)).take_while(|&n| n > 0);
+        let chunks = chunk_lens.flat_map(move |n| {
+            let collection = collection.clone();
+            (0..len).step_by(n).map(move |start| {
+                let mut collection = collection.clone();
+                remove(&mut collection, start..Ord::min(start + n, len));
+                collection
+            })
+        });
+        empty.into_iter().chain(chunks)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{IndexMap, IndexSet};
+        use alloc::vec::Vec;
+        use quickcheck::Arbitrary;
+
+        #[test]
+        fn shrink_keeps_order() {
+            let map: IndexMap<u8, u8> = [(5, 0), (3, 2), (9, 0)].into_iter().collect();
+            let shrunk: Vec<Vec<_>> = map.shrink().map(|map| map.into_iter().collect()).collect();
+            // Shrinking the key 5 to 3 would collide, so that is skipped.
+            let expected: [&[(u8, u8)]; 13] = [
+                &[],
+                &[(3, 2), (9, 0)],
+                &[(5, 0), (9, 0)],
+                &[(5, 0), (3, 2)],
+                &[(0, 0), (3, 2), (9, 0)],
+                &[(4, 0), (3, 2), (9, 0)],
+                &[(5, 0), (0, 2), (9, 0)],
+                &[(5, 0), (2, 2), (9, 0)],
+                &[(5, 0), (3, 0), (9, 0)],
+                &[(5, 0), (3, 1), (9, 0)],
+                &[(5, 0), (3, 2), (0, 0)],
+                &[(5, 0), (3, 2), (7, 0)],
+                &[(5, 0), (3, 2), (8, 0)],
+            ];
+            assert_eq!(shrunk, expected);
+
+            let set: IndexSet<u8> = [3, 1, 2].into_iter().collect();
+            let shrunk: Vec<Vec<_>> = set.shrink().map(|set| set.into_iter().collect()).collect();
+            // Shrinking 3 to 2 or 2 to 1 would collide, so those are skipped.
+            let expected: [&[u8]; 7] = [
+                &[],
+                &[1, 2],
+                &[3, 2],
+                &[3, 1],
+                &[0, 1, 2],
+                &[3, 0, 2],
+                &[3, 1, 0],
+            ];
+            assert_eq!(shrunk, expected);
+        }
+
+        #[test]
+        fn shrink_removes_chunks() {
+            let map: IndexMap<u8, ()> = (0..4).map(|i| (i, ())).collect();
+            let removals: Vec<Vec<_>> = map
+                .shrink()
+                .take(7)
+                .map(|map| map.into_keys().collect())
+                .collect();
+            let expected: [&[u8]; 7] = [
+                &[],
+                &[2, 3],
+                &[0, 1],
+                &[1, 2, 3],
+                &[0, 2, 3],
+                &[0, 1, 3],
+                &[0, 1, 2],
+            ];
+            assert_eq!(removals, expected);
         }
     }
 }
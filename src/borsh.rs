@@ -1,5 +1,8 @@
 #![cfg_attr(docsrs, doc(cfg(feature = "borsh")))]
 
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::hash::BuildHasher;
 use core::hash::Hash;
@@ -9,8 +12,11 @@ use borsh::error::ERROR_ZST_FORBIDDEN;
 use borsh::io::{Error, ErrorKind, Read, Result, Write};
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use crate::map::IndexMap;
-use crate::set::IndexSet;
+use crate::interner::{Interner, Symbol};
+use crate::map::{IndexMap, Slice as MapSlice};
+use crate::set::{IndexSet, Slice as SetSlice};
+use crate::util::cautious_capacity;
+use crate::{Bucket, HashValue};
 
 impl<K, V, S> BorshSerialize for IndexMap<K, V, S>
 where
@@ -44,9 +50,7 @@ where
 {
     #[inline]
     fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
-        check_zst::<K>()?;
-        let vec = <Vec<(K, V)>>::deserialize_reader(reader)?;
-        Ok(vec.into_iter().collect::<IndexMap<K, V, S>>())
+        deserialize_map(reader, usize::MAX)
     }
 }
 
@@ -79,13 +83,293 @@ where
 {
     #[inline]
     fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        deserialize_set(reader, usize::MAX)
+    }
+}
+
+impl<K, V> BorshSerialize for MapSlice<K, V>
+where
+    K: BorshSerialize,
+    V: BorshSerialize,
+{
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        check_zst::<K>()?;
+
+        u32::try_from(self.len())
+            .map_err(|_| ErrorKind::InvalidData)?
+            .serialize(writer)?;
+
+        for (key, value) in self {
+            key.serialize(writer)?;
+            value.serialize(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Deserializes a boxed slice like an `IndexMap`, without hashing its keys, so
+/// duplicates are not detected.
+impl<K, V> BorshDeserialize for Box<MapSlice<K, V>>
+where
+    K: BorshDeserialize,
+    V: BorshDeserialize,
+{
+    #[inline]
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        check_zst::<K>()?;
+        let len = u32::deserialize_reader(reader)? as usize;
+
+        // The hashes of a slice are never used, since it can't be looked up.
+        let mut entries = Vec::with_capacity(cautious_capacity::<K, V>(Some(len)));
+        for _ in 0..len {
+            let key = K::deserialize_reader(reader)?;
+            let value = V::deserialize_reader(reader)?;
+            entries.push(Bucket {
+                hash: HashValue(0),
+                key,
+                value,
+            });
+        }
+        Ok(MapSlice::from_boxed(entries.into_boxed_slice()))
+    }
+}
+
+impl<T> BorshSerialize for SetSlice<T>
+where
+    T: BorshSerialize,
+{
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
         check_zst::<T>()?;
-        let vec = <Vec<T>>::deserialize_reader(reader)?;
-        Ok(vec.into_iter().collect::<IndexSet<T, S>>())
+
+        u32::try_from(self.len())
+            .map_err(|_| ErrorKind::InvalidData)?
+            .serialize(writer)?;
+
+        for item in self {
+            item.serialize(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Deserializes a boxed slice like an `IndexSet`, without hashing its values, so
+/// duplicates are not detected.
+impl<T> BorshDeserialize for Box<SetSlice<T>>
+where
+    T: BorshDeserialize,
+{
+    #[inline]
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        check_zst::<T>()?;
+        let len = u32::deserialize_reader(reader)? as usize;
+
+        let mut entries = Vec::with_capacity(cautious_capacity::<T, ()>(Some(len)));
+        for _ in 0..len {
+            entries.push(Bucket {
+                hash: HashValue(0),
+                key: T::deserialize_reader(reader)?,
+                value: (),
+            });
+        }
+        Ok(SetSlice::from_boxed(entries.into_boxed_slice()))
+    }
+}
+
+#[cfg(feature = "borsh-schema")]
+#[cfg_attr(docsrs, doc(cfg(feature = "borsh-schema")))]
+mod schema {
+    use alloc::collections::BTreeMap;
+    use alloc::format;
+
+    use borsh::schema::{add_definition, Declaration, Definition};
+    use borsh::BorshSchema;
+
+    use crate::map::{IndexMap, Slice as MapSlice};
+    use crate::set::{IndexSet, Slice as SetSlice};
+
+    // The hasher `S` is not serialized, so it is left out of the schema, like
+    // `borsh` does for `HashMap`.
+    impl<K, V, S> BorshSchema for IndexMap<K, V, S>
+    where
+        K: BorshSchema,
+        V: BorshSchema,
+    {
+        fn add_definitions_recursively(definitions: &mut BTreeMap<Declaration, Definition>) {
+            let definition = Definition::Sequence {
+                length_width: Definition::DEFAULT_LENGTH_WIDTH,
+                length_range: Definition::DEFAULT_LENGTH_RANGE,
+                elements: <(K, V)>::declaration(),
+            };
+            add_definition(Self::declaration(), definition, definitions);
+            <(K, V)>::add_definitions_recursively(definitions);
+        }
+
+        fn declaration() -> Declaration {
+            format!("IndexMap<{}, {}>", K::declaration(), V::declaration())
+        }
+    }
+
+    // Slices have the same encoding as the map, so they share its schema, like
+    // `borsh` does for `[T]` and `Vec<T>`.
+    impl<K, V> BorshSchema for MapSlice<K, V>
+    where
+        K: BorshSchema,
+        V: BorshSchema,
+    {
+        fn add_definitions_recursively(definitions: &mut BTreeMap<Declaration, Definition>) {
+            IndexMap::<K, V, ()>::add_definitions_recursively(definitions);
+        }
+
+        fn declaration() -> Declaration {
+            IndexMap::<K, V, ()>::declaration()
+        }
+    }
+
+    impl<T, S> BorshSchema for IndexSet<T, S>
+    where
+        T: BorshSchema,
+    {
+        fn add_definitions_recursively(definitions: &mut BTreeMap<Declaration, Definition>) {
+            let definition = Definition::Sequence {
+                length_width: Definition::DEFAULT_LENGTH_WIDTH,
+                length_range: Definition::DEFAULT_LENGTH_RANGE,
+                elements: T::declaration(),
+            };
+            add_definition(Self::declaration(), definition, definitions);
+            T::add_definitions_recursively(definitions);
+        }
+
+        fn declaration() -> Declaration {
+            format!("IndexSet<{}>", T::declaration())
+        }
+    }
+
+    impl<T> BorshSchema for SetSlice<T>
+    where
+        T: BorshSchema,
+    {
+        fn add_definitions_recursively(definitions: &mut BTreeMap<Declaration, Definition>) {
+            IndexSet::<T, ()>::add_definitions_recursively(definitions);
+        }
+
+        fn declaration() -> Declaration {
+            IndexSet::<T, ()>::declaration()
+        }
+    }
+}
+
+impl BorshSerialize for Symbol {
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.as_u32().serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Symbol {
+    #[inline]
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        u32::deserialize_reader(reader).map(Symbol::from)
+    }
+}
+
+impl<S> BorshSerialize for Interner<S> {
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let iterator = self.strings();
+
+        u32::try_from(iterator.len())
+            .map_err(|_| ErrorKind::InvalidData)?
+            .serialize(writer)?;
+
+        for string in iterator {
+            string.serialize(writer)?;
+        }
+
+        Ok(())
     }
 }
 
-fn check_zst<T>() -> Result<()> {
+impl<S> BorshDeserialize for Interner<S>
+where
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let len = u32::deserialize_reader(reader)?;
+        let mut interner = Interner::default();
+        for _ in 0..len {
+            let string = String::deserialize_reader(reader)?;
+            if !interner.intern_full(&string).1 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "duplicate interned string",
+                ));
+            }
+        }
+        Ok(interner)
+    }
+}
+
+/// Deserialize a map of at most `max` entries, reading them one at a time.
+///
+/// Duplicate keys are rejected, since the map would not serialize to the same
+/// bytes again, breaking the canonical encoding of `borsh`.
+pub(crate) fn deserialize_map<R, K, V, S>(reader: &mut R, max: usize) -> Result<IndexMap<K, V, S>>
+where
+    R: Read,
+    K: BorshDeserialize + Eq + Hash,
+    V: BorshDeserialize,
+    S: BuildHasher + Default,
+{
+    check_zst::<K>()?;
+    let len = u32::deserialize_reader(reader)? as usize;
+    if len > max {
+        let msg = format!("map has more than {} entries", max);
+        return Err(Error::new(ErrorKind::InvalidData, msg));
+    }
+
+    let capacity = cautious_capacity::<K, V>(Some(len));
+    let mut map = IndexMap::with_capacity_and_hasher(capacity, S::default());
+    for _ in 0..len {
+        let key = K::deserialize_reader(reader)?;
+        let value = V::deserialize_reader(reader)?;
+        if map.insert_full(key, value).1.is_some() {
+            return Err(Error::new(ErrorKind::InvalidData, "duplicate map key"));
+        }
+    }
+    Ok(map)
+}
+
+/// Deserialize a set of at most `max` values, reading them one at a time,
+/// rejecting duplicates like `deserialize_map`.
+pub(crate) fn deserialize_set<R, T, S>(reader: &mut R, max: usize) -> Result<IndexSet<T, S>>
+where
+    R: Read,
+    T: BorshDeserialize + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    check_zst::<T>()?;
+    let len = u32::deserialize_reader(reader)? as usize;
+    if len > max {
+        let msg = format!("set has more than {} values", max);
+        return Err(Error::new(ErrorKind::InvalidData, msg));
+    }
+
+    let capacity = cautious_capacity::<T, ()>(Some(len));
+    let mut set = IndexSet::with_capacity_and_hasher(capacity, S::default());
+    for _ in 0..len {
+        if !set.insert(T::deserialize_reader(reader)?) {
+            return Err(Error::new(ErrorKind::InvalidData, "duplicate set value"));
+        }
+    }
+    Ok(set)
+}
+
+pub(crate) fn check_zst<T>() -> Result<()> {
     if size_of::<T>() == 0 {
         return Err(Error::new(ErrorKind::InvalidData, ERROR_ZST_FORBIDDEN));
     }
@@ -95,6 +379,7 @@ fn check_zst<T>() -> Result<()> {
 #[cfg(test)]
 mod borsh_tests {
     use super::*;
+    use std::collections::hash_map::RandomState;
 
     #[test]
     fn map_borsh_roundtrip() {
@@ -119,4 +404,122 @@ mod borsh_tests {
             BorshDeserialize::try_from_slice(&serialized_map).unwrap();
         assert_eq!(original_map, deserialized_map);
     }
+
+    #[test]
+    fn interner_borsh_roundtrip() {
+        let original: Interner = ["b", "", "a", "bc"].into_iter().collect();
+        let serialized = borsh::to_vec(&original).unwrap();
+        let deserialized: Interner = BorshDeserialize::try_from_slice(&serialized).unwrap();
+        assert_eq!(original, deserialized);
+
+        // Symbols are saved as their number, and resolve in the loaded interner.
+        let symbols = vec![original.get("a").unwrap(), original.get("bc").unwrap()];
+        let serialized = borsh::to_vec(&symbols).unwrap();
+        assert_eq!(serialized, borsh::to_vec(&vec![2u32, 3]).unwrap());
+        let loaded: Vec<Symbol> = BorshDeserialize::try_from_slice(&serialized).unwrap();
+        assert_eq!(loaded, symbols);
+        assert_eq!(deserialized.resolve(loaded[1]), Some("bc"));
+
+        let duplicated = borsh::to_vec(&["a", "b", "a"].map(String::from).to_vec()).unwrap();
+        let error = <Interner>::try_from_slice(&duplicated).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn borsh_duplicates() {
+        let duplicated = borsh::to_vec(&vec![(1, 2), (3, 4), (1, 5)]).unwrap();
+        let error = IndexMap::<i32, i32>::try_from_slice(&duplicated).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        let duplicated = borsh::to_vec(&vec![1, 2, 1]).unwrap();
+        let error = IndexSet::<i32>::try_from_slice(&duplicated).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn slice_borsh_roundtrip() {
+        let map: IndexMap<i32, i32> = (0..10).map(|i| (i, -i)).collect();
+        let serialized = borsh::to_vec(&map).unwrap();
+        let slice = map.clone().into_boxed_slice();
+        assert_eq!(borsh::to_vec(&slice).unwrap(), serialized);
+        let deserialized: Box<MapSlice<i32, i32>> =
+            BorshDeserialize::try_from_slice(&serialized).unwrap();
+        assert_eq!(deserialized, slice);
+
+        let set: IndexSet<i32> = (0..10).collect();
+        let serialized = borsh::to_vec(&set).unwrap();
+        let slice = set.clone().into_boxed_slice();
+        assert_eq!(borsh::to_vec(&slice).unwrap(), serialized);
+        let deserialized: Box<SetSlice<i32>> =
+            BorshDeserialize::try_from_slice(&serialized).unwrap();
+        assert_eq!(deserialized, slice);
+    }
+
+    #[cfg(feature = "borsh-schema")]
+    #[test]
+    fn borsh_schema() {
+        use borsh::schema::{BorshSchemaContainer, Definition};
+
+        let schema = BorshSchemaContainer::for_type::<IndexMap<u8, String>>();
+        assert_eq!(schema.declaration(), "IndexMap<u8, String>");
+        let elements = match schema.get_definition("IndexMap<u8, String>") {
+            Some(Definition::Sequence { elements, .. }) => elements,
+            other => panic!("unexpected definition {:?}", other),
+        };
+        assert_eq!(elements, "(u8, String)");
+
+        let schema = BorshSchemaContainer::for_type::<Box<SetSlice<u8>>>();
+        assert_eq!(schema.declaration(), "IndexSet<u8>");
+        assert!(schema.get_definition("IndexSet<u8>").is_some());
+    }
+
+    #[test]
+    fn borsh_hashed() {
+        use crate::map::borsh_hashed;
+        use fnv::FnvBuildHasher;
+
+        let map: IndexMap<i32, i32, FnvBuildHasher> = (0..10).map(|i| (i, -i)).collect();
+        let mut serialized = Vec::new();
+        borsh_hashed::serialize(&map, &mut serialized).unwrap();
+
+        // The same hasher rebuilds the table from the saved hashes.
+        let deserialized: IndexMap<i32, i32, FnvBuildHasher> =
+            borsh_hashed::deserialize(&mut &serialized[..]).unwrap();
+        assert!(deserialized.iter().eq(&map));
+        for (key, value) in &map {
+            assert_eq!(deserialized.get(key), Some(value));
+        }
+
+        // A random hasher has a different fingerprint, so it hashes the keys again.
+        let deserialized: IndexMap<i32, i32, RandomState> =
+            borsh_hashed::deserialize(&mut &serialized[..]).unwrap();
+        assert!(deserialized.iter().eq(&map));
+        for (key, value) in &map {
+            assert_eq!(deserialized.get(key), Some(value));
+        }
+
+        let set: IndexSet<i32, FnvBuildHasher> = (0..10).collect();
+        let mut serialized = Vec::new();
+        crate::set::borsh_hashed::serialize(&set, &mut serialized).unwrap();
+        let deserialized: IndexSet<i32, FnvBuildHasher> =
+            crate::set::borsh_hashed::deserialize(&mut &serialized[..]).unwrap();
+        assert!(deserialized.iter().eq(&set));
+        assert!(set.iter().all(|value| deserialized.contains(value)));
+    }
+
+    #[test]
+    fn borsh_max_entries() {
+        let map: IndexMap<i32, i32> = (0..10).map(|i| (i, -i)).collect();
+        let serialized = borsh::to_vec(&map).unwrap();
+        let deserialized: IndexMap<i32, i32> = deserialize_map(&mut &serialized[..], 10).unwrap();
+        assert_eq!(deserialized, map);
+        let error =
+            deserialize_map::<_, i32, i32, RandomState>(&mut &serialized[..], 9).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        // A huge length fails before reading any values.
+        let serialized = borsh::to_vec(&u32::MAX).unwrap();
+        let error = deserialize_set::<_, i32, RandomState>(&mut &serialized[..], 1000).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
 }
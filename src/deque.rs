@@ -0,0 +1,739 @@
+//! [`IndexDequeMap`] is a hash table with an insertion order that can grow
+//! and shrink at both ends in **O(1)** time.
+
+use crate::util::hash_key;
+use crate::{Bucket, Equivalent, HashValue};
+
+use alloc::collections::vec_deque::{self, VecDeque};
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FusedIterator;
+use core::mem;
+use hashbrown::raw::RawTable;
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+/// A hash table of key-value pairs in an order that can change at both ends,
+/// like a [`VecDeque`].
+///
+/// This is like an [`IndexMap`][crate::IndexMap] that is used as an ordered
+/// work queue. The pairs are stored in a ring buffer, and the hash table
+/// stores their positions relative to a moving offset, so
+/// [`push_front`][Self::push_front] and [`pop_front`][Self::pop_front] don't
+/// need to renumber all of the other pairs like
+/// [`IndexMap::shift_insert`][crate::IndexMap::shift_insert] and
+/// [`IndexMap::shift_remove_index`][crate::IndexMap::shift_remove_index] do.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::deque::IndexDequeMap;
+///
+/// let mut queue = IndexDequeMap::new();
+/// queue.push_back("b", 2);
+/// queue.push_back("c", 3);
+/// queue.push_front("a", 1);
+/// assert_eq!(queue.get_index_of("c"), Some(2));
+///
+/// assert_eq!(queue.pop_front(), Some(("a", 1)));
+/// assert_eq!(queue.get_index_of("c"), Some(1));
+/// assert_eq!(queue.pop_if(|_, &mut v| v > 10), None);
+/// assert!(queue.iter().eq([(&"b", &2), (&"c", &3)]));
+/// ```
+#[cfg(feature = "std")]
+pub struct IndexDequeMap<K, V, S = RandomState> {
+    core: DequeCore<K, V>,
+    hash_builder: S,
+}
+#[cfg(not(feature = "std"))]
+pub struct IndexDequeMap<K, V, S> {
+    core: DequeCore<K, V>,
+    hash_builder: S,
+}
+
+/// Core of the deque map that does not depend on S
+struct DequeCore<K, V> {
+    /// indices mapping from the entry hash to its position, which is its
+    /// index plus `offset`, wrapping around.
+    indices: RawTable<usize>,
+    /// entries is a ring buffer of the entries in order.
+    entries: VecDeque<Bucket<K, V>>,
+    /// the position of the first entry.
+    offset: usize,
+}
+
+impl<K, V> DequeCore<K, V> {
+    fn new() -> Self {
+        DequeCore {
+            indices: RawTable::new(),
+            entries: VecDeque::new(),
+            offset: 0,
+        }
+    }
+
+    fn with_capacity(n: usize) -> Self {
+        DequeCore {
+            indices: RawTable::with_capacity(n),
+            entries: VecDeque::with_capacity(n),
+            offset: 0,
+        }
+    }
+
+    fn position(&self, index: usize) -> usize {
+        self.offset.wrapping_add(index)
+    }
+
+    fn get_index_of<Q>(&self, hash: HashValue, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Equivalent<K>,
+    {
+        let (entries, offset) = (&self.entries, self.offset);
+        let eq = move |&pos: &usize| key.equivalent(&entries[pos.wrapping_sub(offset)].key);
+        let pos = self.indices.get(hash.get(), eq)?;
+        Some(pos.wrapping_sub(offset))
+    }
+
+    /// Insert the position of a new entry that is already in `entries`.
+    fn insert_position(&mut self, hash: HashValue, index: usize) {
+        let (entries, offset) = (&self.entries, self.offset);
+        let hasher = move |&pos: &usize| entries[pos.wrapping_sub(offset)].hash.get();
+        self.indices
+            .insert(hash.get(), self.position(index), hasher);
+    }
+
+    fn erase_position(&mut self, hash: HashValue, index: usize) {
+        let pos = self.position(index);
+        let erased = self.indices.erase_entry(hash.get(), move |&p| p == pos);
+        debug_assert!(erased);
+    }
+
+    fn push_back(&mut self, hash: HashValue, key: K, value: V) {
+        self.entries.push_back(Bucket { hash, key, value });
+        self.insert_position(hash, self.entries.len() - 1);
+    }
+
+    fn push_front(&mut self, hash: HashValue, key: K, value: V) {
+        self.entries.push_front(Bucket { hash, key, value });
+        self.offset = self.offset.wrapping_sub(1);
+        self.insert_position(hash, 0);
+    }
+
+    fn pop_back(&mut self) -> Option<(K, V)> {
+        let entry = self.entries.pop_back()?;
+        self.erase_position(entry.hash, self.entries.len());
+        Some((entry.key, entry.value))
+    }
+
+    fn pop_front(&mut self) -> Option<(K, V)> {
+        let entry = self.entries.pop_front()?;
+        self.erase_position(entry.hash, 0);
+        self.offset = self.offset.wrapping_add(1);
+        Some((entry.key, entry.value))
+    }
+
+    fn swap_remove_back(&mut self, index: usize) -> Option<(K, V)> {
+        let hash = self.entries.get(index)?.hash;
+        self.erase_position(hash, index);
+        let entry = self.entries.swap_remove_back(index)?;
+        if let Some(moved) = self.entries.get(index) {
+            let (old, new) = (self.position(self.entries.len()), self.position(index));
+            let pos = self
+                .indices
+                .get_mut(moved.hash.get(), move |&p| p == old)
+                .expect("index not found");
+            *pos = new;
+        }
+        Some((entry.key, entry.value))
+    }
+
+    fn clear(&mut self) {
+        self.indices.clear();
+        self.entries.clear();
+        self.offset = 0;
+    }
+}
+
+impl<K, V> Clone for DequeCore<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        DequeCore {
+            indices: self.indices.clone(),
+            entries: self.entries.clone(),
+            offset: self.offset,
+        }
+    }
+}
+
+impl<K, V, S> Clone for IndexDequeMap<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        IndexDequeMap {
+            core: self.core.clone(),
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for IndexDequeMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<K, V> IndexDequeMap<K, V> {
+    /// Create a new map. (Does not allocate.)
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Create a new map with capacity for `n` key-value pairs. (Does not
+    /// allocate if `n` is zero.)
+    pub fn with_capacity(n: usize) -> Self {
+        Self::with_capacity_and_hasher(n, RandomState::new())
+    }
+}
+
+impl<K, V, S> IndexDequeMap<K, V, S> {
+    /// Create a new map with `hash_builder`. (Does not allocate.)
+    pub fn with_hasher(hash_builder: S) -> Self {
+        IndexDequeMap {
+            core: DequeCore::new(),
+            hash_builder,
+        }
+    }
+
+    /// Create a new map with capacity for `n` key-value pairs, and with
+    /// `hash_builder`. (Does not allocate if `n` is zero.)
+    pub fn with_capacity_and_hasher(n: usize, hash_builder: S) -> Self {
+        IndexDequeMap {
+            core: DequeCore::with_capacity(n),
+            hash_builder,
+        }
+    }
+
+    /// Return the number of key-value pairs in the map.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn len(&self) -> usize {
+        self.core.entries.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return a reference to the map's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.core.entries.iter(),
+        }
+    }
+
+    /// Remove all key-value pairs in the map, while preserving its capacity.
+    pub fn clear(&mut self) {
+        self.core.clear();
+    }
+
+    /// Get a key-value pair by index.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.core.entries.get(index).map(Bucket::refs)
+    }
+
+    /// Get a key-value pair by index, with mutable access to the value.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.core.entries.get_mut(index).map(Bucket::ref_mut)
+    }
+
+    /// Get the first key-value pair.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.core.entries.front().map(Bucket::refs)
+    }
+
+    /// Get the last key-value pair.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.core.entries.back().map(Bucket::refs)
+    }
+
+    /// Get the first entry in the map for in-place manipulation.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(OccupiedEntry {
+            core: &mut self.core,
+            end: End::Front,
+        })
+    }
+
+    /// Get the last entry in the map for in-place manipulation.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn last_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(OccupiedEntry {
+            core: &mut self.core,
+            end: End::Back,
+        })
+    }
+
+    /// Remove the first key-value pair.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn pop_front(&mut self) -> Option<(K, V)> {
+        self.core.pop_front()
+    }
+
+    /// Remove the last key-value pair.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn pop_back(&mut self) -> Option<(K, V)> {
+        self.core.pop_back()
+    }
+
+    /// Remove the first key-value pair if `predicate` returns `true` for it.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn pop_front_if<F>(&mut self, predicate: F) -> Option<(K, V)>
+    where
+        F: FnOnce(&K, &mut V) -> bool,
+    {
+        let (key, value) = self.core.entries.front_mut()?.ref_mut();
+        if predicate(key, value) {
+            self.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Remove the last key-value pair if `predicate` returns `true` for it.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn pop_if<F>(&mut self, predicate: F) -> Option<(K, V)>
+    where
+        F: FnOnce(&K, &mut V) -> bool,
+    {
+        let (key, value) = self.core.entries.back_mut()?.ref_mut();
+        if predicate(key, value) {
+            self.pop_back()
+        } else {
+            None
+        }
+    }
+
+    /// Remove the key-value pair by index, replacing it with the last pair.
+    ///
+    /// Like [`VecDeque::swap_remove_back`], this perturbs the position of what
+    /// used to be the last element.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.core.swap_remove_back(index)
+    }
+}
+
+impl<K, V, S> IndexDequeMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Insert a key-value pair at the back of the map.
+    ///
+    /// If an equivalent key already exists in the map, its value is replaced
+    /// and the old value is returned, keeping its position. The key itself
+    /// is not updated.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn push_back(&mut self, key: K, value: V) -> Option<V> {
+        let hash = hash_key(&self.hash_builder, &key);
+        match self.core.get_index_of(hash, &key) {
+            Some(i) => Some(mem::replace(&mut self.core.entries[i].value, value)),
+            None => {
+                self.core.push_back(hash, key, value);
+                None
+            }
+        }
+    }
+
+    /// Insert a key-value pair at the front of the map.
+    ///
+    /// If an equivalent key already exists in the map, its value is replaced
+    /// and the old value is returned, keeping its position. The key itself
+    /// is not updated.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn push_front(&mut self, key: K, value: V) -> Option<V> {
+        let hash = hash_key(&self.hash_builder, &key);
+        match self.core.get_index_of(hash, &key) {
+            Some(i) => Some(mem::replace(&mut self.core.entries[i].value, value)),
+            None => {
+                self.core.push_front(hash, key, value);
+                None
+            }
+        }
+    }
+
+    /// Return item index, if it exists in the map.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        let hash = hash_key(&self.hash_builder, key);
+        self.core.get_index_of(hash, key)
+    }
+
+    /// Return `true` if an equivalent to `key` exists in the map.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Return a reference to the value stored for `key`, if it is present.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let i = self.get_index_of(key)?;
+        Some(&self.core.entries[i].value)
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is present.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let i = self.get_index_of(key)?;
+        Some(&mut self.core.entries[i].value)
+    }
+
+    /// Remove the key-value pair equivalent to `key`, replacing it with the
+    /// last pair.
+    ///
+    /// Like [`VecDeque::swap_remove_back`], this perturbs the position of what
+    /// used to be the last element.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let i = self.get_index_of(key)?;
+        Some(self.core.swap_remove_back(i)?.1)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum End {
+    Front,
+    Back,
+}
+
+/// A view into the first or last entry of an [`IndexDequeMap`].
+///
+/// This `struct` is created by the [`IndexDequeMap::first_entry`] and
+/// [`IndexDequeMap::last_entry`] methods.
+pub struct OccupiedEntry<'a, K, V> {
+    core: &'a mut DequeCore<K, V>,
+    end: End,
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    fn bucket(&self) -> &Bucket<K, V> {
+        let bucket = match self.end {
+            End::Front => self.core.entries.front(),
+            End::Back => self.core.entries.back(),
+        };
+        bucket.expect("non-empty map")
+    }
+
+    fn into_bucket(self) -> &'a mut Bucket<K, V> {
+        let bucket = match self.end {
+            End::Front => self.core.entries.front_mut(),
+            End::Back => self.core.entries.back_mut(),
+        };
+        bucket.expect("non-empty map")
+    }
+
+    /// Return the index of the key-value pair.
+    pub fn index(&self) -> usize {
+        match self.end {
+            End::Front => 0,
+            End::Back => self.core.entries.len() - 1,
+        }
+    }
+
+    /// Gets a reference to the entry's key in the map.
+    pub fn key(&self) -> &K {
+        &self.bucket().key
+    }
+
+    /// Gets a reference to the entry's value in the map.
+    pub fn get(&self) -> &V {
+        &self.bucket().value
+    }
+
+    /// Gets a mutable reference to the entry's value in the map.
+    ///
+    /// If you need a reference which may outlive the destruction of the
+    /// entry value, see [`into_mut`][Self::into_mut].
+    pub fn get_mut(&mut self) -> &mut V {
+        let bucket = match self.end {
+            End::Front => self.core.entries.front_mut(),
+            End::Back => self.core.entries.back_mut(),
+        };
+        &mut bucket.expect("non-empty map").value
+    }
+
+    /// Converts into a mutable reference to the entry's value in the map,
+    /// with a lifetime bound to the map itself.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.into_bucket().value
+    }
+
+    /// Sets the value of the entry to `value`, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the key-value pair from its end of the map, and return it.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn remove_entry(self) -> (K, V) {
+        let removed = match self.end {
+            End::Front => self.core.pop_front(),
+            End::Back => self.core.pop_back(),
+        };
+        removed.expect("non-empty map")
+    }
+
+    /// Remove the key-value pair from its end of the map, and return the value.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OccupiedEntry<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<K, V, S> Default for IndexDequeMap<K, V, S>
+where
+    S: Default,
+{
+    /// Return an empty [`IndexDequeMap`]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for IndexDequeMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Push all key-value pairs at the back, in iteration order, replacing
+    /// the values of keys that are already present.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iterable: I) {
+        for (key, value) in iterable {
+            self.push_back(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for IndexDequeMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iterable: I) -> Self {
+        let iter = iterable.into_iter();
+        let (low, _) = iter.size_hint();
+        let mut map = Self::with_capacity_and_hasher(low, S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a IndexDequeMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`IndexDequeMap`].
+///
+/// This `struct` is created by the [`IndexDequeMap::iter`] method.
+/// See its documentation for more.
+pub struct Iter<'a, K, V> {
+    iter: vec_deque::Iter<'a, Bucket<K, V>>,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(Bucket::refs)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n).map(Bucket::refs)
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(Bucket::refs)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<K, V, S>(map: &IndexDequeMap<K, V, S>)
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        assert_eq!(map.core.indices.len(), map.len());
+        for (i, (key, _)) in map.iter().enumerate() {
+            assert_eq!(map.get_index_of(key), Some(i));
+        }
+    }
+
+    #[test]
+    fn both_ends() {
+        let mut map = IndexDequeMap::new();
+        for i in 0..50 {
+            assert_eq!(map.push_back(i, i), None);
+            assert_eq!(map.push_front(-i - 1, i), None);
+        }
+        assert_eq!(map.push_front(10, 100), Some(10));
+        check(&map);
+        assert!(map.iter().map(|(&k, _)| k).eq(-50..50));
+
+        for i in 0..40 {
+            assert_eq!(map.pop_front(), Some((i - 50, 49 - i)));
+            assert_eq!(map.pop_back().map(|(k, _)| k), Some(49 - i));
+        }
+        check(&map);
+        assert!(map.iter().map(|(&k, _)| k).eq(-10..10));
+        assert_eq!(map.get(&-10), Some(&9));
+    }
+
+    #[test]
+    fn wrapping_offset() {
+        let mut map = IndexDequeMap::new();
+        map.core.offset = usize::MAX - 5;
+        for i in 0..10 {
+            map.push_back(i, ());
+            map.push_front(-i - 1, ());
+        }
+        check(&map);
+        for _ in 0..15 {
+            map.pop_front();
+        }
+        check(&map);
+        assert_eq!(map.get_index_of(&9), Some(4));
+    }
+
+    #[test]
+    fn entries_and_removal() {
+        let mut map: IndexDequeMap<i32, i32> = (0..10).map(|i| (i, i)).collect();
+        assert_eq!(map.pop_if(|_, &mut v| v < 5), None);
+        assert_eq!(map.pop_front_if(|_, v| *v < 5), Some((0, 0)));
+
+        let mut entry = map.first_entry().unwrap();
+        assert_eq!((entry.index(), *entry.key()), (0, 1));
+        *entry.get_mut() += 10;
+        assert_eq!(entry.insert(20), 11);
+        let entry = map.last_entry().unwrap();
+        assert_eq!((entry.index(), *entry.key()), (8, 9));
+        assert_eq!(entry.remove_entry(), (9, 9));
+        assert_eq!(map.first(), Some((&1, &20)));
+        assert_eq!(map.last(), Some((&8, &8)));
+
+        assert_eq!(map.swap_remove(&3), Some(3));
+        assert_eq!(map.swap_remove_index(0), Some((1, 20)));
+        check(&map);
+        assert!(map.iter().map(|(&k, _)| k).eq([7, 2, 8, 4, 5, 6]));
+
+        map.clear();
+        assert!(map.first_entry().is_none());
+        assert_eq!(map.pop_if(|_, _| true), None);
+    }
+}
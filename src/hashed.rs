@@ -0,0 +1,306 @@
+//! Lookups with a precomputed hash in [`IndexMap`] and [`IndexSet`].
+
+use crate::map::Entry;
+use crate::util::hash_key;
+use crate::{Entries, Equivalent, HashValue, IndexMap, IndexSet};
+
+use core::hash::{BuildHasher, Hash};
+
+/// A key with its hash precomputed by a [`BuildHasher`].
+///
+/// This lets the same key be looked up in many maps or sets while hashing it
+/// only once, as long as they all compute the same hashes as the hasher that
+/// created it, like clones of a [`BuildHasherDefault`][core::hash::BuildHasherDefault]
+/// or of one [`RandomState`][std::collections::hash_map::RandomState].
+/// Using it with any other hasher is a logic error: lookups are likely to
+/// fail and insertions may duplicate keys. Debug builds check this.
+///
+/// Lookups take a `Hashed<&Q>` of a borrowed key, while insertions take a
+/// `Hashed<K>` of the key itself, which [`as_ref`][Self::as_ref] can borrow.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::{Hashed, IndexMap};
+///
+/// let a = IndexMap::from([("x", 1), ("y", 2)]);
+/// let mut b = IndexMap::with_hasher(a.hasher().clone());
+/// b.insert("y", 20);
+///
+/// let key = Hashed::new("y", a.hasher());
+/// assert_eq!(a.get_hashed(key.as_ref()), Some(&2));
+/// assert_eq!(b.get_hashed(key.as_ref()), Some(&20));
+/// b.insert_hashed(key, 200);
+/// assert_eq!(b["y"], 200);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Hashed<T> {
+    key: T,
+    hash: HashValue,
+}
+
+impl<T> Hashed<T> {
+    /// Hash `key` with `hash_builder`.
+    pub fn new<S>(key: T, hash_builder: &S) -> Self
+    where
+        T: Hash,
+        S: BuildHasher,
+    {
+        let hash = hash_key(hash_builder, &key);
+        Hashed { key, hash }
+    }
+
+    /// Return a reference to the key.
+    pub fn key(&self) -> &T {
+        &self.key
+    }
+
+    /// Return the key, dropping its hash.
+    pub fn into_key(self) -> T {
+        self.key
+    }
+
+    /// Return the precomputed hash.
+    pub fn hash(&self) -> u64 {
+        self.hash.get()
+    }
+
+    /// Borrow the key, keeping its hash.
+    pub fn as_ref(&self) -> Hashed<&T> {
+        Hashed {
+            key: &self.key,
+            hash: self.hash,
+        }
+    }
+
+    /// Return the hash after checking in debug builds that `hash_builder`
+    /// computes it too.
+    fn checked_hash<S>(&self, hash_builder: &S) -> HashValue
+    where
+        T: Hash,
+        S: BuildHasher,
+    {
+        debug_assert!(
+            hash_key(hash_builder, &self.key) == self.hash,
+            "the key was hashed by a different hasher"
+        );
+        self.hash
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Return item index, if it exists in the map.
+    ///
+    /// Like [`get_index_of`][Self::get_index_of], with a precomputed hash.
+    pub fn get_index_of_hashed<Q>(&self, key: Hashed<&Q>) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let hash = key.checked_hash(&self.hash_builder);
+        self.core.get_index_of(hash, key.key)
+    }
+
+    /// Return `true` if an equivalent to the key exists in the map.
+    ///
+    /// Like [`contains_key`][Self::contains_key], with a precomputed hash.
+    pub fn contains_key_hashed<Q>(&self, key: Hashed<&Q>) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.get_index_of_hashed(key).is_some()
+    }
+
+    /// Return a reference to the value stored for the key, if it is present.
+    ///
+    /// Like [`get`][Self::get], with a precomputed hash.
+    pub fn get_hashed<Q>(&self, key: Hashed<&Q>) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let i = self.get_index_of_hashed(key)?;
+        Some(&self.core.as_entries()[i].value)
+    }
+
+    /// Return a mutable reference to the value stored for the key, if it is
+    /// present.
+    ///
+    /// Like [`get_mut`][Self::get_mut], with a precomputed hash.
+    pub fn get_mut_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let i = self.get_index_of_hashed(key)?;
+        Some(&mut self.core.as_entries_mut()[i].value)
+    }
+
+    /// Insert a key-value pair in the map, and get their index.
+    ///
+    /// Like [`insert_full`][Self::insert_full], with a precomputed hash.
+    pub fn insert_full_hashed(&mut self, key: Hashed<K>, value: V) -> (usize, Option<V>) {
+        let hash = key.checked_hash(&self.hash_builder);
+        self.core.insert_full(hash, key.key, value)
+    }
+
+    /// Insert a key-value pair in the map.
+    ///
+    /// Like [`insert`][Self::insert], with a precomputed hash.
+    pub fn insert_hashed(&mut self, key: Hashed<K>, value: V) -> Option<V> {
+        self.insert_full_hashed(key, value).1
+    }
+
+    /// Get the given key's corresponding entry in the map for insertion and/or
+    /// in-place manipulation.
+    ///
+    /// Like [`entry`][Self::entry], with a precomputed hash.
+    pub fn entry_hashed(&mut self, key: Hashed<K>) -> Entry<'_, K, V> {
+        let hash = key.checked_hash(&self.hash_builder);
+        self.core.entry(hash, key.key)
+    }
+
+    /// Remove the key-value pair equivalent to the key and return it and the
+    /// index it had, by swapping it with the last element.
+    ///
+    /// Like [`swap_remove_full`][Self::swap_remove_full], with a precomputed hash.
+    pub fn swap_remove_full_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<(usize, K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let hash = key.checked_hash(&self.hash_builder);
+        self.core.swap_remove_full(hash, key.key)
+    }
+
+    /// Remove the key-value pair equivalent to the key and return its value,
+    /// by swapping it with the last element.
+    ///
+    /// Like [`swap_remove`][Self::swap_remove], with a precomputed hash.
+    pub fn swap_remove_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        Some(self.swap_remove_full_hashed(key)?.2)
+    }
+
+    /// Remove the key-value pair equivalent to the key and return it and the
+    /// index it had, by shifting all of the elements that follow it.
+    ///
+    /// Like [`shift_remove_full`][Self::shift_remove_full], with a precomputed hash.
+    pub fn shift_remove_full_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<(usize, K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let hash = key.checked_hash(&self.hash_builder);
+        self.core.shift_remove_full(hash, key.key)
+    }
+
+    /// Remove the key-value pair equivalent to the key and return its value,
+    /// by shifting all of the elements that follow it.
+    ///
+    /// Like [`shift_remove`][Self::shift_remove], with a precomputed hash.
+    pub fn shift_remove_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        Some(self.shift_remove_full_hashed(key)?.2)
+    }
+}
+
+impl<T, S> IndexSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Return item index, if it exists in the set.
+    ///
+    /// Like [`get_index_of`][Self::get_index_of], with a precomputed hash.
+    pub fn get_index_of_hashed<Q>(&self, value: Hashed<&Q>) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.get_index_of_hashed(value)
+    }
+
+    /// Return `true` if an equivalent to the value exists in the set.
+    ///
+    /// Like [`contains`][Self::contains], with a precomputed hash.
+    pub fn contains_hashed<Q>(&self, value: Hashed<&Q>) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.contains_key_hashed(value)
+    }
+
+    /// Return a reference to the value stored in the set, if it is present.
+    ///
+    /// Like [`get`][Self::get], with a precomputed hash.
+    pub fn get_hashed<Q>(&self, value: Hashed<&Q>) -> Option<&T>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        let i = self.map.get_index_of_hashed(value)?;
+        Some(&self.map.core.as_entries()[i].key)
+    }
+
+    /// Insert the value into the set, and get its index.
+    ///
+    /// Like [`insert_full`][Self::insert_full], with a precomputed hash.
+    pub fn insert_full_hashed(&mut self, value: Hashed<T>) -> (usize, bool) {
+        let (index, existing) = self.map.insert_full_hashed(value, ());
+        (index, existing.is_none())
+    }
+
+    /// Insert the value into the set.
+    ///
+    /// Like [`insert`][Self::insert], with a precomputed hash.
+    pub fn insert_hashed(&mut self, value: Hashed<T>) -> bool {
+        self.insert_full_hashed(value).1
+    }
+
+    /// Remove the value from the set and return it and the index it had, by
+    /// swapping it with the last element.
+    ///
+    /// Like [`swap_remove_full`][Self::swap_remove_full], with a precomputed hash.
+    pub fn swap_remove_full_hashed<Q>(&mut self, value: Hashed<&Q>) -> Option<(usize, T)>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        let (i, value, ()) = self.map.swap_remove_full_hashed(value)?;
+        Some((i, value))
+    }
+
+    /// Remove the value from the set, by swapping it with the last element.
+    ///
+    /// Like [`swap_remove`][Self::swap_remove], with a precomputed hash.
+    pub fn swap_remove_hashed<Q>(&mut self, value: Hashed<&Q>) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.swap_remove_full_hashed(value).is_some()
+    }
+
+    /// Remove the value from the set and return it and the index it had, by
+    /// shifting all of the elements that follow it.
+    ///
+    /// Like [`shift_remove_full`][Self::shift_remove_full], with a precomputed hash.
+    pub fn shift_remove_full_hashed<Q>(&mut self, value: Hashed<&Q>) -> Option<(usize, T)>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        let (i, value, ()) = self.map.shift_remove_full_hashed(value)?;
+        Some((i, value))
+    }
+
+    /// Remove the value from the set, by shifting all of the elements that
+    /// follow it.
+    ///
+    /// Like [`shift_remove`][Self::shift_remove], with a precomputed hash.
+    pub fn shift_remove_hashed<Q>(&mut self, value: Hashed<&Q>) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.shift_remove_full_hashed(value).is_some()
+    }
+}
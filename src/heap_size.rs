@@ -0,0 +1,63 @@
+#[cfg(feature = "deepsize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deepsize")))]
+mod impl_deepsize {
+    use crate::{IndexMap, IndexSet};
+    use deepsize::{Context, DeepSizeOf};
+
+    impl<K, V, S> DeepSizeOf for IndexMap<K, V, S>
+    where
+        K: DeepSizeOf,
+        V: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.iter()
+                .fold(self.allocation_size().total(), |sum, (key, value)| {
+                    sum + key.deep_size_of_children(context) + value.deep_size_of_children(context)
+                })
+        }
+    }
+
+    impl<T, S> DeepSizeOf for IndexSet<T, S>
+    where
+        T: DeepSizeOf,
+    {
+        fn deep_size_of_children(&self, context: &mut Context) -> usize {
+            self.iter()
+                .fold(self.allocation_size().total(), |sum, value| {
+                    sum + value.deep_size_of_children(context)
+                })
+        }
+    }
+}
+
+#[cfg(feature = "get-size")]
+#[cfg_attr(docsrs, doc(cfg(feature = "get-size")))]
+mod impl_get_size {
+    use crate::{IndexMap, IndexSet};
+    use get_size::GetSize;
+
+    impl<K, V, S> GetSize for IndexMap<K, V, S>
+    where
+        K: GetSize,
+        V: GetSize,
+    {
+        fn get_heap_size(&self) -> usize {
+            self.iter()
+                .fold(self.allocation_size().total(), |sum, (key, value)| {
+                    sum + key.get_heap_size() + value.get_heap_size()
+                })
+        }
+    }
+
+    impl<T, S> GetSize for IndexSet<T, S>
+    where
+        T: GetSize,
+    {
+        fn get_heap_size(&self) -> usize {
+            self.iter()
+                .fold(self.allocation_size().total(), |sum, value| {
+                    sum + value.get_heap_size()
+                })
+        }
+    }
+}
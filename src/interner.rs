@@ -0,0 +1,514 @@
+//! [`Interner`] is an insertion-ordered set of strings that are stored
+//! contiguously, identified by compact [`Symbol`]s.
+
+use crate::HashValue;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::iter::FusedIterator;
+use core::ops::{Index, Range};
+use hashbrown::raw::RawTable;
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+/// A compact identifier of a string in an [`Interner`].
+///
+/// Symbols are numbered in the order their strings were first interned,
+/// starting from zero, so they can also be used as indices. With the `serde`
+/// or `borsh` features, they are serialized as that `u32` number, so they can
+/// be saved together with their interner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Return the symbol with the given index.
+    ///
+    /// ***Panics*** if `index` does not fit in a `u32`.
+    pub fn from_index(index: usize) -> Self {
+        Symbol(u32::try_from(index).expect("symbol index out of range"))
+    }
+
+    /// Return the index of the symbol, which is the number of strings
+    /// interned before it.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Return the raw value of the symbol.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for Symbol {
+    fn from(value: u32) -> Self {
+        Symbol(value)
+    }
+}
+
+impl From<Symbol> for u32 {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0
+    }
+}
+
+/// An insertion-ordered set of strings, stored contiguously in a single
+/// buffer and identified by [`Symbol`]s.
+///
+/// This is like an `IndexSet<String>`, but without an allocation for every
+/// string, and with symbols that are half the size of a `usize` index on
+/// 64-bit targets. Interning a string returns the symbol of an equal string
+/// if there was one already, else it appends the string.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::interner::Interner;
+///
+/// let mut interner = Interner::new();
+/// let hello = interner.intern("hello");
+/// let world = interner.intern("world");
+/// assert_eq!(interner.intern("hello"), hello);
+///
+/// assert_eq!(interner.get("world"), Some(world));
+/// assert_eq!(interner.resolve(hello), Some("hello"));
+/// assert_eq!(&interner[world], "world");
+/// assert_eq!(interner.len(), 2);
+/// ```
+#[cfg(feature = "std")]
+pub struct Interner<S = RandomState> {
+    core: InternerCore,
+    hash_builder: S,
+}
+#[cfg(not(feature = "std"))]
+pub struct Interner<S> {
+    core: InternerCore,
+    hash_builder: S,
+}
+
+/// Core of the interner that does not depend on S
+#[derive(Clone)]
+struct InternerCore {
+    /// indices mapping from the string hash to its symbol.
+    indices: RawTable<Symbol>,
+    /// all strings, concatenated in the order of their symbols.
+    arena: String,
+    /// the end of each string in `arena`, and its hash.
+    ends: Vec<(usize, HashValue)>,
+}
+
+impl InternerCore {
+    const fn new() -> Self {
+        InternerCore {
+            indices: RawTable::new(),
+            arena: String::new(),
+            ends: Vec::new(),
+        }
+    }
+
+    fn with_capacity(n: usize, bytes: usize) -> Self {
+        InternerCore {
+            indices: RawTable::with_capacity(n),
+            arena: String::with_capacity(bytes),
+            ends: Vec::with_capacity(n),
+        }
+    }
+
+    fn span(&self, index: usize) -> Option<Range<usize>> {
+        let end = self.ends.get(index)?.0;
+        let start = match index.checked_sub(1) {
+            Some(prev) => self.ends[prev].0,
+            None => 0,
+        };
+        Some(start..end)
+    }
+
+    fn resolve(&self, index: usize) -> Option<&str> {
+        Some(&self.arena[self.span(index)?])
+    }
+
+    fn get(&self, hash: HashValue, string: &str) -> Option<Symbol> {
+        let eq = |symbol: &Symbol| self.resolve(symbol.index()) == Some(string);
+        self.indices.get(hash.get(), eq).copied()
+    }
+
+    /// Append a string, *without* checking whether it already exists.
+    fn push(&mut self, hash: HashValue, string: &str) -> Symbol {
+        let symbol = Symbol::from_index(self.ends.len());
+        self.arena.push_str(string);
+        self.ends.push((self.arena.len(), hash));
+        let ends = &self.ends;
+        self.indices
+            .insert(hash.get(), symbol, move |s| ends[s.index()].1.get());
+        symbol
+    }
+}
+
+impl<S> Clone for Interner<S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Interner {
+            core: self.core.clone(),
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for Interner<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.strings()).finish()
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl Interner {
+    /// Create a new interner. (Does not allocate.)
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Create a new interner with capacity for `n` strings of `bytes` total
+    /// length. (Does not allocate if both are zero.)
+    pub fn with_capacity(n: usize, bytes: usize) -> Self {
+        Self::with_capacity_and_hasher(n, bytes, RandomState::new())
+    }
+}
+
+impl<S> Interner<S> {
+    /// Create a new interner with `hash_builder`.
+    ///
+    /// This function is `const`, so it
+    /// can be called in `static` contexts.
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        Interner {
+            core: InternerCore::new(),
+            hash_builder,
+        }
+    }
+
+    /// Create a new interner with capacity for `n` strings of `bytes` total
+    /// length, and with `hash_builder`. (Does not allocate if both are zero.)
+    pub fn with_capacity_and_hasher(n: usize, bytes: usize, hash_builder: S) -> Self {
+        Interner {
+            core: InternerCore::with_capacity(n, bytes),
+            hash_builder,
+        }
+    }
+
+    /// Return the number of interned strings.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn len(&self) -> usize {
+        self.core.ends.len()
+    }
+
+    /// Returns true if no strings have been interned.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the total length in bytes of all interned strings.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn total_len(&self) -> usize {
+        self.core.arena.len()
+    }
+
+    /// Return a reference to the interner's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Return the string of a symbol, or `None` if the symbol is out of bounds.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.core.resolve(symbol.index())
+    }
+
+    /// Return an iterator over the symbols and their strings, in their order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            core: &self.core,
+            range: 0..self.len(),
+        }
+    }
+
+    /// Return an iterator over the interned strings, in their order.
+    pub fn strings(&self) -> Strings<'_> {
+        Strings { iter: self.iter() }
+    }
+
+    /// Remove all strings from the interner, while preserving its capacity.
+    ///
+    /// All symbols are invalidated, but they will be reused for new strings.
+    pub fn clear(&mut self) {
+        self.core.indices.clear();
+        self.core.arena.clear();
+        self.core.ends.clear();
+    }
+}
+
+impl<S> Interner<S>
+where
+    S: BuildHasher,
+{
+    fn hash(&self, string: &str) -> HashValue {
+        let mut h = self.hash_builder.build_hasher();
+        string.hash(&mut h);
+        HashValue(h.finish() as usize)
+    }
+
+    /// Return the symbol of `string`, interning a copy of it if it is not
+    /// present yet.
+    ///
+    /// ***Panics*** if there would be more than `u32::MAX + 1` strings.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn intern(&mut self, string: &str) -> Symbol {
+        self.intern_full(string).0
+    }
+
+    /// Return the symbol of `string`, interning a copy of it if it is not
+    /// present yet, and whether it was newly interned.
+    ///
+    /// ***Panics*** if there would be more than `u32::MAX + 1` strings.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn intern_full(&mut self, string: &str) -> (Symbol, bool) {
+        let hash = self.hash(string);
+        match self.core.get(hash, string) {
+            Some(symbol) => (symbol, false),
+            None => (self.core.push(hash, string), true),
+        }
+    }
+
+    /// Return the symbol of `string`, if it is interned.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        if self.is_empty() {
+            None
+        } else {
+            let hash = self.hash(string);
+            self.core.get(hash, string)
+        }
+    }
+
+    /// Return `true` if `string` is interned.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains(&self, string: &str) -> bool {
+        self.get(string).is_some()
+    }
+}
+
+impl<S> Index<Symbol> for Interner<S> {
+    type Output = str;
+
+    /// Return the string of a symbol.
+    ///
+    /// ***Panics*** if the symbol is out of bounds.
+    fn index(&self, symbol: Symbol) -> &str {
+        self.resolve(symbol)
+            .expect("Interner: symbol out of bounds")
+    }
+}
+
+impl<S> Default for Interner<S>
+where
+    S: Default,
+{
+    /// Return an empty [`Interner`]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<S1, S2> PartialEq<Interner<S2>> for Interner<S1> {
+    /// Interners are equal if they have the same strings in the same order,
+    /// so they assign the same symbols.
+    fn eq(&self, other: &Interner<S2>) -> bool {
+        self.core.arena == other.core.arena
+            && self.len() == other.len()
+            && self
+                .core
+                .ends
+                .iter()
+                .zip(&other.core.ends)
+                .all(|(a, b)| a.0 == b.0)
+    }
+}
+
+impl<S> Eq for Interner<S> {}
+
+impl<'a, S> Extend<&'a str> for Interner<S>
+where
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iterable: I) {
+        for string in iterable {
+            self.intern(string);
+        }
+    }
+}
+
+impl<'a, S> FromIterator<&'a str> for Interner<S>
+where
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iterable: I) -> Self {
+        let mut interner = Self::default();
+        interner.extend(iterable);
+        interner
+    }
+}
+
+impl<'a, S> IntoIterator for &'a Interner<S> {
+    type Item = (Symbol, &'a str);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the symbols and strings of an [`Interner`].
+///
+/// This `struct` is created by the [`Interner::iter`] method.
+/// See its documentation for more.
+#[derive(Clone)]
+pub struct Iter<'a> {
+    core: &'a InternerCore,
+    range: Range<usize>,
+}
+
+impl<'a> Iter<'a> {
+    fn item(&self, index: usize) -> (Symbol, &'a str) {
+        let string = self.core.resolve(index).expect("interned string");
+        (Symbol::from_index(index), string)
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (Symbol, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.range.next()?;
+        Some(self.item(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth(n)?;
+        Some(self.item(index))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.range.next_back()?;
+        Some(self.item(index))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl FusedIterator for Iter<'_> {}
+
+impl fmt::Debug for Iter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the strings of an [`Interner`].
+///
+/// This `struct` is created by the [`Interner::strings`] method.
+/// See its documentation for more.
+#[derive(Clone)]
+pub struct Strings<'a> {
+    iter: Iter<'a>,
+}
+
+impl<'a> Iterator for Strings<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, s)| s)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.iter.nth(n).map(|(_, s)| s)
+    }
+}
+
+impl DoubleEndedIterator for Strings<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, s)| s)
+    }
+}
+
+impl ExactSizeIterator for Strings<'_> {}
+
+impl FusedIterator for Strings<'_> {}
+
+impl fmt::Debug for Strings<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn intern_and_resolve() {
+        let mut interner = Interner::new();
+        let words = ["", "a", "bc", "a", "", "def", "bc"];
+        let symbols: Vec<Symbol> = words.iter().map(|w| interner.intern(w)).collect();
+        assert_eq!(interner.len(), 4);
+        assert_eq!(interner.total_len(), 6);
+        assert_eq!(symbols, [0, 1, 2, 1, 0, 3, 2].map(Symbol));
+        for (word, symbol) in words.iter().zip(symbols) {
+            assert_eq!(interner.resolve(symbol), Some(*word));
+            assert_eq!(interner.get(word), Some(symbol));
+        }
+        assert_eq!(interner.resolve(Symbol(4)), None);
+        assert_eq!(interner.get("ab"), None);
+        assert!(interner.strings().eq(["", "a", "bc", "def"]));
+    }
+
+    #[test]
+    fn grow() {
+        let mut interner = Interner::with_capacity(0, 0);
+        for i in 0..1000 {
+            assert_eq!(interner.intern_full(&i.to_string()), (Symbol(i), true));
+        }
+        for i in 0..1000 {
+            assert_eq!(interner.intern_full(&i.to_string()), (Symbol(i), false));
+        }
+        let copy: Interner = interner.strings().collect();
+        assert_eq!(copy, interner);
+        interner.clear();
+        assert!(interner.is_empty());
+        assert_eq!(interner.intern("7"), Symbol(0));
+    }
+}
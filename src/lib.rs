@@ -34,13 +34,29 @@
 //! * `serde`: Adds implementations for [`Serialize`] and [`Deserialize`]
 //!   to [`IndexMap`] and [`IndexSet`]. Alternative implementations for
 //!   (de)serializing [`IndexMap`] as an ordered sequence are available in the
-//!   [`map::serde_seq`] module.
+//!   [`map::serde_seq`] module, and as a list of key-value structs in the
+//!   [`map::serde_entries`] module.
 //! * `borsh`: Adds implementations for [`BorshSerialize`] and [`BorshDeserialize`]
-//!   to [`IndexMap`] and [`IndexSet`].
+//!   to [`IndexMap`] and [`IndexSet`]. Duplicate keys are rejected when
+//!   deserializing, to keep the encoding canonical.
+//! * `borsh-schema`: Adds implementations for `borsh`'s [`BorshSchema`] to
+//!   [`IndexMap`] and [`IndexSet`], with its unstable `unstable__schema` feature.
+//! * `rkyv`: Adds implementations for `rkyv`'s [`Archive`] to [`IndexMap`] and
+//!   [`IndexSet`], whose archived forms are in the `rkyv` module. This enables
+//!   `rkyv`'s `size_32` feature for the size of the archived indices.
+//! * `rkyv-custom-size`: The same as `rkyv`, but without picking a size, for
+//!   crates that enable `rkyv`'s `size_16` or `size_64` feature instead. Those
+//!   features are mutually exclusive, so such crates must not enable `rkyv`.
 //! * `arbitrary`: Adds implementations for the [`arbitrary::Arbitrary`] trait
 //!   to [`IndexMap`] and [`IndexSet`].
 //! * `quickcheck`: Adds implementations for the [`quickcheck::Arbitrary`] trait
 //!   to [`IndexMap`] and [`IndexSet`].
+//! * `deepsize`: Adds implementations for the [`deepsize::DeepSizeOf`] trait
+//!   to [`IndexMap`] and [`IndexSet`].
+//! * `get-size`: Adds implementations for the [`get_size::GetSize`] trait
+//!   to [`IndexMap`] and [`IndexSet`].
+//! * `proptest`: Adds `proptest` strategies for [`IndexMap`] and [`IndexSet`]
+//!   in the [`proptest`] module. This feature also enables `std`.
 //!
 //! _Note: only the `std` feature is enabled by default._
 //!
@@ -50,8 +66,12 @@
 //! [`Deserialize`]: `::serde::Deserialize`
 //! [`BorshSerialize`]: `::borsh::BorshSerialize`
 //! [`BorshDeserialize`]: `::borsh::BorshDeserialize`
+//! [`BorshSchema`]: `::borsh::BorshSchema`
+//! [`Archive`]: `::rkyv::Archive`
 //! [`arbitrary::Arbitrary`]: `::arbitrary::Arbitrary`
 //! [`quickcheck::Arbitrary`]: `::quickcheck::Arbitrary`
+//! [`deepsize::DeepSizeOf`]: `::deepsize::DeepSizeOf`
+//! [`get_size::GetSize`]: `::get_size::GetSize`
 //!
 //! ### Alternate Hashers
 //!
@@ -112,6 +132,8 @@ extern crate std;
 use alloc::vec::{self, Vec};
 
 mod arbitrary;
+mod hashed;
+mod heap_size;
 #[macro_use]
 mod macros;
 #[cfg(feature = "borsh")]
@@ -120,8 +142,19 @@ mod borsh;
 mod serde;
 mod util;
 
+pub mod deque;
+pub mod interner;
 pub mod map;
+pub mod priority;
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub mod proptest;
+#[cfg(feature = "rkyv-custom-size")]
+pub mod rkyv;
 pub mod set;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod sync;
 
 // Placed after `map` and `set` so new `rayon` methods on the types
 // are documented after the "normal" methods.
@@ -131,6 +164,7 @@ mod rayon;
 #[cfg(feature = "rustc-rayon")]
 mod rustc;
 
+pub use crate::hashed::Hashed;
 pub use crate::map::IndexMap;
 pub use crate::set::IndexSet;
 pub use equivalent::Equivalent;
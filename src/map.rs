@@ -2,10 +2,39 @@
 //! pairs is independent of the hash values of the keys.
 
 mod core;
+mod cursor;
+mod diff;
+mod frozen;
+mod hashdos;
 mod iter;
 mod mutable;
+mod observer;
+mod rekey;
 mod slice;
-
+mod stats;
+mod transaction;
+
+#[cfg(feature = "borsh")]
+#[cfg_attr(docsrs, doc(cfg(feature = "borsh")))]
+pub mod borsh_hashed;
+#[cfg(feature = "borsh")]
+#[cfg_attr(docsrs, doc(cfg(feature = "borsh")))]
+pub mod borsh_limits;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_dedup;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_entries;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_hashed;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_limits;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_merge;
 #[cfg(feature = "serde")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 pub mod serde_seq;
@@ -15,11 +44,23 @@ mod tests;
 
 pub use self::core::raw_entry_v1::{self, RawEntryApiV1};
 pub use self::core::{Entry, IndexedEntry, OccupiedEntry, VacantEntry};
+pub use self::cursor::CursorMut;
+pub use self::diff::Diff;
+pub use self::frozen::FrozenIndexMap;
+pub use self::hashdos::{Fallback, FallbackHasher, GuardedIndexMap, Reseed};
 pub use self::iter::{
     Drain, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys, Splice, Values, ValuesMut,
 };
 pub use self::mutable::MutableKeys;
+pub use self::observer::{
+    IndexObserver, Observed, ObservedCursorMut, ObservedEntry, ObservedIndexedEntry,
+    ObservedOccupiedEntry, ObservedRawEntryBuilderMut, ObservedRawEntryMut,
+    ObservedRawOccupiedEntryMut, ObservedRawVacantEntryMut, ObservedVacantEntry,
+};
+pub use self::rekey::{ConflictError, RenameError};
 pub use self::slice::Slice;
+pub use self::stats::{AllocationSize, TableStats};
+pub use self::transaction::Transaction;
 
 #[cfg(feature = "rayon")]
 pub use crate::rayon::map as rayon;
@@ -36,7 +77,7 @@ use alloc::vec::Vec;
 use std::collections::hash_map::RandomState;
 
 use self::core::IndexMapCore;
-use crate::util::{third, try_simplify_range};
+use crate::util::{hash_key, third, try_simplify_range};
 use crate::{Bucket, Entries, Equivalent, HashValue, TryReserveError};
 
 /// A hash table where the iteration order of the key-value pairs is independent
@@ -85,12 +126,12 @@ use crate::{Bucket, Entries, Equivalent, HashValue, TryReserveError};
 #[cfg(feature = "std")]
 pub struct IndexMap<K, V, S = RandomState> {
     pub(crate) core: IndexMapCore<K, V>,
-    hash_builder: S,
+    pub(crate) hash_builder: S,
 }
 #[cfg(not(feature = "std"))]
 pub struct IndexMap<K, V, S> {
     pub(crate) core: IndexMapCore<K, V>,
-    hash_builder: S,
+    pub(crate) hash_builder: S,
 }
 
 impl<K, V, S> Clone for IndexMap<K, V, S>
@@ -204,6 +245,37 @@ impl<K, V, S> IndexMap<K, V, S> {
         }
     }
 
+    /// Create a map from entries that are known to have unique keys.
+    pub(crate) fn from_entries(entries: Vec<Bucket<K, V>>, hash_builder: S) -> Self {
+        IndexMap {
+            core: IndexMapCore::from_entries(entries),
+            hash_builder,
+        }
+    }
+
+    /// Create a map from the parts of [`into_parts`][Self::into_parts], without
+    /// hashing any keys.
+    ///
+    /// Each entry is a `(hash, key, value)`, in order. The hashes must be those
+    /// that `hash_builder` computes for the keys, and the keys must be unique,
+    /// but neither is checked. Otherwise, the map's lookups will be wrong, and
+    /// other methods may panic, but this is not undefined behavior.
+    ///
+    /// See [`from_parts`][Self::from_parts] to check the parts in debug builds.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn from_parts_unchecked(entries: Vec<(u64, K, V)>, hash_builder: S) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|(hash, key, value)| Bucket {
+                hash: HashValue(hash as usize),
+                key,
+                value,
+            })
+            .collect();
+        Self::from_entries(entries, hash_builder)
+    }
+
     /// Return the number of elements the map can hold without reallocating.
     ///
     /// This number is a lower bound; the map might be able to hold more,
@@ -519,6 +591,72 @@ where
     {
         Splice::new(self, range, replace_with.into_iter())
     }
+
+    /// Create a map from the parts of [`into_parts`][Self::into_parts], without
+    /// hashing any keys in release builds.
+    ///
+    /// This is like [`from_parts_unchecked`][Self::from_parts_unchecked], but
+    /// debug builds check that each hash matches its key and that the keys are
+    /// unique.
+    ///
+    /// ***Panics*** in debug builds if the parts are not consistent.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn from_parts(entries: Vec<(u64, K, V)>, hash_builder: S) -> Self {
+        let map = Self::from_parts_unchecked(entries, hash_builder);
+        if cfg!(debug_assertions) {
+            map.check_parts();
+        }
+        map
+    }
+
+    /// Convert the map to use another hasher, hashing all keys again.
+    ///
+    /// This reuses the allocations of the entries and of the hash table, only
+    /// recomputing the saved hashes, so the order and indices of the entries
+    /// are unchanged.
+    ///
+    /// Computes in **O(n)** time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fnv::FnvBuildHasher;
+    /// use indexmap::IndexMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let mut map = IndexMap::with_hasher(FnvBuildHasher::default());
+    /// map.insert("a", 1);
+    /// map.insert("b", 2);
+    ///
+    /// let map: IndexMap<_, _, RandomState> = map.with_new_hasher(RandomState::new());
+    /// assert_eq!(map.get_index_of("b"), Some(1));
+    /// ```
+    pub fn with_new_hasher<S2>(self, hash_builder: S2) -> IndexMap<K, V, S2>
+    where
+        S2: BuildHasher,
+    {
+        let mut core = self.core;
+        core.rehash(|key| hash_key(&hash_builder, key));
+        IndexMap { core, hash_builder }
+    }
+
+    /// Check that each saved hash matches its key, and that the keys are unique.
+    pub(crate) fn check_parts(&self) {
+        for (i, bucket) in self.as_entries().iter().enumerate() {
+            assert!(
+                self.hash(&bucket.key) == bucket.hash,
+                "wrong hash for the key at index {}",
+                i
+            );
+            assert_eq!(
+                self.get_index_of(&bucket.key),
+                Some(i),
+                "duplicate key at index {}",
+                i
+            );
+        }
+    }
 }
 
 impl<K, V, S> IndexMap<K, V, S>
@@ -783,6 +921,78 @@ where
             }
         }
     }
+
+    /// Remove the key-value pairs equivalent to any of `keys`, and return them
+    /// in the order they had in the map.
+    ///
+    /// Like [`swap_remove`][Self::swap_remove], the vacated positions are
+    /// filled by the remaining pairs from the end of the map, keeping their
+    /// relative order. **This perturbs the position of what used to be the
+    /// last elements!**
+    ///
+    /// Keys that are not in the map are ignored.
+    ///
+    /// Computes in **O(m log m)** time (average), for *m* keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map: IndexMap<i32, char> = (0..6).zip('a'..).collect();
+    /// let removed = map.swap_remove_many(&[4, 1, 9]);
+    /// assert_eq!(removed, [(1, 'b'), (4, 'e')]);
+    /// assert!(map.keys().eq(&[0, 5, 2, 3]));
+    /// ```
+    pub fn swap_remove_many<'q, Q, I>(&mut self, keys: I) -> Vec<(K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let indices = self.indices_of(keys);
+        self.core.swap_remove_indices(indices)
+    }
+
+    /// Remove the key-value pairs equivalent to any of `keys`, and return them
+    /// in the order they had in the map.
+    ///
+    /// Like [`shift_remove`][Self::shift_remove], the pairs are removed by
+    /// shifting all of the elements that follow them, preserving their
+    /// relative order, but the indices are all corrected at once.
+    /// **This perturbs the index of all of those elements!**
+    ///
+    /// Keys that are not in the map are ignored.
+    ///
+    /// Computes in **O(n + m log m)** time (average), for *m* keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map: IndexMap<i32, char> = (0..6).zip('a'..).collect();
+    /// let removed = map.shift_remove_many(&[4, 1, 9]);
+    /// assert_eq!(removed, [(1, 'b'), (4, 'e')]);
+    /// assert!(map.keys().eq(&[0, 2, 3, 5]));
+    /// ```
+    pub fn shift_remove_many<'q, Q, I>(&mut self, keys: I) -> Vec<(K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let indices = self.indices_of(keys);
+        self.core.shift_remove_indices(indices)
+    }
+
+    fn indices_of<'q, Q, I>(&self, keys: I) -> Vec<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        keys.into_iter()
+            .filter_map(|key| self.get_index_of(key))
+            .collect()
+    }
 }
 
 impl<K, V, S> IndexMap<K, V, S> {
@@ -1002,6 +1212,35 @@ impl<K, V, S> IndexMap<K, V, S> {
         Slice::from_boxed(self.into_entries().into_boxed_slice())
     }
 
+    /// Take the map apart into its ordered entries, with the hashes of their
+    /// keys, and its `BuildHasher`.
+    ///
+    /// Each entry is a `(hash, key, value)`, so the map can be rebuilt with
+    /// [`from_parts`][Self::from_parts] without hashing the keys again.
+    ///
+    /// Computes in **O(n)** time.
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let map = IndexMap::from([("a", 1), ("b", 2)]);
+    /// let (mut entries, hasher) = map.into_parts();
+    /// entries.retain(|&(_, key, _)| key != "a");
+    ///
+    /// let map = IndexMap::from_parts(entries, hasher);
+    /// assert_eq!(map, IndexMap::from([("b", 2)]));
+    /// assert_eq!(map.get("b"), Some(&2));
+    /// ```
+    pub fn into_parts(self) -> (Vec<(u64, K, V)>, S) {
+        let entries = self
+            .core
+            .into_entries()
+            .into_iter()
+            .map(|bucket| (bucket.hash.get(), bucket.key, bucket.value))
+            .collect();
+        (entries, self.hash_builder)
+    }
+
     /// Get a key-value pair by index
     ///
     /// Valid indices are *0 <= index < self.len()*
@@ -1108,6 +1347,46 @@ impl<K, V, S> IndexMap<K, V, S> {
         self.core.shift_remove_index(index)
     }
 
+    /// Remove the key-value pairs at all of these indices, and return them
+    /// in the order they had in the map.
+    ///
+    /// Like [`swap_remove_index`][Self::swap_remove_index], the vacated
+    /// positions are filled by the remaining pairs from the end of the map,
+    /// keeping their relative order. Repeated indices are only removed once.
+    ///
+    /// ***Panics*** if any index is out of bounds.
+    ///
+    /// Computes in **O(m log m)** time (average), for *m* indices.
+    pub fn swap_remove_indices(&mut self, indices: &[usize]) -> Vec<(K, V)> {
+        self.core.swap_remove_indices(indices.to_vec())
+    }
+
+    /// Remove the key-value pairs at all of these indices, and return them
+    /// in the order they had in the map.
+    ///
+    /// Like [`shift_remove_index`][Self::shift_remove_index], the pairs are
+    /// removed by shifting all of the elements that follow them, but the
+    /// indices are all corrected at once. Repeated indices are only removed
+    /// once.
+    ///
+    /// ***Panics*** if any index is out of bounds.
+    ///
+    /// Computes in **O(n + m log m)** time (average), for *m* indices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map: IndexMap<i32, char> = (0..6).zip('a'..).collect();
+    /// let removed = map.shift_remove_indices(&[5, 0, 2, 0]);
+    /// assert_eq!(removed, [(0, 'a'), (2, 'c'), (5, 'f')]);
+    /// assert!(map.keys().eq(&[1, 3, 4]));
+    /// ```
+    pub fn shift_remove_indices(&mut self, indices: &[usize]) -> Vec<(K, V)> {
+        self.core.shift_remove_indices(indices.to_vec())
+    }
+
     /// Moves the position of a key-value pair from one index to another
     /// by shifting all other pairs in-between.
     ///
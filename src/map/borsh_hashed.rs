@@ -0,0 +1,98 @@
+//! Functions to serialize and deserialize an [`IndexMap`] with `borsh` and the
+//! hashes of its keys.
+//!
+//! These work like [`map::serde_hashed`][crate::map::serde_hashed], writing
+//! the `u64` fingerprint of the map's hasher and a `u32` length, followed by a
+//! `u64` hash, the key, and the value of each entry. The saved hashes are only
+//! reused if the default hasher has the same fingerprint, and otherwise the
+//! keys are hashed again, rejecting duplicates like the default implementation.
+//!
+//! The functions fit the signatures of the `#[borsh(serialize_with = ...)]`
+//! and `#[borsh(deserialize_with = ...)]` field attributes.
+//!
+//! ```
+//! use fxhash::FxBuildHasher;
+//! use indexmap::map::borsh_hashed;
+//! use indexmap::IndexMap;
+//!
+//! let map: IndexMap<u8, u8, FxBuildHasher> = (0..10).map(|i| (i, i)).collect();
+//! let mut bytes = Vec::new();
+//! borsh_hashed::serialize(&map, &mut bytes).unwrap();
+//!
+//! let copy: IndexMap<u8, u8, FxBuildHasher> = borsh_hashed::deserialize(&mut &bytes[..]).unwrap();
+//! assert_eq!(copy, map);
+//! ```
+
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use core::hash::{BuildHasher, Hash};
+
+use super::IndexMap;
+use crate::borsh::check_zst;
+use crate::util::{cautious_capacity, fingerprint};
+use crate::vec::Vec;
+use crate::{Bucket, Entries, HashValue};
+
+/// Serializes an [`IndexMap`] with its hasher fingerprint and the hashes of its keys.
+pub fn serialize<W, K, V, S>(map: &IndexMap<K, V, S>, writer: &mut W) -> Result<()>
+where
+    W: Write,
+    K: BorshSerialize,
+    V: BorshSerialize,
+    S: BuildHasher,
+{
+    check_zst::<K>()?;
+
+    fingerprint(map.hasher()).serialize(writer)?;
+    u32::try_from(map.len())
+        .map_err(|_| ErrorKind::InvalidData)?
+        .serialize(writer)?;
+
+    for bucket in map.as_entries() {
+        bucket.hash.get().serialize(writer)?;
+        bucket.key.serialize(writer)?;
+        bucket.value.serialize(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Deserializes an [`IndexMap`] with its hasher fingerprint and the hashes of its keys,
+/// which are only reused if the default hasher has the same fingerprint.
+pub fn deserialize<R, K, V, S>(reader: &mut R) -> Result<IndexMap<K, V, S>>
+where
+    R: Read,
+    K: BorshDeserialize + Eq + Hash,
+    V: BorshDeserialize,
+    S: BuildHasher + Default,
+{
+    check_zst::<K>()?;
+
+    let saved = u64::deserialize_reader(reader)?;
+    let len = u32::deserialize_reader(reader)? as usize;
+    let capacity = cautious_capacity::<K, V>(Some(len));
+    let hash_builder = S::default();
+
+    if fingerprint(&hash_builder) == saved {
+        let mut entries = Vec::with_capacity(capacity);
+        for _ in 0..len {
+            let hash = HashValue(u64::deserialize_reader(reader)? as usize);
+            let key = K::deserialize_reader(reader)?;
+            let value = V::deserialize_reader(reader)?;
+            entries.push(Bucket { hash, key, value });
+        }
+        Ok(IndexMap::from_entries(entries, hash_builder))
+    } else {
+        let mut map = IndexMap::with_capacity_and_hasher(capacity, hash_builder);
+        for _ in 0..len {
+            u64::deserialize_reader(reader)?;
+            let key = K::deserialize_reader(reader)?;
+            let value = V::deserialize_reader(reader)?;
+            if map.insert_full(key, value).1.is_some() {
+                return Err(Error::new(ErrorKind::InvalidData, "duplicate map key"));
+            }
+        }
+        Ok(map)
+    }
+}
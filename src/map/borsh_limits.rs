@@ -0,0 +1,51 @@
+//! Functions to deserialize an [`IndexMap`] with `borsh` and a limit on its entries.
+//!
+//! The default `BorshDeserialize` implementation accepts any `u32` length.
+//! These functions fail with [`ErrorKind::InvalidData`] when the length is
+//! larger than the limit, before reading any entries. `deserialize_max` fits
+//! the signature of the `#[borsh(deserialize_with = ...)]` field attribute.
+//!
+//! ```
+//! use indexmap::map::borsh_limits::{deserialize_max, deserialize_with_max};
+//! use indexmap::IndexMap;
+//!
+//! let map: IndexMap<u8, u8> = (0..10).map(|i| (i, i)).collect();
+//! let bytes = borsh::to_vec(&map).unwrap();
+//!
+//! let copy: IndexMap<u8, u8> = deserialize_max::<10, _, _, _, _>(&mut &bytes[..]).unwrap();
+//! assert_eq!(copy, map);
+//! let result: Result<IndexMap<u8, u8>, _> = deserialize_with_max(&mut &bytes[..], 9);
+//! assert!(result.is_err());
+//! ```
+//!
+//! [`ErrorKind::InvalidData`]: `::borsh::io::ErrorKind::InvalidData`
+
+use borsh::io::{Read, Result};
+use borsh::BorshDeserialize;
+
+use core::hash::{BuildHasher, Hash};
+
+use super::IndexMap;
+use crate::borsh::deserialize_map;
+
+/// Deserializes an [`IndexMap`] of at most `N` entries.
+pub fn deserialize_max<const N: usize, R, K, V, S>(reader: &mut R) -> Result<IndexMap<K, V, S>>
+where
+    R: Read,
+    K: BorshDeserialize + Eq + Hash,
+    V: BorshDeserialize,
+    S: BuildHasher + Default,
+{
+    deserialize_map(reader, N)
+}
+
+/// Deserializes an [`IndexMap`] of at most `max` entries.
+pub fn deserialize_with_max<R, K, V, S>(reader: &mut R, max: usize) -> Result<IndexMap<K, V, S>>
+where
+    R: Read,
+    K: BorshDeserialize + Eq + Hash,
+    V: BorshDeserialize,
+    S: BuildHasher + Default,
+{
+    deserialize_map(reader, max)
+}
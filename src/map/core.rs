@@ -19,6 +19,7 @@ use crate::TryReserveError;
 use core::mem;
 use core::ops::RangeBounds;
 
+use super::stats::{AllocationSize, TableStats};
 use crate::util::simplify_range;
 use crate::{Bucket, Entries, Equivalent, HashValue};
 
@@ -143,6 +144,13 @@ impl<K, V> IndexMapCore<K, V> {
         }
     }
 
+    /// Build the map from entries that are known to have unique keys.
+    pub(crate) fn from_entries(entries: Vec<Bucket<K, V>>) -> Self {
+        let mut indices = RawTable::with_capacity(entries.len());
+        raw::insert_bulk_no_grow(&mut indices, &entries);
+        IndexMapCore { indices, entries }
+    }
+
     #[inline]
     pub(crate) fn len(&self) -> usize {
         self.indices.len()
@@ -153,6 +161,108 @@ impl<K, V> IndexMapCore<K, V> {
         Ord::min(self.indices.capacity(), self.entries.capacity())
     }
 
+    pub(crate) fn allocation_size(&self) -> AllocationSize {
+        AllocationSize {
+            entries: self.entries.capacity() * mem::size_of::<Bucket<K, V>>(),
+            indices: self.indices.allocation_info().1.size(),
+        }
+    }
+
+    pub(crate) fn stats(&self) -> TableStats {
+        let mut hashes: Vec<_> = self
+            .entries
+            .iter()
+            .map(|bucket| bucket.hash.get())
+            .collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        TableStats {
+            len: self.len(),
+            buckets: match self.indices.capacity() {
+                0 => 0,
+                _ => self.indices.buckets(),
+            },
+            indices_capacity: self.indices.capacity(),
+            entries_capacity: self.entries.capacity(),
+            probe_distances: self.probe_distances(),
+            distinct_hashes: hashes.len(),
+        }
+    }
+
+    /// Recompute the hash of every entry and rebuild the indices, keeping the
+    /// entries in place.
+    pub(crate) fn rehash(&mut self, mut hash: impl FnMut(&K) -> HashValue) {
+        for bucket in &mut self.entries {
+            bucket.hash = hash(&bucket.key);
+        }
+        self.indices.clear();
+        raw::insert_bulk_no_grow(&mut self.indices, &self.entries);
+    }
+
+    /// Like `rehash`, but also remove every entry with a key equal to an
+    /// earlier one, returning them with the new index of that earlier entry.
+    /// The old index of each of them is passed to `removed_at`, in order.
+    pub(crate) fn rehash_dedup(
+        &mut self,
+        mut hash: impl FnMut(&K) -> HashValue,
+        mut removed_at: impl FnMut(usize),
+    ) -> Vec<(usize, K, V)>
+    where
+        K: Eq,
+    {
+        for bucket in &mut self.entries {
+            bucket.hash = hash(&bucket.key);
+        }
+        self.indices.clear();
+
+        // Pairs of each duplicate index and the index of its original.
+        let mut duplicates = Vec::new();
+        for (i, bucket) in self.entries.iter().enumerate() {
+            let eq = equivalent(&bucket.key, &self.entries);
+            match self.indices.get(bucket.hash.get(), eq) {
+                Some(&original) => duplicates.push((i, original)),
+                None => {
+                    self.indices
+                        .insert(bucket.hash.get(), i, get_hash(&self.entries));
+                }
+            }
+        }
+        let start = match duplicates.first() {
+            Some(&(start, _)) => start,
+            None => return Vec::new(),
+        };
+
+        let tail = self.entries.split_off(start);
+        let mut removed = Vec::with_capacity(duplicates.len());
+        let mut next = duplicates.iter().peekable();
+        for (i, entry) in (start..).zip(tail) {
+            match next.next_if(|&&(d, _)| d == i) {
+                Some(&(_, original)) => {
+                    removed_at(i);
+                    // Originals only shift down past the duplicates before them.
+                    let shift = duplicates.partition_point(|&(d, _)| d < original);
+                    removed.push((original - shift, entry.key, entry.value));
+                }
+                None => self.entries.push(entry),
+            }
+        }
+        self.rebuild_hash_table();
+        removed
+    }
+
+    /// Replace the key at `index` with one that has `hash`, returning the old key.
+    ///
+    /// The new key must not be equal to any other key in the map.
+    pub(crate) fn replace_key(&mut self, index: usize, hash: HashValue, key: K) -> K {
+        erase_index(&mut self.indices, self.entries[index].hash, index);
+        let bucket = &mut self.entries[index];
+        bucket.hash = hash;
+        let old = mem::replace(&mut bucket.key, key);
+        self.indices
+            .insert(hash.get(), index, get_hash(&self.entries));
+        old
+    }
+
     pub(crate) fn clear(&mut self) {
         self.indices.clear();
         self.entries.clear();
@@ -331,11 +441,45 @@ impl<K, V> IndexMapCore<K, V> {
         self.indices.get(hash.get(), eq).copied()
     }
 
+    /// Same as `get_index_of`, also adding the number of keys that were
+    /// compared to `probes`.
+    pub(crate) fn get_index_of_counted<Q>(
+        &self,
+        hash: HashValue,
+        key: &Q,
+        probes: &mut usize,
+    ) -> Option<usize>
+    where
+        Q: ?Sized + Equivalent<K>,
+    {
+        let eq = equivalent(key, &self.entries);
+        let eq = |i: &usize| {
+            *probes += 1;
+            eq(i)
+        };
+        self.indices.get(hash.get(), eq).copied()
+    }
+
     pub(crate) fn insert_full(&mut self, hash: HashValue, key: K, value: V) -> (usize, Option<V>)
     where
         K: Eq,
     {
-        match self.find_or_insert(hash, &key) {
+        self.insert_full_counted(hash, key, value, &mut 0)
+    }
+
+    /// Same as `insert_full`, also adding the number of keys that were
+    /// compared to `probes`.
+    pub(crate) fn insert_full_counted(
+        &mut self,
+        hash: HashValue,
+        key: K,
+        value: V,
+        probes: &mut usize,
+    ) -> (usize, Option<V>)
+    where
+        K: Eq,
+    {
+        match self.find_or_insert_counted(hash, &key, probes) {
             Ok(i) => (i, Some(mem::replace(&mut self.entries[i].value, value))),
             Err(i) => {
                 debug_assert_eq!(i, self.entries.len());
@@ -372,7 +516,7 @@ impl<K, V> IndexMapCore<K, V> {
         }
     }
 
-    fn insert_unique(&mut self, hash: HashValue, key: K, value: V) -> usize {
+    pub(super) fn insert_unique(&mut self, hash: HashValue, key: K, value: V) -> usize {
         let i = self.indices.len();
         self.indices.insert(hash.get(), i, get_hash(&self.entries));
         debug_assert_eq!(i, self.entries.len());
@@ -380,7 +524,7 @@ impl<K, V> IndexMapCore<K, V> {
         i
     }
 
-    fn shift_insert_unique(&mut self, index: usize, hash: HashValue, key: K, value: V) {
+    pub(super) fn shift_insert_unique(&mut self, index: usize, hash: HashValue, key: K, value: V) {
         let end = self.indices.len();
         assert!(index <= end);
         // Increment others first so we don't have duplicate indices.
@@ -605,6 +749,90 @@ impl<K, V> IndexMapCore<K, V> {
         debug_assert_eq!(self.indices.len(), start + shifted);
     }
 
+    /// Sort and deduplicate `indices` for removal.
+    ///
+    /// ***Panics*** if any index is out of bounds.
+    fn removal_indices(&self, mut indices: Vec<usize>) -> Vec<usize> {
+        indices.sort_unstable();
+        indices.dedup();
+        if let Some(&last) = indices.last() {
+            assert!(last < self.entries.len(), "IndexMap: index out of bounds");
+        }
+        indices
+    }
+
+    /// Remove the entries at all of these indices by shifting the entries that
+    /// follow them, returning the removed pairs in their original order.
+    ///
+    /// ***Panics*** if any index is out of bounds.
+    pub(crate) fn shift_remove_indices(&mut self, indices: Vec<usize>) -> Vec<(K, V)> {
+        let indices = self.removal_indices(indices);
+        let start = match indices.first() {
+            Some(&start) => start,
+            None => return Vec::new(),
+        };
+
+        // Use a heuristic between a full sweep vs. a `find()` for every affected item.
+        let affected_entries = &self.entries[start..];
+        if affected_entries.len() > self.indices.buckets() / 2 {
+            self.erase_indices_sweep_sorted(&indices);
+        } else {
+            // Ascending order ensures that each new index has been vacated.
+            let mut removed = indices.iter().peekable();
+            for (i, entry) in (start..).zip(affected_entries) {
+                if removed.next_if_eq(&&i).is_some() {
+                    erase_index(&mut self.indices, entry.hash, i);
+                } else {
+                    let shift = indices.len() - removed.len();
+                    update_index(&mut self.indices, entry.hash, i, i - shift);
+                }
+            }
+        }
+
+        let tail = self.entries.split_off(start);
+        let mut removed = Vec::with_capacity(indices.len());
+        let mut next = indices.iter().peekable();
+        for (i, entry) in (start..).zip(tail) {
+            if next.next_if_eq(&&i).is_some() {
+                removed.push((entry.key, entry.value));
+            } else {
+                self.entries.push(entry);
+            }
+        }
+        removed
+    }
+
+    /// Remove the entries at all of these indices by moving the last entries
+    /// into their place, returning the removed pairs in their original order.
+    ///
+    /// ***Panics*** if any index is out of bounds.
+    pub(crate) fn swap_remove_indices(&mut self, indices: Vec<usize>) -> Vec<(K, V)> {
+        let indices = self.removal_indices(indices);
+        let len = self.entries.len();
+        let end = len - indices.len();
+
+        for &i in &indices {
+            erase_index(&mut self.indices, self.entries[i].hash, i);
+        }
+
+        // Fill the holes below `end` with the kept entries from `end..`, in order.
+        let (holes, tail) = indices.split_at(indices.partition_point(|&i| i < end));
+        let movers = (end..len).filter(|i| tail.binary_search(i).is_err());
+        let mut origins: Vec<usize> = (end..len).collect();
+        for (&hole, from) in holes.iter().zip(movers) {
+            update_index(&mut self.indices, self.entries[from].hash, from, hole);
+            self.entries.swap(hole, from);
+            origins[from - end] = hole;
+        }
+
+        let mut removed: Vec<_> = origins.into_iter().zip(self.entries.drain(end..)).collect();
+        removed.sort_unstable_by_key(|&(i, _)| i);
+        removed
+            .into_iter()
+            .map(|(_, entry)| (entry.key, entry.value))
+            .collect()
+    }
+
     pub(crate) fn retain_in_order<F>(&mut self, mut keep: F)
     where
         F: FnMut(&mut K, &mut V) -> bool,
@@ -633,6 +861,72 @@ impl<K, V> IndexMapCore<K, V> {
     }
 }
 
+/// Overwrites the entries of a map in place, with the same result as clearing
+/// it and inserting each new entry in turn, but reusing the old keys and values.
+///
+/// Any old entries that were not overwritten are dropped with the `Refill`.
+#[cfg(feature = "serde")]
+pub(crate) struct Refill<'a, K, V> {
+    core: &'a mut IndexMapCore<K, V>,
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<K, V> IndexMapCore<K, V> {
+    pub(crate) fn refill(&mut self) -> Refill<'_, K, V> {
+        self.indices.clear();
+        Refill { core: self, len: 0 }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V> Refill<'_, K, V> {
+    /// Return the next old entry to overwrite, if any are left.
+    pub(crate) fn next_slot(&mut self) -> Option<(&mut K, &mut V)> {
+        self.core.entries.get_mut(self.len).map(Bucket::muts)
+    }
+
+    /// Insert the entry of the last `next_slot` after it was overwritten.
+    ///
+    /// If its key is a duplicate, the value is swapped into the existing entry,
+    /// and the slot is reused again.
+    pub(crate) fn insert_slot(&mut self, hash: HashValue)
+    where
+        K: Eq,
+    {
+        let (entries, rest) = self.core.entries.split_at_mut(self.len);
+        let slot = &mut rest[0];
+        let eq = equivalent(&slot.key, entries);
+        match self.core.indices.get(hash.get(), eq) {
+            Some(&i) => mem::swap(&mut entries[i].value, &mut slot.value),
+            None => {
+                slot.hash = hash;
+                self.core
+                    .indices
+                    .insert(hash.get(), self.len, get_hash(entries));
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Insert a new entry once all of the old ones are used.
+    pub(crate) fn insert(&mut self, hash: HashValue, key: K, value: V)
+    where
+        K: Eq,
+    {
+        debug_assert_eq!(self.len, self.core.entries.len());
+        self.core.insert_full(hash, key, value);
+        self.len = self.core.entries.len();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V> Drop for Refill<'_, K, V> {
+    fn drop(&mut self) {
+        self.core.entries.truncate(self.len);
+    }
+}
+
 #[test]
 fn assert_send_sync() {
     fn assert_send_sync<T: Send + Sync>() {}
@@ -3,6 +3,7 @@
 //! mostly in dealing with its bucket "pointers".
 
 use super::{equivalent, get_hash, Bucket, HashValue, IndexMapCore};
+use crate::vec::Vec;
 use hashbrown::raw::RawTable;
 
 type RawBucket = hashbrown::raw::Bucket<usize>;
@@ -49,6 +50,23 @@ impl<K, V> IndexMapCore<K, V> {
         }
     }
 
+    /// Sweep the whole table to erase the sorted, distinct `removed` indices,
+    /// and shift the others down past them
+    pub(super) fn erase_indices_sweep_sorted(&mut self, removed: &[usize]) {
+        // SAFETY: we're not letting any of the buckets escape this function
+        unsafe {
+            for bucket in self.indices.iter() {
+                let i = bucket.as_mut();
+                let below = removed.partition_point(|&r| r < *i);
+                if removed.get(below) == Some(&*i) {
+                    self.indices.erase(bucket);
+                } else {
+                    *i -= below;
+                }
+            }
+        }
+    }
+
     /// Search for a key in the table and return `Ok(entry_index)` if found.
     /// Otherwise, insert the key and return `Err(new_index)`.
     ///
@@ -56,11 +74,29 @@ impl<K, V> IndexMapCore<K, V> {
     /// even before checking if it's already present, so this is somewhat biased
     /// towards new items.
     pub(crate) fn find_or_insert(&mut self, hash: HashValue, key: &K) -> Result<usize, usize>
+    where
+        K: Eq,
+    {
+        self.find_or_insert_counted(hash, key, &mut 0)
+    }
+
+    /// Same as `find_or_insert`, also adding the number of keys that were
+    /// compared to `probes`.
+    pub(crate) fn find_or_insert_counted(
+        &mut self,
+        hash: HashValue,
+        key: &K,
+        probes: &mut usize,
+    ) -> Result<usize, usize>
     where
         K: Eq,
     {
         let hash = hash.get();
         let eq = equivalent(key, &self.entries);
+        let eq = |i: &usize| {
+            *probes += 1;
+            eq(i)
+        };
         let hasher = get_hash(&self.entries);
         // SAFETY: We're not mutating between find and read/insert.
         unsafe {
@@ -93,6 +129,26 @@ impl<K, V> IndexMapCore<K, V> {
         }
     }
 
+    /// Count the indices at each distance from the bucket where probing for
+    /// their hash starts, wrapping around the end of the table.
+    pub(super) fn probe_distances(&self) -> Vec<usize> {
+        let bucket_mask = self.indices.buckets() - 1;
+        let mut counts = Vec::new();
+        // SAFETY: we're not letting any of the buckets escape this function
+        unsafe {
+            for bucket in self.indices.iter() {
+                // This is how hashbrown picks the first bucket to probe.
+                let start = self.entries[*bucket.as_ref()].hash.get() as usize & bucket_mask;
+                let distance = self.indices.bucket_index(&bucket).wrapping_sub(start) & bucket_mask;
+                if distance >= counts.len() {
+                    counts.resize(distance + 1, 0);
+                }
+                counts[distance] += 1;
+            }
+        }
+        counts
+    }
+
     pub(super) fn indices_mut(&mut self) -> impl Iterator<Item = &mut usize> {
         // SAFETY: we're not letting any of the buckets escape this function,
         // only the item references that are appropriately bound to `&mut self`.
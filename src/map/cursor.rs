@@ -0,0 +1,225 @@
+//! A cursor for walking and editing an [`IndexMap`] in place.
+
+use super::{Entry, IndexMap};
+use crate::Equivalent;
+
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+/// A cursor over an [`IndexMap`] that can insert and remove entries around
+/// its position.
+///
+/// The cursor points either at an entry or at a "ghost" position after the
+/// last entry, which wraps around to the first entry like the cursors of
+/// [`LinkedList`][alloc::collections::LinkedList]. Every edit goes through
+/// the map's own operations, so the hash table always stays consistent.
+///
+/// This `struct` is created by the [`IndexMap::cursor_mut_at`] and
+/// [`IndexMap::cursor_mut_at_key`] methods. See their documentation for more.
+pub struct CursorMut<'a, K, V, S> {
+    pub(super) map: &'a mut IndexMap<K, V, S>,
+    pub(super) index: usize,
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Returns a cursor pointing at the entry at `index`, or at the ghost
+    /// position if `index` is equal to the length of the map.
+    ///
+    /// ***Panics*** if `index` is greater than the length of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map = IndexMap::from([("a", 1), ("c", 3), ("d", 4)]);
+    /// let mut cursor = map.cursor_mut_at(0);
+    /// cursor.insert_after("b", 2);
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some((&"b", &mut 2)));
+    /// assert_eq!(cursor.peek_next(), Some((&"c", &mut 3)));
+    /// assert_eq!(cursor.remove_current(), Some(("b", 2)));
+    /// assert_eq!(cursor.index(), Some(1));
+    ///
+    /// let rest = cursor.split_after();
+    /// assert!(map.keys().eq(&["a", "c"]));
+    /// assert!(rest.keys().eq(&["d"]));
+    /// ```
+    pub fn cursor_mut_at(&mut self, index: usize) -> CursorMut<'_, K, V, S> {
+        assert!(index <= self.len(), "IndexMap: index out of bounds");
+        CursorMut { map: self, index }
+    }
+
+    /// Returns a cursor pointing at the entry for `key`, if it is present.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn cursor_mut_at_key<Q>(&mut self, key: &Q) -> Option<CursorMut<'_, K, V, S>>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+        S: BuildHasher,
+    {
+        let index = self.get_index_of(key)?;
+        Some(CursorMut { map: self, index })
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for CursorMut<'_, K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CursorMut")
+            .field("index", &self.index())
+            .field("current", &self.map.get_index(self.index))
+            .finish()
+    }
+}
+
+impl<'a, K, V, S> CursorMut<'a, K, V, S> {
+    /// Returns the index of the current entry, or `None` at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        if self.index < self.map.len() {
+            Some(self.index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current entry, or `None` at the ghost position.
+    pub fn current(&mut self) -> Option<(&K, &mut V)> {
+        self.map.get_index_mut(self.index)
+    }
+
+    /// Returns the entry after the current one, which is the first entry at
+    /// the ghost position.
+    pub fn peek_next(&mut self) -> Option<(&K, &mut V)> {
+        let next = self.next_index();
+        self.map.get_index_mut(next)
+    }
+
+    /// Returns the entry before the current one, which is the last entry at
+    /// the ghost position.
+    pub fn peek_prev(&mut self) -> Option<(&K, &mut V)> {
+        let prev = self.prev_index();
+        self.map.get_index_mut(prev)
+    }
+
+    /// Moves the cursor to the next entry, or to the ghost position after
+    /// the last entry, or from there to the first entry.
+    pub fn move_next(&mut self) {
+        self.index = self.next_index();
+    }
+
+    /// Moves the cursor to the previous entry, or to the ghost position
+    /// before the first entry, or from there to the last entry.
+    pub fn move_prev(&mut self) {
+        self.index = self.prev_index();
+    }
+
+    fn next_index(&self) -> usize {
+        if self.index == self.map.len() {
+            0
+        } else {
+            self.index + 1
+        }
+    }
+
+    fn prev_index(&self) -> usize {
+        match self.index {
+            0 => self.map.len(),
+            i => i - 1,
+        }
+    }
+
+    /// Removes the current entry and returns it, by shifting all of the
+    /// entries that follow it. The cursor then points at the next entry.
+    ///
+    /// Returns `None` at the ghost position.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        self.map.shift_remove_index(self.index)
+    }
+
+    /// Splits the map after the current entry, returning a new map with all
+    /// of the following entries.
+    ///
+    /// At the ghost position, all entries are moved to the new map.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn split_after(&mut self) -> IndexMap<K, V, S>
+    where
+        S: Clone,
+    {
+        let at = self.next_index();
+        let rest = self.map.split_off(at);
+        self.index = Ord::min(self.index, self.map.len());
+        rest
+    }
+}
+
+impl<'a, K, V, S> CursorMut<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair before the current entry, which is at the end
+    /// of the map at the ghost position. The cursor keeps its entry.
+    ///
+    /// If an equivalent key already exists in the map, its entry is moved
+    /// there and its value is replaced, returning the old value. The key
+    /// itself is not updated.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn insert_before(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, false)
+    }
+
+    /// Inserts a key-value pair after the current entry, which is at the
+    /// start of the map at the ghost position. The cursor keeps its entry.
+    ///
+    /// If an equivalent key already exists in the map, its entry is moved
+    /// there and its value is replaced, returning the old value. The key
+    /// itself is not updated.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn insert_after(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, true)
+    }
+
+    fn insert(&mut self, key: K, value: V, after: bool) -> Option<V> {
+        let len = self.map.len();
+        let ghost = self.index == len;
+        match self.map.entry(key) {
+            Entry::Vacant(entry) => {
+                let to = match (ghost, after) {
+                    (true, true) => 0,
+                    (_, false) => self.index,
+                    (false, true) => self.index + 1,
+                };
+                entry.shift_insert(to, value);
+                if ghost || !after {
+                    self.index += 1;
+                }
+                None
+            }
+            Entry::Occupied(mut entry) => {
+                let old = entry.insert(value);
+                let from = entry.index();
+                if from != self.index {
+                    // Where the current entry is once the other one is taken out.
+                    let current = if from < self.index {
+                        self.index - 1
+                    } else {
+                        self.index
+                    };
+                    let (to, current) = match (ghost, after) {
+                        (true, true) => (0, len),
+                        (_, false) => (current, current + 1),
+                        (false, true) => (current + 1, current),
+                    };
+                    entry.move_index(to);
+                    self.index = current;
+                }
+                Some(old)
+            }
+        }
+    }
+}
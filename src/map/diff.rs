@@ -0,0 +1,206 @@
+//! Structural differences between two [`IndexMap`]s.
+
+use super::{Entries, IndexMap};
+
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash};
+
+/// A minimal edit script transforming one [`IndexMap`] into another.
+///
+/// This `struct` is created by the [`IndexMap::diff`] method, and it can be
+/// applied with [`IndexMap::apply_patch`]. See their documentation for more.
+///
+/// The moved keys are those that are not part of a longest common subsequence
+/// of the keys that both maps have in common, so the keys that keep their
+/// relative order are left in place.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diff<K, V> {
+    pub(crate) removed: Vec<K>,
+    pub(crate) changed: Vec<(K, V)>,
+    pub(crate) moved: Vec<(usize, K)>,
+    pub(crate) inserted: Vec<(usize, K, V)>,
+}
+
+impl<K, V> Diff<K, V> {
+    /// Returns `true` if the diff has no edits, so both maps were equal
+    /// in contents and order.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.moved.is_empty()
+            && self.inserted.is_empty()
+    }
+
+    /// Returns the keys that are only present in the original map.
+    pub fn removed(&self) -> &[K] {
+        &self.removed
+    }
+
+    /// Returns the keys present in both maps with their new value, if
+    /// the value was not equal in the original map.
+    pub fn changed(&self) -> &[(K, V)] {
+        &self.changed
+    }
+
+    /// Returns the keys present in both maps with their new index, if
+    /// they did not keep their relative order, in ascending index order.
+    pub fn moved(&self) -> &[(usize, K)] {
+        &self.moved
+    }
+
+    /// Returns the keys that are only present in the new map with their
+    /// index and value, in ascending index order.
+    pub fn inserted(&self) -> &[(usize, K, V)] {
+        &self.inserted
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: PartialEq + Clone,
+    S: BuildHasher,
+{
+    /// Computes the edits needed to turn this map into `other`, including
+    /// both its key-value pairs and their order.
+    ///
+    /// The keys that both maps have in common are only reported as moved if
+    /// they are not part of a longest common subsequence of the key order.
+    ///
+    /// Computes in **O(n log n)** time (average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let old = IndexMap::from([("a", 1), ("b", 2), ("c", 3), ("d", 4)]);
+    /// let new = IndexMap::from([("b", 2), ("a", 1), ("c", 30), ("e", 5)]);
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.removed(), &["d"]);
+    /// assert_eq!(diff.changed(), &[("c", 30)]);
+    /// assert_eq!(diff.moved(), &[(0, "b")]);
+    /// assert_eq!(diff.inserted(), &[(3, "e", 5)]);
+    ///
+    /// let mut map = old.clone();
+    /// map.apply_patch(diff);
+    /// assert!(map.iter().eq(&new));
+    /// ```
+    pub fn diff<S2>(&self, other: &IndexMap<K, V, S2>) -> Diff<K, V>
+    where
+        S2: BuildHasher,
+    {
+        let removed = self
+            .keys()
+            .filter(|key| !other.contains_key(*key))
+            .cloned()
+            .collect();
+
+        // The indices in `self` of the common keys, in the order of `other`.
+        let mut common = Vec::new();
+        let mut changed = Vec::new();
+        let mut inserted = Vec::new();
+        for (i, (key, value)) in other.iter().enumerate() {
+            match self.get_full(key) {
+                Some((j, _, old_value)) => {
+                    common.push((i, j));
+                    if old_value != value {
+                        changed.push((key.clone(), value.clone()));
+                    }
+                }
+                None => inserted.push((i, key.clone(), value.clone())),
+            }
+        }
+
+        let mut stable = longest_increasing(&common).into_iter().peekable();
+        let mut moved = Vec::new();
+        for (pos, &(i, _)) in common.iter().enumerate() {
+            if stable.next_if_eq(&pos).is_none() {
+                moved.push((i, other.as_entries()[i].key.clone()));
+            }
+        }
+
+        Diff {
+            removed,
+            changed,
+            moved,
+            inserted,
+        }
+    }
+
+    /// Applies the edits of a [`Diff`] to this map.
+    ///
+    /// If the diff was computed from a map with the same contents and order,
+    /// then this map will be equal to the other map of that diff afterward,
+    /// including its order. Keys that are not found for removal or updates
+    /// are ignored.
+    ///
+    /// ***Panics*** if an inserted or moved index is out of bounds, which
+    /// can only happen if the diff did not come from an equal map.
+    ///
+    /// Computes in **O(n m)** time (average) for *m* moves and insertions.
+    pub fn apply_patch(&mut self, patch: Diff<K, V>) {
+        let Diff {
+            removed,
+            changed,
+            moved,
+            inserted,
+        } = patch;
+
+        for key in &removed {
+            self.shift_remove(key);
+        }
+        for (key, value) in changed {
+            if let Some(old_value) = self.get_mut(&key) {
+                *old_value = value;
+            }
+        }
+
+        // Take out the moved entries, so only the stable ones remain in their
+        // relative order, then put everything else back in ascending order.
+        let moved: Vec<_> = moved
+            .into_iter()
+            .filter_map(|(i, key)| {
+                let (key, value) = self.shift_remove_entry(&key)?;
+                Some((i, key, value))
+            })
+            .collect();
+        let mut inserted = inserted.into_iter().peekable();
+        for (i, key, value) in moved {
+            while let Some((j, key, value)) = inserted.next_if(|&(j, ..)| j < i) {
+                self.shift_insert(j, key, value);
+            }
+            self.shift_insert(i, key, value);
+        }
+        for (j, key, value) in inserted {
+            self.shift_insert(j, key, value);
+        }
+    }
+}
+
+/// Find a longest increasing subsequence of the second items in `pairs`,
+/// returning the positions of that subsequence within `pairs`.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<usize> {
+    // `tails[l]` is the position of the smallest tail of a subsequence of length `l + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = Vec::with_capacity(pairs.len());
+    for (pos, &(_, x)) in pairs.iter().enumerate() {
+        let l = tails.partition_point(|&t| pairs[t].1 < x);
+        prev.push(if l > 0 { Some(tails[l - 1]) } else { None });
+        if l == tails.len() {
+            tails.push(pos);
+        } else {
+            tails[l] = pos;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(pos) = next {
+        result.push(pos);
+        next = prev[pos];
+    }
+    result.reverse();
+    result
+}
@@ -0,0 +1,488 @@
+//! A read-only [`IndexMap`] with a perfect hash.
+
+use super::{Bucket, Entries, IndexMap, IntoIter, Iter, Keys, Slice, Values};
+use crate::util::mix;
+use crate::{Equivalent, HashValue};
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::ops::Index;
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+/// A read-only [`IndexMap`], where every lookup needs a single probe.
+///
+/// This `struct` is created by the [`IndexMap::freeze`] method, and it can be
+/// turned back into an [`IndexMap`] with [`thaw`][Self::thaw], both without
+/// hashing any keys again. See their documentation for more.
+///
+/// The hash table of indices is replaced by a perfect hash function over
+/// the stored hashes of the keys, in the style of CHD (compress, hash,
+/// and displace). Each lookup reads one displacement and one slot, and only
+/// compares the key of that slot, unless some keys have equal hashes.
+///
+/// The keys cannot be modified, and nothing can be inserted or removed, but
+/// the values can still be modified in place.
+#[cfg(feature = "std")]
+pub struct FrozenIndexMap<K, V, S = RandomState> {
+    entries: Vec<Bucket<K, V>>,
+    phf: PerfectHash,
+    hash_builder: S,
+}
+#[cfg(not(feature = "std"))]
+pub struct FrozenIndexMap<K, V, S> {
+    entries: Vec<Bucket<K, V>>,
+    phf: PerfectHash,
+    hash_builder: S,
+}
+
+/// The average number of distinct hashes per displacement bucket.
+const BUCKET_LOAD: usize = 2;
+
+/// One slot in this many is left free, for a load factor of about 0.95.
+const LOAD_SLACK: usize = 19;
+
+/// The number of pilots to try for each bucket, before adding more slots.
+const MAX_PILOT: u32 = 1 << 16;
+
+/// An absent index in `same_hash`.
+const NONE: usize = usize::MAX;
+
+/// A perfect hash function from the distinct entry hashes to their first
+/// entry index, with about 5% of the slots left empty.
+#[derive(Clone)]
+struct PerfectHash {
+    /// the seed that places every key of each bucket in a free slot.
+    pilots: Box<[u32]>,
+    /// the index of the first entry with the hash that maps to each slot.
+    slots: Box<[usize]>,
+    /// the next entry index with the same hash as each entry, which is
+    /// empty unless some entries have equal hashes.
+    same_hash: Box<[usize]>,
+}
+
+/// Map `x` onto `0..n` by its high bits.
+#[inline]
+fn reduce(x: u64, n: usize) -> usize {
+    ((u128::from(x) * n as u128) >> 64) as usize
+}
+
+impl PerfectHash {
+    fn new<K, V>(entries: &[Bucket<K, V>]) -> Self {
+        // Find the first entry of each distinct hash, chaining the others.
+        // Sorting by the mixed hash also groups them by their bucket.
+        let mut order: Vec<(u64, usize)> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (mix(entry.hash.get()), i))
+            .collect();
+        order.sort_unstable();
+        let mut distinct: Vec<(u64, usize)> = Vec::with_capacity(order.len());
+        let mut same_hash = Vec::new();
+        let mut last = NONE;
+        for (mixed, i) in order {
+            match distinct.last() {
+                Some(&(first, _)) if first == mixed => {
+                    if same_hash.is_empty() {
+                        same_hash = alloc::vec![NONE; entries.len()];
+                    }
+                    same_hash[last] = i;
+                }
+                _ => distinct.push((mixed, i)),
+            }
+            last = i;
+        }
+
+        let n = distinct.len();
+        let num_buckets = Ord::max(1, (n + BUCKET_LOAD - 1) / BUCKET_LOAD);
+        let buckets: Vec<(usize, u64, usize)> = distinct
+            .into_iter()
+            .map(|(mixed, i)| (reduce(mixed, num_buckets), mixed, i))
+            .collect();
+
+        // Place the largest buckets first, while there are many free slots.
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        while start < buckets.len() {
+            let bucket = buckets[start].0;
+            let len = buckets[start..].partition_point(|&(b, _, _)| b == bucket);
+            ranges.push((bucket, start..start + len));
+            start += len;
+        }
+        ranges.sort_by_key(|(_, range)| Reverse(range.len()));
+
+        // Leave some slots free, so the last buckets quickly find a pilot,
+        // and add more if some bucket finds none at all.
+        let mut num_slots = n + n / LOAD_SLACK + 1;
+        loop {
+            let mut pilots = alloc::vec![0; num_buckets];
+            let mut slots = alloc::vec![NONE; num_slots];
+            // A compact copy of which slots are taken, for faster searches.
+            let mut taken = alloc::vec![0u64; (num_slots + 63) / 64];
+            let mut placed = Vec::new();
+            let all_placed = ranges.iter().all(|(bucket, range)| {
+                let members = &buckets[range.clone()];
+                let pilot = (0..MAX_PILOT).find(|&pilot| {
+                    placed.clear();
+                    members.iter().all(|&(_, mixed, _)| {
+                        let slot = Self::slot(mixed, pilot, num_slots);
+                        let free =
+                            taken[slot / 64] & (1 << (slot % 64)) == 0 && !placed.contains(&slot);
+                        placed.push(slot);
+                        free
+                    })
+                });
+                match pilot {
+                    Some(pilot) => {
+                        pilots[*bucket] = pilot;
+                        for (&slot, &(_, _, i)) in placed.iter().zip(members) {
+                            slots[slot] = i;
+                            taken[slot / 64] |= 1 << (slot % 64);
+                        }
+                        true
+                    }
+                    None => false,
+                }
+            });
+            if all_placed {
+                return PerfectHash {
+                    pilots: pilots.into_boxed_slice(),
+                    slots: slots.into_boxed_slice(),
+                    same_hash: same_hash.into_boxed_slice(),
+                };
+            }
+            num_slots += num_slots / 8;
+        }
+    }
+
+    #[inline]
+    fn slot(mixed: u64, pilot: u32, n: usize) -> usize {
+        let seed = (u64::from(pilot) + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        reduce(mix(mixed ^ seed), n)
+    }
+
+    /// Return the index of the first entry that could have this hash.
+    #[inline]
+    fn first(&self, hash: HashValue) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mixed = mix(hash.get());
+        let pilot = self.pilots[reduce(mixed, self.pilots.len())];
+        match self.slots[Self::slot(mixed, pilot, self.slots.len())] {
+            NONE => None,
+            index => Some(index),
+        }
+    }
+
+    #[inline]
+    fn next(&self, index: usize) -> Option<usize> {
+        match self.same_hash.get(index) {
+            Some(&next) if next != NONE => Some(next),
+            _ => None,
+        }
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Converts the map into a read-only [`FrozenIndexMap`], where lookups
+    /// need a single probe.
+    ///
+    /// The keys are not hashed again, since the perfect hash is built from
+    /// their stored hashes. The entries keep their order and their allocation.
+    ///
+    /// Computes in **O(n log n)** time (average), where sorting the hashes
+    /// takes most of it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let map: IndexMap<_, _> = (0..100).map(|i| (i.to_string(), i)).collect();
+    /// let frozen = map.freeze();
+    /// assert_eq!(frozen.get("42"), Some(&42));
+    /// assert_eq!(frozen.get_index_of("7"), Some(7));
+    /// assert_eq!(frozen.get("100"), None);
+    ///
+    /// let mut map = frozen.thaw();
+    /// map.insert("100".to_string(), 100);
+    /// ```
+    pub fn freeze(self) -> FrozenIndexMap<K, V, S> {
+        let IndexMap { core, hash_builder } = self;
+        let entries = core.into_entries();
+        FrozenIndexMap {
+            phf: PerfectHash::new(&entries),
+            entries,
+            hash_builder,
+        }
+    }
+}
+
+impl<K, V, S> Clone for FrozenIndexMap<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        FrozenIndexMap {
+            entries: self.entries.clone(),
+            phf: self.phf.clone(),
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for FrozenIndexMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> FrozenIndexMap<K, V, S> {
+    /// Converts back into a mutable [`IndexMap`], without hashing the keys again.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn thaw(self) -> IndexMap<K, V, S> {
+        IndexMap::from_entries(self.entries, self.hash_builder)
+    }
+
+    /// Return the number of key-value pairs in the map.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return a reference to the map's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.entries)
+    }
+
+    /// Return an iterator over the keys of the map, in their order
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys::new(&self.entries)
+    }
+
+    /// Return an iterator over the values of the map, in their order
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values::new(&self.entries)
+    }
+
+    /// Returns a slice of all the key-value pairs in the map.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn as_slice(&self) -> &Slice<K, V> {
+        Slice::from_slice(&self.entries)
+    }
+
+    /// Returns a mutable slice of all the key-value pairs in the map.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn as_mut_slice(&mut self) -> &mut Slice<K, V> {
+        Slice::from_mut_slice(&mut self.entries)
+    }
+
+    /// Get a key-value pair by index
+    ///
+    /// Valid indices are *0 <= index < self.len()*
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(Bucket::refs)
+    }
+
+    /// Get a key-value pair by index, with mutable access to the value
+    ///
+    /// Valid indices are *0 <= index < self.len()*
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.entries.get_mut(index).map(Bucket::ref_mut)
+    }
+
+    /// Get the first key-value pair
+    ///
+    /// Computes in **O(1)** time.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.entries.first().map(Bucket::refs)
+    }
+
+    /// Get the last key-value pair
+    ///
+    /// Computes in **O(1)** time.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(Bucket::refs)
+    }
+}
+
+impl<K, V, S> FrozenIndexMap<K, V, S>
+where
+    S: BuildHasher,
+{
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> HashValue {
+        let mut h = self.hash_builder.build_hasher();
+        key.hash(&mut h);
+        HashValue(h.finish() as usize)
+    }
+
+    /// Return `true` if an equivalent to `key` exists in the map.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Return a reference to the value stored for `key`, if it is present,
+    /// else `None`.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.get_index_of(key)?;
+        Some(&self.entries[index].value)
+    }
+
+    /// Return references to the key-value pair stored for `key`,
+    /// if it is present, else `None`.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.get_index_of(key)?;
+        Some(self.entries[index].refs())
+    }
+
+    /// Return item index, key and value
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.get_index_of(key)?;
+        let (key, value) = self.entries[index].refs();
+        Some((index, key, value))
+    }
+
+    /// Return item index, if it exists in the map
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        let hash = self.hash(key);
+        let mut index = self.phf.first(hash)?;
+        loop {
+            let entry = &self.entries[index];
+            if entry.hash != hash {
+                // All entries in the chain have the same hash.
+                return None;
+            }
+            if key.equivalent(&entry.key) {
+                return Some(index);
+            }
+            index = self.phf.next(index)?;
+        }
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is
+    /// present, else `None`.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.get_index_of(key)?;
+        Some(&mut self.entries[index].value)
+    }
+}
+
+impl<K, V, Q: ?Sized, S> Index<&Q> for FrozenIndexMap<K, V, S>
+where
+    Q: Hash + Equivalent<K>,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied `key`.
+    ///
+    /// ***Panics*** if `key` is not present in the map.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("FrozenIndexMap: key not found")
+    }
+}
+
+impl<K, V, S> Index<usize> for FrozenIndexMap<K, V, S> {
+    type Output = V;
+
+    /// Returns a reference to the value at the supplied `index`.
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    fn index(&self, index: usize) -> &V {
+        self.get_index(index)
+            .expect("FrozenIndexMap: index out of bounds")
+            .1
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a FrozenIndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V, S> IntoIterator for FrozenIndexMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.entries)
+    }
+}
+
+impl<K, V, S> From<IndexMap<K, V, S>> for FrozenIndexMap<K, V, S> {
+    fn from(map: IndexMap<K, V, S>) -> Self {
+        map.freeze()
+    }
+}
+
+impl<K, V, S> From<FrozenIndexMap<K, V, S>> for IndexMap<K, V, S> {
+    fn from(map: FrozenIndexMap<K, V, S>) -> Self {
+        map.thaw()
+    }
+}
@@ -0,0 +1,556 @@
+//! Opt-in HashDoS detection for an [`IndexMap`].
+
+use super::{Drain, Entry, IndexMap, IterMut, ValuesMut};
+use crate::util::hash_key;
+use crate::Equivalent;
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::mem;
+use core::ops::{Deref, RangeBounds};
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+/// A [`BuildHasher`] that can change to a different hash function.
+///
+/// This is used by a [`GuardedIndexMap`] when it finds too many keys with
+/// colliding hashes, which may be an attack on a weak hash function.
+pub trait Reseed: BuildHasher {
+    /// Change the hash function, so that all keys must be hashed again.
+    fn reseed(&mut self);
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl Reseed for RandomState {
+    /// Replace the random keys with new ones.
+    fn reseed(&mut self) {
+        *self = RandomState::new();
+    }
+}
+
+/// A [`BuildHasher`] that starts with a fast hasher `S` and switches to a
+/// resistant hasher `F` when it is reseeded.
+///
+/// Reseeding again after the switch replaces `F` with a new default, which
+/// picks new random keys for hashers like [`RandomState`].
+///
+/// # Examples
+///
+/// ```
+/// use fnv::FnvBuildHasher;
+/// use indexmap::map::Fallback;
+/// use indexmap::IndexMap;
+///
+/// let map: IndexMap<u32, u32, Fallback<FnvBuildHasher>> = IndexMap::default();
+/// let mut map = map.guard(8);
+/// map.extend((0..1000).map(|i| (i, i)));
+/// // Fnv has no trouble with these keys.
+/// assert!(!map.hasher().is_fallback());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct Fallback<S, F = RandomState> {
+    choice: Choice<S, F>,
+}
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Debug)]
+pub struct Fallback<S, F> {
+    choice: Choice<S, F>,
+}
+
+#[derive(Clone, Debug)]
+enum Choice<S, F> {
+    Primary(S),
+    Fallback(F),
+}
+
+impl<S, F> Fallback<S, F> {
+    /// Create a hasher that uses `primary` until it is reseeded.
+    pub fn new(primary: S) -> Self {
+        Fallback {
+            choice: Choice::Primary(primary),
+        }
+    }
+
+    /// Return `true` if this has switched to the fallback hasher.
+    pub fn is_fallback(&self) -> bool {
+        matches!(self.choice, Choice::Fallback(_))
+    }
+}
+
+impl<S: Default, F> Default for Fallback<S, F> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S, F> BuildHasher for Fallback<S, F>
+where
+    S: BuildHasher,
+    F: BuildHasher,
+{
+    type Hasher = FallbackHasher<S::Hasher, F::Hasher>;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        FallbackHasher(match &self.choice {
+            Choice::Primary(s) => Choice::Primary(s.build_hasher()),
+            Choice::Fallback(f) => Choice::Fallback(f.build_hasher()),
+        })
+    }
+}
+
+impl<S, F> Reseed for Fallback<S, F>
+where
+    S: BuildHasher,
+    F: BuildHasher + Default,
+{
+    fn reseed(&mut self) {
+        self.choice = Choice::Fallback(F::default());
+    }
+}
+
+/// The [`Hasher`] of a [`Fallback`], using either of its hashers.
+#[derive(Clone, Debug)]
+pub struct FallbackHasher<S, F>(Choice<S, F>);
+
+macro_rules! delegate {
+    ($($method:ident($ty:ty),)*) => {$(
+        fn $method(&mut self, i: $ty) {
+            match &mut self.0 {
+                Choice::Primary(h) => h.$method(i),
+                Choice::Fallback(h) => h.$method(i),
+            }
+        }
+    )*};
+}
+
+impl<S: Hasher, F: Hasher> Hasher for FallbackHasher<S, F> {
+    fn finish(&self) -> u64 {
+        match &self.0 {
+            Choice::Primary(h) => h.finish(),
+            Choice::Fallback(h) => h.finish(),
+        }
+    }
+
+    delegate! {
+        write(&[u8]),
+        write_u8(u8),
+        write_u16(u16),
+        write_u32(u32),
+        write_u64(u64),
+        write_usize(usize),
+    }
+}
+
+/// An [`IndexMap`] that reseeds its hasher when an insertion compares too
+/// many keys.
+///
+/// This `struct` is created by the [`IndexMap::guard`] method, and it can be
+/// turned back into an [`IndexMap`] with [`unguard`][Self::unguard]. See
+/// their documentation for more.
+///
+/// The map dereferences to an [`IndexMap`] for read-only access, and the
+/// mutating methods are its own, so that every insertion is watched.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct GuardedIndexMap<K, V, S = RandomState> {
+    map: IndexMap<K, V, S>,
+    max_probes: usize,
+    reseeds: usize,
+}
+#[cfg(not(feature = "std"))]
+#[derive(Clone)]
+pub struct GuardedIndexMap<K, V, S> {
+    map: IndexMap<K, V, S>,
+    max_probes: usize,
+    reseeds: usize,
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Converts the map into a [`GuardedIndexMap`], which reseeds its hasher
+    /// when an insertion compares more than `max_probes` keys.
+    ///
+    /// Finding a key in the hash table compares it with every key whose hash
+    /// is similar enough, which are very few with a good hash function. Keys
+    /// that were crafted to collide under a fast hash function like FNV or Fx
+    /// can instead make every lookup compare them all, taking **O(n)** time.
+    /// When a guarded map sees that while inserting a key, it calls
+    /// [`Reseed::reseed`] and hashes all keys again, without changing the
+    /// order or indices of the entries.
+    ///
+    /// The limit doubles after each reseed, so keys that collide under every
+    /// seed can't make each insertion rehash the map. Clones of the guarded
+    /// map keep that state, and [`unguard`][GuardedIndexMap::unguard] drops
+    /// it. Lookups that don't insert are not watched, since only insertions
+    /// can add colliding keys.
+    ///
+    /// Neither the map nor its entries are moved or hashed again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::hash::{BuildHasherDefault, Hasher};
+    /// use indexmap::map::Fallback;
+    /// use indexmap::IndexMap;
+    ///
+    /// // A hasher so weak that every key collides.
+    /// #[derive(Default)]
+    /// struct Weak;
+    ///
+    /// impl Hasher for Weak {
+    ///     fn finish(&self) -> u64 {
+    ///         0
+    ///     }
+    ///
+    ///     fn write(&mut self, _: &[u8]) {}
+    /// }
+    ///
+    /// let map: IndexMap<u32, u32, Fallback<BuildHasherDefault<Weak>>> = IndexMap::default();
+    /// let mut map = map.guard(16);
+    /// for i in 0..100 {
+    ///     *map.entry(i).or_default() += i;
+    /// }
+    /// assert_eq!(map.reseeds(), 1);
+    /// assert_eq!(map.max_probes(), 32);
+    /// assert!(map.hasher().is_fallback());
+    ///
+    /// let map = map.unguard();
+    /// assert!(map.keys().copied().eq(0..100));
+    /// assert_eq!(map.get_index_of(&42), Some(42));
+    /// ```
+    pub fn guard(self, max_probes: usize) -> GuardedIndexMap<K, V, S> {
+        GuardedIndexMap {
+            map: self,
+            max_probes,
+            reseeds: 0,
+        }
+    }
+}
+
+impl<K, V, S> Deref for GuardedIndexMap<K, V, S> {
+    type Target = IndexMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K, V, S> fmt::Debug for GuardedIndexMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GuardedIndexMap")
+            .field("map", &self.map)
+            .field("max_probes", &self.max_probes)
+            .field("reseeds", &self.reseeds)
+            .finish()
+    }
+}
+
+impl<K, V, S> GuardedIndexMap<K, V, S> {
+    /// Converts back into an unguarded [`IndexMap`], forgetting the limit and
+    /// the number of reseeds.
+    pub fn unguard(self) -> IndexMap<K, V, S> {
+        self.map
+    }
+
+    /// Returns the number of keys an insertion may compare before the map
+    /// reseeds its hasher.
+    pub fn max_probes(&self) -> usize {
+        self.max_probes
+    }
+
+    /// Sets the number of keys an insertion may compare before the map
+    /// reseeds its hasher.
+    pub fn set_max_probes(&mut self, max_probes: usize) {
+        self.max_probes = max_probes;
+    }
+
+    /// Returns how many times the map has reseeded its hasher since it was
+    /// guarded.
+    pub fn reseeds(&self) -> usize {
+        self.reseeds
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order
+    ///
+    /// See [`IndexMap::iter_mut`].
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.map.iter_mut()
+    }
+
+    /// Return an iterator over mutable references to the values of the map,
+    /// in their order
+    ///
+    /// See [`IndexMap::values_mut`].
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.map.values_mut()
+    }
+
+    /// Get a key-value pair by index, with a mutable value
+    ///
+    /// See [`IndexMap::get_index_mut`].
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.map.get_index_mut(index)
+    }
+
+    /// Remove all key-value pairs in the map, while preserving its capacity.
+    ///
+    /// See [`IndexMap::clear`].
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Shortens the map, keeping the first `len` elements and dropping the rest.
+    ///
+    /// See [`IndexMap::truncate`].
+    pub fn truncate(&mut self, len: usize) {
+        self.map.truncate(len);
+    }
+
+    /// Clears the key-value pairs in the given range, returning them in a
+    /// draining iterator.
+    ///
+    /// See [`IndexMap::drain`].
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, K, V>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.map.drain(range)
+    }
+
+    /// Remove the last key-value pair
+    ///
+    /// See [`IndexMap::pop`].
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        self.map.pop()
+    }
+
+    /// Scan through each key-value pair in the map and keep those where the
+    /// closure `keep` returns `true`.
+    ///
+    /// See [`IndexMap::retain`].
+    pub fn retain<F>(&mut self, keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.map.retain(keep);
+    }
+
+    /// Sort the map's key-value pairs in place using the comparison function `cmp`.
+    ///
+    /// See [`IndexMap::sort_by`].
+    pub fn sort_by<F>(&mut self, cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.map.sort_by(cmp);
+    }
+
+    /// Reverses the order of the map's key-value pairs in place.
+    ///
+    /// See [`IndexMap::reverse`].
+    pub fn reverse(&mut self) {
+        self.map.reverse();
+    }
+
+    /// Remove the key-value pair by index, replacing it with the last one.
+    ///
+    /// See [`IndexMap::swap_remove_index`].
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.map.swap_remove_index(index)
+    }
+
+    /// Remove the key-value pair by index, shifting all that follow it.
+    ///
+    /// See [`IndexMap::shift_remove_index`].
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.map.shift_remove_index(index)
+    }
+
+    /// Moves the position of a key-value pair from one index to another
+    /// by shifting all other pairs in-between.
+    ///
+    /// See [`IndexMap::move_index`].
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.map.move_index(from, to);
+    }
+
+    /// Swaps the position of two key-value pairs in the map.
+    ///
+    /// See [`IndexMap::swap_indices`].
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.map.swap_indices(a, b);
+    }
+}
+
+impl<K, V, S> GuardedIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Reserve capacity for `additional` more key-value pairs.
+    ///
+    /// See [`IndexMap::reserve`].
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    /// Shrink the capacity of the map as much as possible.
+    ///
+    /// See [`IndexMap::shrink_to_fit`].
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit();
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is present.
+    ///
+    /// See [`IndexMap::get_mut`].
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// Remove the key-value pair equivalent to `key` and return its value,
+    /// replacing it with the last one.
+    ///
+    /// See [`IndexMap::swap_remove`].
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.swap_remove(key)
+    }
+
+    /// Remove the key-value pair equivalent to `key` and return its value,
+    /// shifting all that follow it.
+    ///
+    /// See [`IndexMap::shift_remove`].
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.shift_remove(key)
+    }
+
+    /// Sort the map's key-value pairs by the default ordering of the keys.
+    ///
+    /// See [`IndexMap::sort_keys`].
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.map.sort_keys();
+    }
+}
+
+impl<K, V, S> GuardedIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: Reseed,
+{
+    /// Reseed the hasher if an insertion compared more than `max_probes` keys.
+    fn check_probes(&mut self, probes: usize) {
+        if probes > self.max_probes {
+            self.map.hash_builder.reseed();
+            let hash_builder = &self.map.hash_builder;
+            self.map.core.rehash(|key| hash_key(hash_builder, key));
+            self.max_probes = Ord::max(self.max_probes.saturating_mul(2), 1);
+            self.reseeds += 1;
+        }
+    }
+
+    /// Look up a key that is about to be inserted, and reseed if needed.
+    fn watch(&mut self, key: &K) {
+        let hash = self.map.hash(key);
+        let mut probes = 0;
+        (self.map.core).get_index_of_counted(hash, key, &mut probes);
+        self.check_probes(probes);
+    }
+
+    /// Insert a key-value pair in the map.
+    ///
+    /// See [`IndexMap::insert`].
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Insert a key-value pair in the map, and get their index.
+    ///
+    /// See [`IndexMap::insert_full`].
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        let hash = self.map.hash(&key);
+        let mut probes = 0;
+        let result = (self.map.core).insert_full_counted(hash, key, value, &mut probes);
+        self.check_probes(probes);
+        result
+    }
+
+    /// Insert a key-value pair in the map at its ordered position among sorted keys.
+    ///
+    /// See [`IndexMap::insert_sorted`].
+    pub fn insert_sorted(&mut self, key: K, value: V) -> (usize, Option<V>)
+    where
+        K: Ord,
+    {
+        match self.map.binary_search_keys(&key) {
+            Ok(i) => (i, Some(mem::replace(&mut self.map[i], value))),
+            Err(i) => (i, self.shift_insert(i, key, value)),
+        }
+    }
+
+    /// Insert a key-value pair in the map at the given index.
+    ///
+    /// See [`IndexMap::shift_insert`].
+    pub fn shift_insert(&mut self, index: usize, key: K, value: V) -> Option<V> {
+        self.watch(&key);
+        self.map.shift_insert(index, key, value)
+    }
+
+    /// Get the given key's corresponding entry in the map for insertion and/or
+    /// in-place manipulation.
+    ///
+    /// The key is looked up before the entry is returned, so the map may
+    /// reseed then, but not when a vacant entry is inserted.
+    ///
+    /// See [`IndexMap::entry`].
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        self.watch(&key);
+        self.map.entry(key)
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for GuardedIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: Reseed,
+{
+    /// Extend the map with all key-value pairs in the iterable.
+    ///
+    /// See [`IndexMap::extend`].
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iterable: I) {
+        let iter = iterable.into_iter();
+        let reserve = if self.is_empty() {
+            iter.size_hint().0
+        } else {
+            (iter.size_hint().0 + 1) / 2
+        };
+        self.reserve(reserve);
+        iter.for_each(move |(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+impl<K, V, S> From<GuardedIndexMap<K, V, S>> for IndexMap<K, V, S> {
+    fn from(map: GuardedIndexMap<K, V, S>) -> Self {
+        map.unguard()
+    }
+}
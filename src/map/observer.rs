@@ -0,0 +1,1032 @@
+//! Opt-in notifications of index changes in an [`IndexMap`].
+
+mod cursor;
+mod entry;
+mod raw_entry_v1;
+
+pub use self::cursor::ObservedCursorMut;
+pub use self::entry::{
+    ObservedEntry, ObservedIndexedEntry, ObservedOccupiedEntry, ObservedVacantEntry,
+};
+pub use self::raw_entry_v1::{
+    ObservedRawEntryBuilderMut, ObservedRawEntryMut, ObservedRawOccupiedEntryMut,
+    ObservedRawVacantEntryMut,
+};
+
+use super::{ConflictError, Diff, Entries, Entry, IndexMap, RawEntryApiV1, RenameError};
+use crate::{Equivalent, Hashed};
+
+use alloc::vec::{self, Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::ops::{Deref, Range, RangeBounds};
+
+use crate::util::{hash_key, permute, simplify_range};
+
+/// An observer of the changes to the entry indices of an [`IndexMap`] or
+/// [`IndexSet`][crate::IndexSet].
+///
+/// Observers are used through the [`Observed`] view of a map, created by
+/// [`IndexMap::observed`], or its set counterpart [`IndexSet::observed`][crate::IndexSet::observed].
+/// Maps that are not observed do not pay anything for this.
+///
+/// All notifications for a single operation describe it as a whole: `removed`
+/// and the `old` side of `relocated` are indices from *before* the operation,
+/// while `inserted` and the `new` side of `relocated` are indices from
+/// *after* the operation. Any index that is not mentioned keeps its entry.
+///
+/// All methods do nothing by default.
+pub trait IndexObserver {
+    /// A new entry was inserted at `index`.
+    fn inserted(&mut self, index: usize) {
+        let _ = index;
+    }
+
+    /// The entry at `index` was removed.
+    fn removed(&mut self, index: usize) {
+        let _ = index;
+    }
+
+    /// The entry at `old` was moved to `new`.
+    fn relocated(&mut self, old: usize, new: usize) {
+        let _ = (old, new);
+    }
+}
+
+impl<O: ?Sized + IndexObserver> IndexObserver for &mut O {
+    fn inserted(&mut self, index: usize) {
+        (**self).inserted(index);
+    }
+
+    fn removed(&mut self, index: usize) {
+        (**self).removed(index);
+    }
+
+    fn relocated(&mut self, old: usize, new: usize) {
+        (**self).relocated(old, new);
+    }
+}
+
+/// Report that the entries in `range` moved one index up or down.
+fn shifted<O: IndexObserver>(observer: &mut O, range: Range<usize>, up: bool) {
+    for i in range {
+        let new = if up { i + 1 } else { i - 1 };
+        observer.relocated(i, new);
+    }
+}
+
+/// Report an insertion at `index` of a map that had `len` entries.
+fn shift_inserted<O: IndexObserver>(observer: &mut O, index: usize, len: usize) {
+    shifted(observer, index..len, true);
+    observer.inserted(index);
+}
+
+/// Report a removal at `index` by swapping, from a map that had `len` entries.
+fn swap_removed<O: IndexObserver>(observer: &mut O, index: usize, len: usize) {
+    observer.removed(index);
+    if index < len - 1 {
+        observer.relocated(len - 1, index);
+    }
+}
+
+/// Report a removal at `index` by shifting, from a map that had `len` entries.
+fn shift_removed<O: IndexObserver>(observer: &mut O, index: usize, len: usize) {
+    observer.removed(index);
+    shifted(observer, index + 1..len, false);
+}
+
+/// Report a move from one index to another by shifting the others.
+fn moved<O: IndexObserver>(observer: &mut O, from: usize, to: usize) {
+    if from != to {
+        observer.relocated(from, to);
+        if from < to {
+            shifted(observer, from + 1..to + 1, false);
+        } else {
+            shifted(observer, to..from, true);
+        }
+    }
+}
+
+/// Report that two entries swapped their indices.
+fn swapped<O: IndexObserver>(observer: &mut O, a: usize, b: usize) {
+    if a != b {
+        observer.relocated(a, b);
+        observer.relocated(b, a);
+    }
+}
+
+/// A mutable view of an [`IndexMap`] that reports every change of the
+/// entry indices to an [`IndexObserver`].
+///
+/// This `struct` is created by the [`IndexMap::observed`] method.
+/// See its documentation for more.
+///
+/// The view dereferences to the map for read-only access, and each of its
+/// own mutating methods reports its changes once they are applied. Changes
+/// made to the map directly, while it is not borrowed by a view, are not
+/// reported.
+pub struct Observed<'a, K, V, S, O> {
+    map: &'a mut IndexMap<K, V, S>,
+    observer: O,
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Returns a mutable view of the map that reports all index changes to
+    /// `observer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    /// use indexmap::map::IndexObserver;
+    ///
+    /// #[derive(Default)]
+    /// struct Relocations(Vec<(usize, usize)>);
+    ///
+    /// impl IndexObserver for Relocations {
+    ///     fn relocated(&mut self, old: usize, new: usize) {
+    ///         self.0.push((old, new));
+    ///     }
+    /// }
+    ///
+    /// let mut map = IndexMap::from([("a", 1), ("b", 2), ("c", 3)]);
+    /// let mut relocations = Relocations::default();
+    /// map.observed(&mut relocations).swap_remove("a");
+    /// assert_eq!(relocations.0, [(2, 0)]);
+    /// ```
+    pub fn observed<O: IndexObserver>(&mut self, observer: O) -> Observed<'_, K, V, S, O> {
+        Observed {
+            map: self,
+            observer,
+        }
+    }
+}
+
+impl<K, V, S, O> Deref for Observed<'_, K, V, S, O> {
+    type Target = IndexMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.map
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S, O> fmt::Debug for Observed<'_, K, V, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Observed").field(&*self.map).finish()
+    }
+}
+
+impl<'a, K, V, S, O> Observed<'a, K, V, S, O>
+where
+    O: IndexObserver,
+{
+    /// Returns a reference to the observer.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Returns a mutable reference to the observer.
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Consumes the view, returning the observer.
+    pub fn into_observer(self) -> O {
+        self.observer
+    }
+
+    /// Get a key-value pair by index, with mutable access to the value.
+    ///
+    /// See [`IndexMap::get_index_mut`].
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.map.get_index_mut(index)
+    }
+
+    /// Remove the last key-value pair.
+    ///
+    /// See [`IndexMap::pop`].
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let popped = self.map.pop()?;
+        self.observer.removed(self.map.len());
+        Some(popped)
+    }
+
+    /// Remove all key-value pairs in the map.
+    ///
+    /// See [`IndexMap::clear`].
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Shortens the map, keeping the first `len` elements and dropping the rest.
+    ///
+    /// See [`IndexMap::truncate`].
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.map.len();
+        self.map.truncate(len);
+        for i in len..old_len {
+            self.observer.removed(i);
+        }
+    }
+
+    /// Clears the given index range, returning those key-value pairs in an
+    /// iterator.
+    ///
+    /// Unlike [`IndexMap::drain`], the pairs are removed right away, so the
+    /// change can be reported before this returns.
+    pub fn drain<R>(&mut self, range: R) -> vec::IntoIter<(K, V)>
+    where
+        R: RangeBounds<usize>,
+    {
+        let old_len = self.map.len();
+        let range = simplify_range(range, old_len);
+        let drained: Vec<_> = self.map.drain(range.clone()).collect();
+        for i in range.clone() {
+            self.observer.removed(i);
+        }
+        let shift = range.len();
+        for i in range.end..old_len {
+            self.observer.relocated(i, i - shift);
+        }
+        drained.into_iter()
+    }
+
+    /// Splits the map in two at the given index, and reports the entries
+    /// that were moved to the new map as removed.
+    ///
+    /// See [`IndexMap::split_off`].
+    pub fn split_off(&mut self, at: usize) -> IndexMap<K, V, S>
+    where
+        S: Clone,
+    {
+        let len = self.map.len();
+        let rest = self.map.split_off(at);
+        for i in at..len {
+            self.observer.removed(i);
+        }
+        rest
+    }
+
+    /// Remove the key-value pair by index, swapping it with the last element.
+    ///
+    /// See [`IndexMap::swap_remove_index`].
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let removed = self.map.swap_remove_index(index)?;
+        swap_removed(&mut self.observer, index, self.map.len() + 1);
+        Some(removed)
+    }
+
+    /// Remove the key-value pair by index, shifting all elements that follow it.
+    ///
+    /// See [`IndexMap::shift_remove_index`].
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let removed = self.map.shift_remove_index(index)?;
+        shift_removed(&mut self.observer, index, self.map.len() + 1);
+        Some(removed)
+    }
+
+    /// Moves the position of a key-value pair from one index to another.
+    ///
+    /// See [`IndexMap::move_index`].
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.map.move_index(from, to);
+        moved(&mut self.observer, from, to);
+    }
+
+    /// Swaps the position of two key-value pairs in the map.
+    ///
+    /// See [`IndexMap::swap_indices`].
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.map.swap_indices(a, b);
+        swapped(&mut self.observer, a, b);
+    }
+
+    /// Remove the key-value pairs at all of these indices, filling their
+    /// places with the pairs from the end of the map.
+    ///
+    /// See [`IndexMap::swap_remove_indices`].
+    pub fn swap_remove_indices(&mut self, indices: &[usize]) -> Vec<(K, V)> {
+        let len = self.map.len();
+        let removed = self.map.swap_remove_indices(indices);
+        let sorted = sorted_indices(indices);
+        let end = len - sorted.len();
+        let holes = sorted.iter().take_while(|&&i| i < end);
+        let movers = (end..len).filter(|i| sorted.binary_search(i).is_err());
+        for &i in &sorted {
+            self.observer.removed(i);
+        }
+        for (&hole, from) in holes.zip(movers) {
+            self.observer.relocated(from, hole);
+        }
+        removed
+    }
+
+    /// Remove the key-value pairs at all of these indices, shifting all
+    /// elements that follow them.
+    ///
+    /// See [`IndexMap::shift_remove_indices`].
+    pub fn shift_remove_indices(&mut self, indices: &[usize]) -> Vec<(K, V)> {
+        let len = self.map.len();
+        let removed = self.map.shift_remove_indices(indices);
+        let sorted = sorted_indices(indices);
+        let mut next = sorted.iter().peekable();
+        for i in sorted.first().map_or(len, |&start| start)..len {
+            if next.next_if_eq(&&i).is_some() {
+                self.observer.removed(i);
+            } else {
+                let shift = sorted.len() - next.len();
+                self.observer.relocated(i, i - shift);
+            }
+        }
+        removed
+    }
+
+    /// Keep the key-value pairs where the closure `keep` returns `true`.
+    ///
+    /// See [`IndexMap::retain`].
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let kept: Vec<bool> = self.map.iter_mut().map(|(k, v)| keep(k, v)).collect();
+        let mut flags = kept.iter();
+        self.map.retain(|_, _| *flags.next().unwrap());
+
+        let mut new = 0;
+        for (old, &kept) in kept.iter().enumerate() {
+            if !kept {
+                self.observer.removed(old);
+            } else {
+                if old != new {
+                    self.observer.relocated(old, new);
+                }
+                new += 1;
+            }
+        }
+    }
+
+    /// Reverses the order of the map’s key-value pairs in place.
+    ///
+    /// See [`IndexMap::reverse`].
+    pub fn reverse(&mut self) {
+        self.map.reverse();
+        let len = self.map.len();
+        for i in 0..len {
+            let new = len - i - 1;
+            if i != new {
+                self.observer.relocated(i, new);
+            }
+        }
+    }
+
+    /// Sort the map’s key-value pairs by the default ordering of the keys.
+    ///
+    /// See [`IndexMap::sort_keys`].
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| K::cmp(k1, k2));
+    }
+
+    /// Sort the map’s key-value pairs in place using the comparison function `cmp`.
+    ///
+    /// See [`IndexMap::sort_by`].
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        let entries = self.map.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&entries[a], &entries[b]);
+            cmp(&a.key, &a.value, &b.key, &b.value)
+        });
+        self.permute(&order);
+    }
+
+    /// Sort the map's key-value pairs by the default ordering of the keys, but
+    /// may not preserve the order of equal elements.
+    ///
+    /// See [`IndexMap::sort_unstable_keys`].
+    pub fn sort_unstable_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_unstable_by(|k1, _, k2, _| K::cmp(k1, k2));
+    }
+
+    /// Sort the map's key-value pairs in place using the comparison function `cmp`, but
+    /// may not preserve the order of equal elements.
+    ///
+    /// See [`IndexMap::sort_unstable_by`].
+    pub fn sort_unstable_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        let entries = self.map.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_unstable_by(|&a, &b| {
+            let (a, b) = (&entries[a], &entries[b]);
+            cmp(&a.key, &a.value, &b.key, &b.value)
+        });
+        self.permute(&order);
+    }
+
+    /// Sort the map’s key-value pairs in place using a sort-key extraction function.
+    ///
+    /// See [`IndexMap::sort_by_cached_key`].
+    pub fn sort_by_cached_key<T, F>(&mut self, mut sort_key: F)
+    where
+        T: Ord,
+        F: FnMut(&K, &V) -> T,
+    {
+        let entries = self.map.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_by_cached_key(|&i| sort_key(&entries[i].key, &entries[i].value));
+        self.permute(&order);
+    }
+
+    /// Creates a raw entry builder for the map, whose entries report their
+    /// index changes.
+    ///
+    /// See [`RawEntryApiV1::raw_entry_mut_v1`].
+    pub fn raw_entry_mut_v1(&mut self) -> ObservedRawEntryBuilderMut<'_, K, V, S, O> {
+        let len = self.map.len();
+        ObservedRawEntryBuilderMut::new(self.map.raw_entry_mut_v1(), len, &mut self.observer)
+    }
+
+    /// Get an entry in the map by index for in-place manipulation, which
+    /// reports its index changes.
+    ///
+    /// See [`IndexMap::get_index_entry`].
+    pub fn get_index_entry(&mut self, index: usize) -> Option<ObservedIndexedEntry<'_, K, V, O>> {
+        let len = self.map.len();
+        let inner = self.map.get_index_entry(index)?;
+        Some(ObservedIndexedEntry::new(inner, len, &mut self.observer))
+    }
+
+    /// Returns a cursor pointing at the entry at `index`, which reports the
+    /// index changes of its edits.
+    ///
+    /// See [`IndexMap::cursor_mut_at`].
+    pub fn cursor_mut_at(&mut self, index: usize) -> ObservedCursorMut<'_, K, V, S, O> {
+        ObservedCursorMut::new(self.map.cursor_mut_at(index), &mut self.observer)
+    }
+
+    /// Reorder the entries so that the new index `i` holds the old entry `order[i]`.
+    pub(crate) fn permute(&mut self, order: &[usize]) {
+        self.map.with_entries(|entries| permute(entries, order));
+        for (new, &old) in order.iter().enumerate() {
+            if old != new {
+                self.observer.relocated(old, new);
+            }
+        }
+    }
+}
+
+impl<'a, K, V, S, O> Observed<'a, K, V, S, O>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    O: IndexObserver,
+{
+    /// Insert a key-value pair in the map.
+    ///
+    /// See [`IndexMap::insert`].
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Insert a key-value pair in the map with a precomputed hash.
+    ///
+    /// See [`IndexMap::insert_hashed`].
+    pub fn insert_hashed(&mut self, key: Hashed<K>, value: V) -> Option<V> {
+        self.insert_full_hashed(key, value).1
+    }
+
+    /// Insert a key-value pair in the map with a precomputed hash, and get
+    /// their index.
+    ///
+    /// See [`IndexMap::insert_full_hashed`].
+    pub fn insert_full_hashed(&mut self, key: Hashed<K>, value: V) -> (usize, Option<V>) {
+        let (index, old) = self.map.insert_full_hashed(key, value);
+        if old.is_none() {
+            self.observer.inserted(index);
+        }
+        (index, old)
+    }
+
+    /// Insert a key-value pair in the map, and get their index.
+    ///
+    /// See [`IndexMap::insert_full`].
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        let (index, old) = self.map.insert_full(key, value);
+        if old.is_none() {
+            self.observer.inserted(index);
+        }
+        (index, old)
+    }
+
+    /// Get the given key’s corresponding entry in the map for insertion and/or
+    /// in-place manipulation, which reports its index changes.
+    ///
+    /// See [`IndexMap::entry`].
+    pub fn entry(&mut self, key: K) -> ObservedEntry<'_, K, V, O> {
+        let len = self.map.len();
+        let observer = &mut self.observer;
+        match self.map.entry(key) {
+            Entry::Occupied(inner) => {
+                ObservedEntry::Occupied(ObservedOccupiedEntry::new(inner, len, observer))
+            }
+            Entry::Vacant(inner) => {
+                ObservedEntry::Vacant(ObservedVacantEntry::new(inner, observer))
+            }
+        }
+    }
+
+    /// Get the entry of a key with a precomputed hash, which reports its
+    /// index changes.
+    ///
+    /// See [`IndexMap::entry_hashed`].
+    pub fn entry_hashed(&mut self, key: Hashed<K>) -> ObservedEntry<'_, K, V, O> {
+        let len = self.map.len();
+        let observer = &mut self.observer;
+        match self.map.entry_hashed(key) {
+            Entry::Occupied(inner) => {
+                ObservedEntry::Occupied(ObservedOccupiedEntry::new(inner, len, observer))
+            }
+            Entry::Vacant(inner) => {
+                ObservedEntry::Vacant(ObservedVacantEntry::new(inner, observer))
+            }
+        }
+    }
+
+    /// Returns a cursor pointing at the entry for `key`, if it is present,
+    /// which reports the index changes of its edits.
+    ///
+    /// See [`IndexMap::cursor_mut_at_key`].
+    pub fn cursor_mut_at_key<Q>(&mut self, key: &Q) -> Option<ObservedCursorMut<'_, K, V, S, O>>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let cursor = self.map.cursor_mut_at_key(key)?;
+        Some(ObservedCursorMut::new(cursor, &mut self.observer))
+    }
+
+    /// Insert a key-value pair in the map at its ordered position among sorted keys.
+    ///
+    /// See [`IndexMap::insert_sorted`].
+    pub fn insert_sorted(&mut self, key: K, value: V) -> (usize, Option<V>)
+    where
+        K: Ord,
+    {
+        match self.map.binary_search_keys(&key) {
+            Ok(i) => (i, Some(core::mem::replace(&mut self.map[i], value))),
+            Err(i) => (i, self.shift_insert(i, key, value)),
+        }
+    }
+
+    /// Insert a key-value pair in the map at the given index.
+    ///
+    /// See [`IndexMap::shift_insert`].
+    pub fn shift_insert(&mut self, index: usize, key: K, value: V) -> Option<V> {
+        match self.map.get_index_of(&key) {
+            Some(from) => {
+                let old = core::mem::replace(&mut self.map[from], value);
+                self.move_index(from, index);
+                Some(old)
+            }
+            None => {
+                let old_len = self.map.len();
+                self.map.shift_insert(index, key, value);
+                shift_inserted(&mut self.observer, index, old_len);
+                None
+            }
+        }
+    }
+
+    /// Insert a key-value pair in the map before the entry at the given index,
+    /// or at the end if the index is equal to the length of the map.
+    ///
+    /// If an equivalent key already exists in the map, its value is replaced
+    /// and the entry is moved just before the entry at `index`, which is one
+    /// index lower if it moved up. The index it ends up at is returned with
+    /// the old value.
+    ///
+    /// ***Panics*** if `index` is greater than the length of the map.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn insert_before(&mut self, index: usize, key: K, value: V) -> (usize, Option<V>) {
+        assert!(index <= self.map.len(), "index out of bounds");
+        match self.map.get_index_of(&key) {
+            Some(from) => {
+                let to = if from < index { index - 1 } else { index };
+                let old = core::mem::replace(&mut self.map[from], value);
+                self.move_index(from, to);
+                (to, Some(old))
+            }
+            None => (index, self.shift_insert(index, key, value)),
+        }
+    }
+
+    /// Replaces the key at `index`, keeping its position and value.
+    ///
+    /// This doesn't change any index, so nothing is reported.
+    ///
+    /// See [`IndexMap::replace_key`].
+    pub fn replace_key(&mut self, index: usize, new_key: K) -> Result<K, ConflictError<K>> {
+        self.map.replace_key(index, new_key)
+    }
+
+    /// Replaces the key equivalent to `old` with `new_key`, keeping its
+    /// position and value.
+    ///
+    /// This doesn't change any index, so nothing is reported.
+    ///
+    /// See [`IndexMap::rename_key`].
+    pub fn rename_key<Q>(&mut self, old: &Q, new_key: K) -> Result<K, RenameError<K>>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.rename_key(old, new_key)
+    }
+
+    /// Recomputes the hash of every key, removing the entries whose keys
+    /// became equal to an earlier one.
+    ///
+    /// See [`IndexMap::rehash_keys`].
+    pub fn rehash_keys(&mut self) -> Vec<(usize, K, V)> {
+        let len = self.map.len();
+        let mut duplicates = Vec::new();
+        let hash_builder = &self.map.hash_builder;
+        let removed = self.map.core.rehash_dedup(
+            |key| hash_key(hash_builder, key),
+            |index| duplicates.push(index),
+        );
+        let mut next = duplicates.iter().peekable();
+        for i in duplicates.first().map_or(len, |&start| start)..len {
+            if next.next_if_eq(&&i).is_some() {
+                self.observer.removed(i);
+            } else {
+                let shift = duplicates.len() - next.len();
+                self.observer.relocated(i, i - shift);
+            }
+        }
+        removed
+    }
+
+    /// Applies the edits of a [`Diff`] to this map, and reports them as a
+    /// single change.
+    ///
+    /// See [`IndexMap::apply_patch`].
+    pub fn apply_patch(&mut self, patch: Diff<K, V>) {
+        let Diff {
+            removed,
+            changed,
+            moved,
+            inserted,
+        } = patch;
+
+        // Follow the steps of `IndexMap::apply_patch`, and sum them up at the end.
+        let mut batch = Batch::new(self.map.len());
+        for key in &removed {
+            batch.step(self.map, |map| map.shift_remove(key));
+        }
+        for (key, value) in changed {
+            if let Some(old_value) = self.map.get_mut(&key) {
+                *old_value = value;
+            }
+        }
+        // A moved pair keeps its original index when it is put back.
+        let moved: Vec<_> = moved
+            .into_iter()
+            .filter_map(|(i, key)| {
+                let index = self.map.get_index_of(&key)?;
+                let origin = batch.origin[index];
+                let (key, value) = batch.step(self.map, |map| map.shift_remove_index(index))?;
+                Some((i, key, value, origin))
+            })
+            .collect();
+        let mut inserted = inserted.into_iter().peekable();
+        for (i, key, value, origin) in moved {
+            while let Some((j, key, value)) = inserted.next_if(|&(j, ..)| j < i) {
+                batch.step(self.map, |map| map.shift_insert(j, key, value));
+            }
+            batch.step(self.map, |map| map.shift_insert(i, key, value));
+            batch.origin[i] = origin;
+        }
+        for (j, key, value) in inserted {
+            batch.step(self.map, |map| map.shift_insert(j, key, value));
+        }
+        batch.report(&mut self.observer);
+    }
+
+    /// Removes the given range of key-value pairs and inserts the pairs of
+    /// `replace_with` in their place, returning the removed pairs.
+    ///
+    /// Unlike [`IndexMap::splice`], the removed pairs are collected and
+    /// `replace_with` is consumed right away, so the whole change can be
+    /// reported at once.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Vec<(K, V)>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let len = self.map.len();
+        let range = simplify_range(range, len);
+        let removed: Vec<_> = self.map.splice(range.clone(), replace_with).collect();
+        let start = range.start;
+        let inserted = self.map.len() + range.len() - len;
+        for i in range.clone() {
+            self.observer.removed(i);
+        }
+        for i in start..start + inserted {
+            self.observer.inserted(i);
+        }
+        for i in range.end..len {
+            let new = i + inserted - range.len();
+            if i != new {
+                self.observer.relocated(i, new);
+            }
+        }
+        removed
+    }
+
+    /// Moves all key-value pairs from `other` into the map, leaving `other`
+    /// empty.
+    ///
+    /// Keys that are already in the map keep their position and get the new
+    /// value, like [`insert`][Self::insert], while the others are appended in
+    /// their order in `other`.
+    pub fn append<S2>(&mut self, other: &mut IndexMap<K, V, S2>) {
+        self.extend(other.drain(..));
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is present.
+    ///
+    /// See [`IndexMap::get_mut`].
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// Remove the key-value pair equivalent to `key`, swapping it with the last element.
+    ///
+    /// See [`IndexMap::swap_remove`].
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.swap_remove_full(key).map(|(_, _, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key`, swapping it with the
+    /// last element, and return it and the index it had.
+    ///
+    /// See [`IndexMap::swap_remove_full`].
+    pub fn swap_remove_full<Q>(&mut self, key: &Q) -> Option<(usize, K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of(key)?;
+        let (key, value) = self.swap_remove_index(index)?;
+        Some((index, key, value))
+    }
+
+    /// Remove and return the key-value pair equivalent to `key`, swapping it
+    /// with the last element.
+    ///
+    /// See [`IndexMap::swap_remove_entry`].
+    pub fn swap_remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.swap_remove_full(key)
+            .map(|(_, key, value)| (key, value))
+    }
+
+    /// Remove the key-value pairs equivalent to any of `keys`, filling their
+    /// places with the pairs from the end of the map.
+    ///
+    /// See [`IndexMap::swap_remove_many`].
+    pub fn swap_remove_many<'q, Q, I>(&mut self, keys: I) -> Vec<(K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let indices = self.map.indices_of(keys);
+        self.swap_remove_indices(&indices)
+    }
+
+    /// Remove the key-value pair equivalent to `key` with a precomputed hash,
+    /// swapping it with the last element.
+    ///
+    /// See [`IndexMap::swap_remove_hashed`].
+    pub fn swap_remove_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.swap_remove_full_hashed(key).map(|(_, _, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key` with a precomputed hash,
+    /// swapping it with the last element, and return it and the index it had.
+    ///
+    /// See [`IndexMap::swap_remove_full_hashed`].
+    pub fn swap_remove_full_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<(usize, K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of_hashed(key)?;
+        let (key, value) = self.swap_remove_index(index)?;
+        Some((index, key, value))
+    }
+
+    /// Remove the key-value pair equivalent to `key`, shifting all elements that follow it.
+    ///
+    /// See [`IndexMap::shift_remove`].
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.shift_remove_full(key).map(|(_, _, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key`, shifting all elements
+    /// that follow it, and return it and the index it had.
+    ///
+    /// See [`IndexMap::shift_remove_full`].
+    pub fn shift_remove_full<Q>(&mut self, key: &Q) -> Option<(usize, K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of(key)?;
+        let (key, value) = self.shift_remove_index(index)?;
+        Some((index, key, value))
+    }
+
+    /// Remove the key-value pair equivalent to `key` with a precomputed hash,
+    /// shifting all elements that follow it.
+    ///
+    /// See [`IndexMap::shift_remove_hashed`].
+    pub fn shift_remove_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.shift_remove_full_hashed(key)
+            .map(|(_, _, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key` with a precomputed hash,
+    /// shifting all elements that follow it, and return it and the index it had.
+    ///
+    /// See [`IndexMap::shift_remove_full_hashed`].
+    pub fn shift_remove_full_hashed<Q>(&mut self, key: Hashed<&Q>) -> Option<(usize, K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of_hashed(key)?;
+        let (key, value) = self.shift_remove_index(index)?;
+        Some((index, key, value))
+    }
+
+    /// Remove and return the key-value pair equivalent to `key`, shifting all
+    /// elements that follow it.
+    ///
+    /// See [`IndexMap::shift_remove_entry`].
+    pub fn shift_remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.shift_remove_full(key)
+            .map(|(_, key, value)| (key, value))
+    }
+
+    /// Remove the key-value pairs equivalent to any of `keys`, shifting all
+    /// elements that follow them.
+    ///
+    /// See [`IndexMap::shift_remove_many`].
+    pub fn shift_remove_many<'q, Q, I>(&mut self, keys: I) -> Vec<(K, V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let indices = self.map.indices_of(keys);
+        self.shift_remove_indices(&indices)
+    }
+}
+
+/// Sums up the changes of several steps, to report them as one.
+#[derive(Default)]
+struct Batch {
+    /// The length before all steps.
+    len: usize,
+    /// The index before all steps of each current entry, or `None` if it's new.
+    origin: Vec<Option<usize>>,
+    /// The changes of the current step.
+    removed: Vec<usize>,
+    relocated: Vec<(usize, usize)>,
+    inserted: Vec<usize>,
+}
+
+impl IndexObserver for Batch {
+    fn inserted(&mut self, index: usize) {
+        self.inserted.push(index);
+    }
+
+    fn removed(&mut self, index: usize) {
+        self.removed.push(index);
+    }
+
+    fn relocated(&mut self, old: usize, new: usize) {
+        self.relocated.push((old, new));
+    }
+}
+
+impl Batch {
+    fn new(len: usize) -> Self {
+        Batch {
+            len,
+            origin: (0..len).map(Some).collect(),
+            ..Batch::default()
+        }
+    }
+
+    /// Run one step on an observed map, then apply its changes to `origin`.
+    fn step<K, V, S, T>(
+        &mut self,
+        map: &mut IndexMap<K, V, S>,
+        f: impl FnOnce(&mut Observed<'_, K, V, S, &mut Self>) -> T,
+    ) -> T {
+        let result = f(&mut map.observed(&mut *self));
+
+        let len = self.origin.len() + self.inserted.len() - self.removed.len();
+        let mut origin = alloc::vec![None; len];
+        let mut moved = alloc::vec![false; self.origin.len()];
+        for &i in &self.removed {
+            moved[i] = true;
+        }
+        for &(old, new) in &self.relocated {
+            moved[old] = true;
+            origin[new] = self.origin[old];
+        }
+        for (i, &old) in self.origin.iter().enumerate() {
+            if !moved[i] {
+                origin[i] = old;
+            }
+        }
+        self.origin = origin;
+        self.removed.clear();
+        self.relocated.clear();
+        self.inserted.clear();
+        result
+    }
+
+    /// Report the changes of all steps together.
+    fn report<O: IndexObserver>(self, observer: &mut O) {
+        let mut kept = alloc::vec![false; self.len];
+        for &old in self.origin.iter().flatten() {
+            kept[old] = true;
+        }
+        for (old, &kept) in kept.iter().enumerate() {
+            if !kept {
+                observer.removed(old);
+            }
+        }
+        for (new, &old) in self.origin.iter().enumerate() {
+            match old {
+                Some(old) if old != new => observer.relocated(old, new),
+                Some(_) => {}
+                None => observer.inserted(new),
+            }
+        }
+    }
+}
+
+/// The distinct indices in ascending order, like the map removes them.
+fn sorted_indices(indices: &[usize]) -> Vec<usize> {
+    let mut sorted = indices.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    sorted
+}
+
+impl<K, V, S, O> Extend<(K, V)> for Observed<'_, K, V, S, O>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    O: IndexObserver,
+{
+    /// Extend the map with all key-value pairs in the iterable.
+    ///
+    /// See [`IndexMap::extend`].
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iterable: I) {
+        for (key, value) in iterable {
+            self.insert_full(key, value);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use super::{moved, shift_inserted, shift_removed, IndexObserver};
+use crate::map::{CursorMut, IndexMap};
+
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+/// A cursor over an [`Observed`][super::Observed] map, which reports the
+/// index changes of its edits.
+///
+/// This `struct` is created by the
+/// [`Observed::cursor_mut_at`][super::Observed::cursor_mut_at] and
+/// [`Observed::cursor_mut_at_key`][super::Observed::cursor_mut_at_key]
+/// methods. It works like [`CursorMut`].
+pub struct ObservedCursorMut<'a, K, V, S, O> {
+    inner: CursorMut<'a, K, V, S>,
+    observer: &'a mut O,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S, O> fmt::Debug for ObservedCursorMut<'_, K, V, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObservedCursorMut")
+            .field(&self.inner)
+            .finish()
+    }
+}
+
+impl<'a, K, V, S, O: IndexObserver> ObservedCursorMut<'a, K, V, S, O> {
+    pub(crate) fn new(inner: CursorMut<'a, K, V, S>, observer: &'a mut O) -> Self {
+        ObservedCursorMut { inner, observer }
+    }
+
+    /// Returns the index of the current entry, or `None` at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.inner.index()
+    }
+
+    /// Returns the current entry, or `None` at the ghost position.
+    pub fn current(&mut self) -> Option<(&K, &mut V)> {
+        self.inner.current()
+    }
+
+    /// Returns the entry after the current one, which is the first entry at
+    /// the ghost position.
+    pub fn peek_next(&mut self) -> Option<(&K, &mut V)> {
+        self.inner.peek_next()
+    }
+
+    /// Returns the entry before the current one, which is the last entry at
+    /// the ghost position.
+    pub fn peek_prev(&mut self) -> Option<(&K, &mut V)> {
+        self.inner.peek_prev()
+    }
+
+    /// Moves the cursor to the next entry, or to the ghost position after
+    /// the last entry, or from there to the first entry.
+    pub fn move_next(&mut self) {
+        self.inner.move_next();
+    }
+
+    /// Moves the cursor to the previous entry, or to the ghost position
+    /// before the first entry, or from there to the last entry.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev();
+    }
+
+    /// Removes the current entry and returns it, by shifting all of the
+    /// entries that follow it.
+    ///
+    /// See [`CursorMut::remove_current`].
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        let len = self.inner.map.len();
+        let index = self.inner.index;
+        let pair = self.inner.remove_current()?;
+        shift_removed(self.observer, index, len);
+        Some(pair)
+    }
+
+    /// Splits the map after the current entry, returning a new map with all
+    /// of the following entries, which are reported as removed.
+    ///
+    /// See [`CursorMut::split_after`].
+    pub fn split_after(&mut self) -> IndexMap<K, V, S>
+    where
+        S: Clone,
+    {
+        let len = self.inner.map.len();
+        let rest = self.inner.split_after();
+        for i in len - rest.len()..len {
+            self.observer.removed(i);
+        }
+        rest
+    }
+}
+
+impl<'a, K, V, S, O> ObservedCursorMut<'a, K, V, S, O>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    O: IndexObserver,
+{
+    /// Inserts a key-value pair before the current entry.
+    ///
+    /// See [`CursorMut::insert_before`].
+    pub fn insert_before(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, false)
+    }
+
+    /// Inserts a key-value pair after the current entry.
+    ///
+    /// See [`CursorMut::insert_after`].
+    pub fn insert_after(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, true)
+    }
+
+    fn insert(&mut self, key: K, value: V, after: bool) -> Option<V> {
+        let len = self.inner.map.len();
+        let current = self.inner.index;
+        let from = self.inner.map.get_index_of(&key);
+        let old = if after {
+            self.inner.insert_after(key, value)
+        } else {
+            self.inner.insert_before(key, value)
+        };
+        // The current entry itself only gets its value replaced.
+        if from == Some(current) {
+            return old;
+        }
+        // Otherwise the pair ends up next to the cursor, which keeps its own entry.
+        let to = match (current == len, after) {
+            (true, true) => 0,
+            (_, false) => self.inner.index - 1,
+            (false, true) => self.inner.index + 1,
+        };
+        match from {
+            None => shift_inserted(self.observer, to, len),
+            Some(from) => moved(self.observer, from, to),
+        }
+        old
+    }
+}
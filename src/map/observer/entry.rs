@@ -0,0 +1,396 @@
+use super::{moved, shift_inserted, shift_removed, swap_removed, swapped, IndexObserver};
+use crate::map::{IndexedEntry, OccupiedEntry, VacantEntry};
+
+use core::fmt;
+
+/// Entry for an existing key-value pair in an [`Observed`][super::Observed]
+/// map or a vacant location to insert one, which reports its index changes.
+///
+/// This `enum` is created by the [`Observed::entry`][super::Observed::entry]
+/// method. It works like [`Entry`][crate::map::Entry].
+pub enum ObservedEntry<'a, K, V, O> {
+    /// Existing slot with equivalent key.
+    Occupied(ObservedOccupiedEntry<'a, K, V, O>),
+    /// Vacant slot (no equivalent key in the map).
+    Vacant(ObservedVacantEntry<'a, K, V, O>),
+}
+
+impl<'a, K, V, O: IndexObserver> ObservedEntry<'a, K, V, O> {
+    /// Return the index where the key-value pair exists or will be inserted.
+    pub fn index(&self) -> usize {
+        match self {
+            ObservedEntry::Occupied(entry) => entry.index(),
+            ObservedEntry::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Gets a reference to the entry's key, either within the map if occupied,
+    /// or else the new key that was used to find the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            ObservedEntry::Occupied(entry) => entry.key(),
+            ObservedEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts the given default value in the entry if it is vacant and returns a mutable
+    /// reference to it. Otherwise a mutable reference to an already existent value is returned.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            ObservedEntry::Occupied(entry) => entry.into_mut(),
+            ObservedEntry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of the `call` function in the entry if it is vacant and returns a mutable
+    /// reference to it. Otherwise a mutable reference to an already existent value is returned.
+    pub fn or_insert_with<F>(self, call: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            ObservedEntry::Occupied(entry) => entry.into_mut(),
+            ObservedEntry::Vacant(entry) => entry.insert(call()),
+        }
+    }
+
+    /// Inserts the result of the `call` function with a reference to the entry's key if it is
+    /// vacant, and returns a mutable reference to the new value. Otherwise a mutable reference to
+    /// an already existent value is returned.
+    pub fn or_insert_with_key<F>(self, call: F) -> &'a mut V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        match self {
+            ObservedEntry::Occupied(entry) => entry.into_mut(),
+            ObservedEntry::Vacant(entry) => {
+                let value = call(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    /// Inserts a default-constructed value in the entry if it is vacant and returns a mutable
+    /// reference to it. Otherwise a mutable reference to an already existent value is returned.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Modifies the entry if it is occupied.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let ObservedEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, O> fmt::Debug for ObservedEntry<'_, K, V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("ObservedEntry");
+        match self {
+            ObservedEntry::Vacant(v) => tuple.field(v),
+            ObservedEntry::Occupied(o) => tuple.field(o),
+        };
+        tuple.finish()
+    }
+}
+
+/// A view into an occupied entry of an [`Observed`][super::Observed] map.
+/// It is part of the [`ObservedEntry`] enum.
+pub struct ObservedOccupiedEntry<'a, K, V, O> {
+    inner: OccupiedEntry<'a, K, V>,
+    len: usize,
+    observer: &'a mut O,
+}
+
+impl<'a, K, V, O: IndexObserver> ObservedOccupiedEntry<'a, K, V, O> {
+    pub(super) fn new(inner: OccupiedEntry<'a, K, V>, len: usize, observer: &'a mut O) -> Self {
+        ObservedOccupiedEntry {
+            inner,
+            len,
+            observer,
+        }
+    }
+
+    /// Return the index of the key-value pair
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Gets a reference to the entry's key in the map.
+    pub fn key(&self) -> &K {
+        self.inner.key()
+    }
+
+    /// Gets a reference to the entry's value in the map.
+    pub fn get(&self) -> &V {
+        self.inner.get()
+    }
+
+    /// Gets a mutable reference to the entry's value in the map.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.inner.get_mut()
+    }
+
+    /// Converts into a mutable reference to the entry's value in the map,
+    /// with a lifetime bound to the map itself.
+    pub fn into_mut(self) -> &'a mut V {
+        self.inner.into_mut()
+    }
+
+    /// Sets the value of the entry to `value`, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        self.inner.insert(value)
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value.
+    ///
+    /// See [`OccupiedEntry::swap_remove`].
+    pub fn swap_remove(self) -> V {
+        self.swap_remove_entry().1
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value.
+    ///
+    /// See [`OccupiedEntry::shift_remove`].
+    pub fn shift_remove(self) -> V {
+        self.shift_remove_entry().1
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry.
+    ///
+    /// See [`OccupiedEntry::swap_remove_entry`].
+    pub fn swap_remove_entry(self) -> (K, V) {
+        let index = self.inner.index();
+        let pair = self.inner.swap_remove_entry();
+        swap_removed(self.observer, index, self.len);
+        pair
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry.
+    ///
+    /// See [`OccupiedEntry::shift_remove_entry`].
+    pub fn shift_remove_entry(self) -> (K, V) {
+        let index = self.inner.index();
+        let pair = self.inner.shift_remove_entry();
+        shift_removed(self.observer, index, self.len);
+        pair
+    }
+
+    /// Moves the position of the entry to a new index.
+    ///
+    /// See [`OccupiedEntry::move_index`].
+    pub fn move_index(self, to: usize) {
+        let from = self.inner.index();
+        self.inner.move_index(to);
+        moved(self.observer, from, to);
+    }
+
+    /// Swaps the position of entry with another.
+    ///
+    /// See [`OccupiedEntry::swap_indices`].
+    pub fn swap_indices(self, other: usize) {
+        let index = self.inner.index();
+        self.inner.swap_indices(other);
+        swapped(self.observer, index, other);
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, O> fmt::Debug for ObservedOccupiedEntry<'_, K, V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObservedOccupiedEntry")
+            .field(&self.inner)
+            .finish()
+    }
+}
+
+/// A view into a vacant entry of an [`Observed`][super::Observed] map.
+/// It is part of the [`ObservedEntry`] enum.
+pub struct ObservedVacantEntry<'a, K, V, O> {
+    inner: VacantEntry<'a, K, V>,
+    observer: &'a mut O,
+}
+
+impl<'a, K, V, O: IndexObserver> ObservedVacantEntry<'a, K, V, O> {
+    pub(super) fn new(inner: VacantEntry<'a, K, V>, observer: &'a mut O) -> Self {
+        ObservedVacantEntry { inner, observer }
+    }
+
+    /// Return the index where a key-value pair may be inserted.
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Gets a reference to the key that was used to find the entry.
+    pub fn key(&self) -> &K {
+        self.inner.key()
+    }
+
+    /// Takes ownership of the key, leaving the entry vacant.
+    pub fn into_key(self) -> K {
+        self.inner.into_key()
+    }
+
+    /// Inserts the entry's key and the given value into the map, and returns a mutable reference
+    /// to the value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.inner.index();
+        let value = self.inner.insert(value);
+        self.observer.inserted(index);
+        value
+    }
+
+    /// Inserts the entry's key and the given value into the map at its ordered
+    /// position among sorted keys, and returns the new index and a mutable
+    /// reference to the value.
+    ///
+    /// See [`VacantEntry::insert_sorted`].
+    pub fn insert_sorted(self, value: V) -> (usize, &'a mut V)
+    where
+        K: Ord,
+    {
+        let len = self.inner.index();
+        let (index, value) = self.inner.insert_sorted(value);
+        shift_inserted(self.observer, index, len);
+        (index, value)
+    }
+
+    /// Inserts the entry's key and the given value into the map at the given index,
+    /// shifting others to the right, and returns a mutable reference to the value.
+    ///
+    /// See [`VacantEntry::shift_insert`].
+    pub fn shift_insert(self, index: usize, value: V) -> &'a mut V {
+        let len = self.inner.index();
+        let value = self.inner.shift_insert(index, value);
+        shift_inserted(self.observer, index, len);
+        value
+    }
+}
+
+impl<K: fmt::Debug, V, O> fmt::Debug for ObservedVacantEntry<'_, K, V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObservedVacantEntry")
+            .field(&self.inner)
+            .finish()
+    }
+}
+
+/// A view into an occupied entry of an [`Observed`][super::Observed] map,
+/// obtained by its index, which reports its index changes.
+///
+/// This `struct` is created by the
+/// [`Observed::get_index_entry`][super::Observed::get_index_entry] method.
+/// It works like [`IndexedEntry`].
+pub struct ObservedIndexedEntry<'a, K, V, O> {
+    inner: IndexedEntry<'a, K, V>,
+    len: usize,
+    observer: &'a mut O,
+}
+
+impl<'a, K, V, O: IndexObserver> ObservedIndexedEntry<'a, K, V, O> {
+    pub(super) fn new(inner: IndexedEntry<'a, K, V>, len: usize, observer: &'a mut O) -> Self {
+        ObservedIndexedEntry {
+            inner,
+            len,
+            observer,
+        }
+    }
+
+    /// Return the index of the key-value pair
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Gets a reference to the entry's key in the map.
+    pub fn key(&self) -> &K {
+        self.inner.key()
+    }
+
+    /// Gets a reference to the entry's value in the map.
+    pub fn get(&self) -> &V {
+        self.inner.get()
+    }
+
+    /// Gets a mutable reference to the entry's value in the map.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.inner.get_mut()
+    }
+
+    /// Sets the value of the entry to `value`, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        self.inner.insert(value)
+    }
+
+    /// Converts into a mutable reference to the entry's value in the map,
+    /// with a lifetime bound to the map itself.
+    pub fn into_mut(self) -> &'a mut V {
+        self.inner.into_mut()
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry.
+    ///
+    /// See [`IndexedEntry::swap_remove_entry`].
+    pub fn swap_remove_entry(self) -> (K, V) {
+        let index = self.inner.index();
+        let pair = self.inner.swap_remove_entry();
+        swap_removed(self.observer, index, self.len);
+        pair
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry.
+    ///
+    /// See [`IndexedEntry::shift_remove_entry`].
+    pub fn shift_remove_entry(self) -> (K, V) {
+        let index = self.inner.index();
+        let pair = self.inner.shift_remove_entry();
+        shift_removed(self.observer, index, self.len);
+        pair
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value.
+    ///
+    /// See [`IndexedEntry::swap_remove`].
+    pub fn swap_remove(self) -> V {
+        self.swap_remove_entry().1
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value.
+    ///
+    /// See [`IndexedEntry::shift_remove`].
+    pub fn shift_remove(self) -> V {
+        self.shift_remove_entry().1
+    }
+
+    /// Moves the position of the entry to a new index.
+    ///
+    /// See [`IndexedEntry::move_index`].
+    pub fn move_index(self, to: usize) {
+        let from = self.inner.index();
+        self.inner.move_index(to);
+        moved(self.observer, from, to);
+    }
+
+    /// Swaps the position of entry with another.
+    ///
+    /// See [`IndexedEntry::swap_indices`].
+    pub fn swap_indices(self, other: usize) {
+        let index = self.inner.index();
+        self.inner.swap_indices(other);
+        swapped(self.observer, index, other);
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, O> fmt::Debug for ObservedIndexedEntry<'_, K, V, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObservedIndexedEntry")
+            .field(&self.inner)
+            .finish()
+    }
+}
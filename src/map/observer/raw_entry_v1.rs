@@ -0,0 +1,384 @@
+use super::{moved, shift_inserted, shift_removed, swap_removed, swapped, IndexObserver};
+use crate::map::raw_entry_v1::{
+    RawEntryBuilderMut, RawEntryMut, RawOccupiedEntryMut, RawVacantEntryMut,
+};
+use crate::Equivalent;
+
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+/// A builder for raw entries of an [`Observed`][super::Observed] map, which
+/// report their index changes.
+///
+/// This `struct` is created by the
+/// [`Observed::raw_entry_mut_v1`][super::Observed::raw_entry_mut_v1] method.
+/// It works like [`RawEntryBuilderMut`].
+pub struct ObservedRawEntryBuilderMut<'a, K, V, S, O> {
+    inner: RawEntryBuilderMut<'a, K, V, S>,
+    len: usize,
+    observer: &'a mut O,
+}
+
+impl<K, V, S, O> fmt::Debug for ObservedRawEntryBuilderMut<'_, K, V, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservedRawEntryBuilderMut")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, K, V, S, O: IndexObserver> ObservedRawEntryBuilderMut<'a, K, V, S, O> {
+    pub(super) fn new(
+        inner: RawEntryBuilderMut<'a, K, V, S>,
+        len: usize,
+        observer: &'a mut O,
+    ) -> Self {
+        ObservedRawEntryBuilderMut {
+            inner,
+            len,
+            observer,
+        }
+    }
+
+    /// Access an entry by key.
+    pub fn from_key<Q>(self, key: &Q) -> ObservedRawEntryMut<'a, K, V, S, O>
+    where
+        S: BuildHasher,
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let entry = self.inner.from_key(key);
+        wrap(entry, self.len, self.observer)
+    }
+
+    /// Access an entry by a key and its hash.
+    pub fn from_key_hashed_nocheck<Q>(
+        self,
+        hash: u64,
+        key: &Q,
+    ) -> ObservedRawEntryMut<'a, K, V, S, O>
+    where
+        Q: ?Sized + Equivalent<K>,
+    {
+        let entry = self.inner.from_key_hashed_nocheck(hash, key);
+        wrap(entry, self.len, self.observer)
+    }
+
+    /// Access an entry by hash.
+    pub fn from_hash<F>(self, hash: u64, is_match: F) -> ObservedRawEntryMut<'a, K, V, S, O>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        let entry = self.inner.from_hash(hash, is_match);
+        wrap(entry, self.len, self.observer)
+    }
+}
+
+fn wrap<'a, K, V, S, O>(
+    entry: RawEntryMut<'a, K, V, S>,
+    len: usize,
+    observer: &'a mut O,
+) -> ObservedRawEntryMut<'a, K, V, S, O> {
+    match entry {
+        RawEntryMut::Occupied(inner) => {
+            ObservedRawEntryMut::Occupied(ObservedRawOccupiedEntryMut {
+                inner,
+                len,
+                observer,
+            })
+        }
+        RawEntryMut::Vacant(inner) => {
+            ObservedRawEntryMut::Vacant(ObservedRawVacantEntryMut { inner, observer })
+        }
+    }
+}
+
+/// Raw entry for an existing key-value pair of an [`Observed`][super::Observed]
+/// map or a vacant location to insert one, which reports its index changes.
+///
+/// It works like [`RawEntryMut`].
+pub enum ObservedRawEntryMut<'a, K, V, S, O> {
+    /// Existing slot with equivalent key.
+    Occupied(ObservedRawOccupiedEntryMut<'a, K, V, S, O>),
+    /// Vacant slot (no equivalent key in the map).
+    Vacant(ObservedRawVacantEntryMut<'a, K, V, S, O>),
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S, O> fmt::Debug for ObservedRawEntryMut<'_, K, V, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("ObservedRawEntryMut");
+        match self {
+            Self::Vacant(v) => tuple.field(v),
+            Self::Occupied(o) => tuple.field(o),
+        };
+        tuple.finish()
+    }
+}
+
+impl<'a, K, V, S, O: IndexObserver> ObservedRawEntryMut<'a, K, V, S, O> {
+    /// Return the index where the key-value pair exists or may be inserted.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Occupied(entry) => entry.index(),
+            Self::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Inserts the given default key and value in the entry if it is vacant and returns mutable
+    /// references to them. Otherwise mutable references to an already existent pair are returned.
+    pub fn or_insert(self, default_key: K, default_value: V) -> (&'a mut K, &'a mut V)
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Self::Occupied(entry) => entry.into_key_value_mut(),
+            Self::Vacant(entry) => entry.insert(default_key, default_value),
+        }
+    }
+
+    /// Inserts the result of the `call` function in the entry if it is vacant and returns mutable
+    /// references to them. Otherwise mutable references to an already existent pair are returned.
+    pub fn or_insert_with<F>(self, call: F) -> (&'a mut K, &'a mut V)
+    where
+        F: FnOnce() -> (K, V),
+        K: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Self::Occupied(entry) => entry.into_key_value_mut(),
+            Self::Vacant(entry) => {
+                let (key, value) = call();
+                entry.insert(key, value)
+            }
+        }
+    }
+
+    /// Modifies the entry if it is occupied.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut K, &mut V),
+    {
+        if let Self::Occupied(entry) = &mut self {
+            let (k, v) = entry.get_key_value_mut();
+            f(k, v);
+        }
+        self
+    }
+}
+
+/// A raw view into an occupied entry of an [`Observed`][super::Observed] map.
+/// It is part of the [`ObservedRawEntryMut`] enum.
+pub struct ObservedRawOccupiedEntryMut<'a, K, V, S, O> {
+    inner: RawOccupiedEntryMut<'a, K, V, S>,
+    len: usize,
+    observer: &'a mut O,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S, O> fmt::Debug
+    for ObservedRawOccupiedEntryMut<'_, K, V, S, O>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObservedRawOccupiedEntryMut")
+            .field(&self.inner)
+            .finish()
+    }
+}
+
+impl<'a, K, V, S, O: IndexObserver> ObservedRawOccupiedEntryMut<'a, K, V, S, O> {
+    /// Return the index of the key-value pair
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Gets a reference to the entry's key in the map.
+    pub fn key(&self) -> &K {
+        self.inner.key()
+    }
+
+    /// Gets a mutable reference to the entry's key in the map.
+    ///
+    /// See [`RawOccupiedEntryMut::key_mut`].
+    pub fn key_mut(&mut self) -> &mut K {
+        self.inner.key_mut()
+    }
+
+    /// Converts into a mutable reference to the entry's key in the map,
+    /// with a lifetime bound to the map itself.
+    ///
+    /// See [`RawOccupiedEntryMut::into_key`].
+    pub fn into_key(self) -> &'a mut K {
+        self.inner.into_key()
+    }
+
+    /// Gets a reference to the entry's value in the map.
+    pub fn get(&self) -> &V {
+        self.inner.get()
+    }
+
+    /// Gets a mutable reference to the entry's value in the map.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.inner.get_mut()
+    }
+
+    /// Converts into a mutable reference to the entry's value in the map,
+    /// with a lifetime bound to the map itself.
+    pub fn into_mut(self) -> &'a mut V {
+        self.inner.into_mut()
+    }
+
+    /// Gets a reference to the entry's key and value in the map.
+    pub fn get_key_value(&self) -> (&K, &V) {
+        self.inner.get_key_value()
+    }
+
+    /// Gets a reference to the entry's key and value in the map.
+    pub fn get_key_value_mut(&mut self) -> (&mut K, &mut V) {
+        self.inner.get_key_value_mut()
+    }
+
+    /// Converts into a mutable reference to the entry's key and value in the map,
+    /// with a lifetime bound to the map itself.
+    pub fn into_key_value_mut(self) -> (&'a mut K, &'a mut V) {
+        self.inner.into_key_value_mut()
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        self.inner.insert(value)
+    }
+
+    /// Sets the key of the entry, and returns the entry's old key.
+    ///
+    /// See [`RawOccupiedEntryMut::insert_key`].
+    pub fn insert_key(&mut self, key: K) -> K {
+        self.inner.insert_key(key)
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value.
+    ///
+    /// See [`RawOccupiedEntryMut::swap_remove`].
+    pub fn swap_remove(self) -> V {
+        self.swap_remove_entry().1
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value.
+    ///
+    /// See [`RawOccupiedEntryMut::shift_remove`].
+    pub fn shift_remove(self) -> V {
+        self.shift_remove_entry().1
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry.
+    ///
+    /// See [`RawOccupiedEntryMut::swap_remove_entry`].
+    pub fn swap_remove_entry(self) -> (K, V) {
+        let index = self.inner.index();
+        let pair = self.inner.swap_remove_entry();
+        swap_removed(self.observer, index, self.len);
+        pair
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry.
+    ///
+    /// See [`RawOccupiedEntryMut::shift_remove_entry`].
+    pub fn shift_remove_entry(self) -> (K, V) {
+        let index = self.inner.index();
+        let pair = self.inner.shift_remove_entry();
+        shift_removed(self.observer, index, self.len);
+        pair
+    }
+
+    /// Moves the position of the entry to a new index.
+    ///
+    /// See [`RawOccupiedEntryMut::move_index`].
+    pub fn move_index(self, to: usize) {
+        let from = self.inner.index();
+        self.inner.move_index(to);
+        moved(self.observer, from, to);
+    }
+
+    /// Swaps the position of entry with another.
+    ///
+    /// See [`RawOccupiedEntryMut::swap_indices`].
+    pub fn swap_indices(self, other: usize) {
+        let index = self.inner.index();
+        self.inner.swap_indices(other);
+        swapped(self.observer, index, other);
+    }
+}
+
+/// A raw view into a vacant entry of an [`Observed`][super::Observed] map.
+/// It is part of the [`ObservedRawEntryMut`] enum.
+pub struct ObservedRawVacantEntryMut<'a, K, V, S, O> {
+    inner: RawVacantEntryMut<'a, K, V, S>,
+    observer: &'a mut O,
+}
+
+impl<K, V, S, O> fmt::Debug for ObservedRawVacantEntryMut<'_, K, V, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservedRawVacantEntryMut")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, K, V, S, O: IndexObserver> ObservedRawVacantEntryMut<'a, K, V, S, O> {
+    /// Return the index where a key-value pair may be inserted.
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Inserts the given key and value into the map,
+    /// and returns mutable references to them.
+    pub fn insert(self, key: K, value: V) -> (&'a mut K, &'a mut V)
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let index = self.inner.index();
+        let pair = self.inner.insert(key, value);
+        self.observer.inserted(index);
+        pair
+    }
+
+    /// Inserts the given key and value into the map with the provided hash,
+    /// and returns mutable references to them.
+    pub fn insert_hashed_nocheck(self, hash: u64, key: K, value: V) -> (&'a mut K, &'a mut V) {
+        let index = self.inner.index();
+        let pair = self.inner.insert_hashed_nocheck(hash, key, value);
+        self.observer.inserted(index);
+        pair
+    }
+
+    /// Inserts the given key and value into the map at the given index,
+    /// shifting others to the right, and returns mutable references to them.
+    ///
+    /// See [`RawVacantEntryMut::shift_insert`].
+    pub fn shift_insert(self, index: usize, key: K, value: V) -> (&'a mut K, &'a mut V)
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let len = self.inner.index();
+        let pair = self.inner.shift_insert(index, key, value);
+        shift_inserted(self.observer, index, len);
+        pair
+    }
+
+    /// Inserts the given key and value into the map with the provided hash
+    /// at the given index, and returns mutable references to them.
+    ///
+    /// See [`RawVacantEntryMut::shift_insert_hashed_nocheck`].
+    pub fn shift_insert_hashed_nocheck(
+        self,
+        index: usize,
+        hash: u64,
+        key: K,
+        value: V,
+    ) -> (&'a mut K, &'a mut V) {
+        let len = self.inner.index();
+        let pair = self
+            .inner
+            .shift_insert_hashed_nocheck(index, hash, key, value);
+        shift_inserted(self.observer, index, len);
+        pair
+    }
+}
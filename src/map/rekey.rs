@@ -0,0 +1,186 @@
+//! Checked key replacement, and repairing an [`IndexMap`] after its keys
+//! were edited in place.
+
+use super::IndexMap;
+use crate::util::hash_key;
+use crate::Equivalent;
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+/// The error returned when a new key is equal to another key in the map.
+///
+/// This `struct` is returned by the [`IndexMap::replace_key`] method, and as
+/// part of a [`RenameError`]. It gives back the rejected key.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ConflictError<K> {
+    key: K,
+    index: usize,
+}
+
+impl<K> ConflictError<K> {
+    /// Returns the index of the existing key that conflicts.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the rejected key.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes back ownership of the rejected key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+}
+
+impl<K> fmt::Display for ConflictError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the key already exists at index {}", self.index)
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<K: fmt::Debug> std::error::Error for ConflictError<K> {}
+
+/// The error returned by [`IndexMap::rename_key`], which gives back the
+/// rejected key.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RenameError<K> {
+    /// The key to rename is not in the map.
+    NotFound(K),
+    /// The new key is equal to another key in the map.
+    Conflict(ConflictError<K>),
+}
+
+impl<K> RenameError<K> {
+    /// Takes back ownership of the rejected key.
+    pub fn into_key(self) -> K {
+        match self {
+            RenameError::NotFound(key) => key,
+            RenameError::Conflict(error) => error.into_key(),
+        }
+    }
+}
+
+impl<K> fmt::Display for RenameError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::NotFound(_) => f.write_str("the key to rename was not found"),
+            RenameError::Conflict(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<K: fmt::Debug> std::error::Error for RenameError<K> {}
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Replaces the key at `index` with `new_key`, keeping its position and
+    /// value, and returns the old key.
+    ///
+    /// If `new_key` is equal to the key at another index, the map is left
+    /// unchanged and an error with that index is returned. It may be equal
+    /// to the key being replaced.
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    ///
+    /// Computes in **O(1)** time (average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map = IndexMap::from([("a", 1), ("b", 2), ("c", 3)]);
+    /// assert_eq!(map.replace_key(1, "x"), Ok("b"));
+    /// assert!(map.keys().eq(&["a", "x", "c"]));
+    /// assert_eq!(map["x"], 2);
+    ///
+    /// let err = map.replace_key(1, "c").unwrap_err();
+    /// assert_eq!(err.index(), 2);
+    /// assert_eq!(err.into_key(), "c");
+    /// ```
+    pub fn replace_key(&mut self, index: usize, new_key: K) -> Result<K, ConflictError<K>> {
+        assert!(index < self.len(), "IndexMap: index out of bounds");
+        let hash = self.hash(&new_key);
+        match self.core.get_index_of(hash, &new_key) {
+            Some(other) if other != index => Err(ConflictError {
+                key: new_key,
+                index: other,
+            }),
+            _ => Ok(self.core.replace_key(index, hash, new_key)),
+        }
+    }
+
+    /// Replaces the key equivalent to `old` with `new_key`, keeping its
+    /// position and value, and returns the old key.
+    ///
+    /// If `old` is not in the map, or `new_key` is equal to another key like
+    /// in [`replace_key`][Self::replace_key], the map is left unchanged and
+    /// `new_key` is given back in the error.
+    ///
+    /// Computes in **O(1)** time (average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::map::{IndexMap, RenameError};
+    ///
+    /// let mut map = IndexMap::from([("a", 1), ("b", 2)]);
+    /// assert_eq!(map.rename_key("a", "x"), Ok("a"));
+    /// assert_eq!(map.rename_key("a", "y"), Err(RenameError::NotFound("y")));
+    /// assert_eq!(map.rename_key("x", "b").unwrap_err().into_key(), "b");
+    /// assert!(map.keys().eq(&["x", "b"]));
+    /// ```
+    pub fn rename_key<Q>(&mut self, old: &Q, new_key: K) -> Result<K, RenameError<K>>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        match self.get_index_of(old) {
+            Some(index) => self
+                .replace_key(index, new_key)
+                .map_err(RenameError::Conflict),
+            None => Err(RenameError::NotFound(new_key)),
+        }
+    }
+
+    /// Recomputes the hash of every key, to repair the map after keys were
+    /// edited through [`MutableKeys`][super::MutableKeys].
+    ///
+    /// If the edits made some keys equal, only the first of them is kept.
+    /// Each later one is removed and returned with its value, along with
+    /// the new index of the key that was kept. The remaining entries keep
+    /// their relative order.
+    ///
+    /// Computes in **O(n + d log d)** time (average), for `d` removed
+    /// duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::map::{IndexMap, MutableKeys};
+    ///
+    /// let mut map = IndexMap::from([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+    /// map.retain2(|key, _| {
+    ///     *key /= 2;
+    ///     true
+    /// });
+    /// assert_eq!(map.rehash_keys(), vec![(1, 1, 'c')]);
+    /// assert!(map.keys().eq(&[0, 1, 2]));
+    /// assert_eq!(map[&2], 'd');
+    /// ```
+    pub fn rehash_keys(&mut self) -> Vec<(usize, K, V)> {
+        let hash_builder = &self.hash_builder;
+        self.core
+            .rehash_dedup(|key| hash_key(hash_builder, key), |_| {})
+    }
+}
@@ -0,0 +1,267 @@
+//! Functions to deserialize an [`IndexMap`] with a policy for duplicate keys.
+//!
+//! The default `serde` implementation inserts each entry in turn, so a
+//! duplicate key keeps the position of its first occurrence with the value of
+//! its last one. The modules here choose a different [`DuplicatePolicy`]:
+//!
+//! - [`duplicate_error`]: fail on the first duplicate key, with [`DuplicateError`].
+//! - [`first_wins`]: keep the first entry and ignore the rest, with [`FirstWins`].
+//! - [`last_wins_move_to_end`]: keep the last entry at the position of the
+//!   last occurrence, with [`LastWinsMoveToEnd`].
+//!
+//! Each module may be used in a field attribute for derived implementations,
+//! and serializes the map just like the default implementation:
+//!
+//! ```
+//! # use indexmap::IndexMap;
+//! # use serde_derive::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! struct Data {
+//!     #[serde(with = "indexmap::map::serde_dedup::duplicate_error")]
+//!     map: IndexMap<i32, u64>,
+//!     // ...
+//! }
+//! ```
+//!
+//! The same policies are available for sets in [`crate::set::serde_dedup`],
+//! and for sequenced maps in [`crate::map::serde_seq`].
+
+use serde::de::{Deserialize, Deserializer};
+
+use core::hash::{BuildHasher, Hash};
+
+use super::{Entry, IndexMap};
+use crate::serde::IndexMapVisitor;
+use crate::Entries;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A policy for duplicate keys when deserializing an [`IndexMap`] or
+/// [`IndexSet`][crate::IndexSet].
+///
+/// This trait is sealed and cannot be implemented for types outside this crate.
+pub trait DuplicatePolicy: private::Sealed {
+    /// State kept by the policy while the entries are inserted.
+    #[doc(hidden)]
+    type State: Default;
+
+    /// Insert the entry into the map, or return `false` to reject it as a duplicate.
+    #[doc(hidden)]
+    fn insert<K, V, S>(
+        state: &mut Self::State,
+        map: &mut IndexMap<K, V, S>,
+        key: K,
+        value: V,
+    ) -> bool
+    where
+        K: Hash + Eq,
+        S: BuildHasher;
+
+    /// Finish the map after all of its entries were inserted.
+    #[doc(hidden)]
+    fn finish<K, V, S>(state: Self::State, map: &mut IndexMap<K, V, S>) {
+        let _ = (state, map);
+    }
+}
+
+/// Reject duplicate keys with a deserialization error.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DuplicateError;
+
+/// Keep the first entry of a duplicate key, ignoring any later ones.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FirstWins;
+
+/// Keep the last entry of a duplicate key, moving it to the position of its
+/// last occurrence.
+///
+/// The entries are reordered once after they are all inserted, so this takes
+/// **O(n log n)** time however many duplicates there are.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LastWinsMoveToEnd;
+
+/// The default policy of [`IndexMap::insert`]: the last value is kept at the
+/// position of the first occurrence.
+pub(crate) struct Insert;
+
+impl DuplicatePolicy for DuplicateError {
+    type State = ();
+
+    fn insert<K, V, S>(_: &mut (), map: &mut IndexMap<K, V, S>, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        match map.entry(key) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                true
+            }
+        }
+    }
+}
+
+impl DuplicatePolicy for FirstWins {
+    type State = ();
+
+    fn insert<K, V, S>(_: &mut (), map: &mut IndexMap<K, V, S>, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        if let Entry::Vacant(entry) = map.entry(key) {
+            entry.insert(value);
+        }
+        true
+    }
+}
+
+/// The position of the last occurrence of each entry, by entry index.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct LastPositions {
+    last: Vec<usize>,
+    count: usize,
+}
+
+impl DuplicatePolicy for LastWinsMoveToEnd {
+    type State = LastPositions;
+
+    fn insert<K, V, S>(
+        state: &mut LastPositions,
+        map: &mut IndexMap<K, V, S>,
+        key: K,
+        value: V,
+    ) -> bool
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        // Replace the key too, which matters for sets.
+        let hash = map.hash(&key);
+        match map.core.replace_full(hash, key, value) {
+            (index, Some(_)) => state.last[index] = state.count,
+            (_, None) => state.last.push(state.count),
+        }
+        state.count += 1;
+        true
+    }
+
+    fn finish<K, V, S>(state: LastPositions, map: &mut IndexMap<K, V, S>) {
+        let LastPositions { last, count } = state;
+        if count == map.len() {
+            return;
+        }
+        let mut order: Vec<usize> = (0..last.len()).collect();
+        order.sort_unstable_by_key(|&i| last[i]);
+        // Apply the permutation in place, one cycle at a time.
+        let mut target = vec![0; order.len()];
+        for (to, from) in order.into_iter().enumerate() {
+            target[from] = to;
+        }
+        map.with_entries(|entries| {
+            for i in 0..entries.len() {
+                while target[i] != i {
+                    let j = target[i];
+                    entries.swap(i, j);
+                    target.swap(i, j);
+                }
+            }
+        });
+    }
+}
+
+impl DuplicatePolicy for Insert {
+    type State = ();
+
+    fn insert<K, V, S>(_: &mut (), map: &mut IndexMap<K, V, S>, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        map.insert(key, value);
+        true
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::DuplicateError {}
+    impl Sealed for super::FirstWins {}
+    impl Sealed for super::LastWinsMoveToEnd {}
+    impl Sealed for super::Insert {}
+}
+
+/// Deserializes an [`IndexMap`] with the duplicate key policy `P`.
+///
+/// This is the generic form of the `deserialize` functions in the policy modules.
+pub fn deserialize<'de, P, D, K, V, S>(deserializer: D) -> Result<IndexMap<K, V, S>, D::Error>
+where
+    P: DuplicatePolicy,
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_map(IndexMapVisitor::<K, V, S, P>::new(usize::MAX))
+}
+
+macro_rules! policy_module {
+    ($(#[$attr:meta])* $name:ident, $policy:ident) => {
+        $(#[$attr])*
+        pub mod $name {
+            use serde::de::{Deserialize, Deserializer};
+            use serde::ser::{Serialize, Serializer};
+
+            use core::hash::{BuildHasher, Hash};
+
+            use super::$policy;
+            use crate::IndexMap;
+
+            /// Serializes an [`IndexMap`] like its default implementation.
+            pub fn serialize<K, V, S, T>(
+                map: &IndexMap<K, V, S>,
+                serializer: T,
+            ) -> Result<T::Ok, T::Error>
+            where
+                K: Serialize,
+                V: Serialize,
+                T: Serializer,
+            {
+                serializer.collect_map(map)
+            }
+
+            #[doc = concat!("Deserializes an [`IndexMap`] with the [`", stringify!($policy), "`] policy.")]
+            pub fn deserialize<'de, D, K, V, S>(
+                deserializer: D,
+            ) -> Result<IndexMap<K, V, S>, D::Error>
+            where
+                D: Deserializer<'de>,
+                K: Deserialize<'de> + Eq + Hash,
+                V: Deserialize<'de>,
+                S: Default + BuildHasher,
+            {
+                super::deserialize::<$policy, D, K, V, S>(deserializer)
+            }
+        }
+    };
+}
+
+policy_module!(
+    /// (De)serialization of an [`IndexMap`], failing on duplicate keys.
+    duplicate_error,
+    DuplicateError
+);
+policy_module!(
+    /// (De)serialization of an [`IndexMap`], keeping the first entry of duplicate keys.
+    first_wins,
+    FirstWins
+);
+policy_module!(
+    /// (De)serialization of an [`IndexMap`], moving duplicate keys to their last position.
+    last_wins_move_to_end,
+    LastWinsMoveToEnd
+);
@@ -0,0 +1,317 @@
+//! Functions to serialize and deserialize an [`IndexMap`] as a list of entry objects.
+//!
+//! Formats like JSON can only use strings for the keys of a map, and
+//! [`serde_seq`][crate::map::serde_seq] writes `[key, value]` tuples, which are
+//! hard to read for other languages. This module serializes `IndexMap` as a
+//! sequence of structs with a `key` and a `value` field instead, in order, so
+//! in JSON it looks like `[{"key": 1, "value": "a"}, ...]`.
+//!
+//! This module may be used in a field attribute for derived implementations:
+//!
+//! ```
+//! # use indexmap::IndexMap;
+//! # use serde_derive::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! struct Data {
+//!     #[serde(with = "indexmap::map::serde_entries")]
+//!     map: IndexMap<(i32, i32), u64>,
+//!     // ...
+//! }
+//! ```
+//!
+//! The field names can be changed with an implementation of [`EntryFields`],
+//! and sets have the same in [`set::serde_entries`][crate::set::serde_entries].
+
+use serde::de::{
+    Deserialize, DeserializeSeed, Deserializer, Error, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use core::fmt::{self, Formatter};
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+
+use crate::util::cautious_capacity;
+use crate::IndexMap;
+
+/// The names of the fields of each serialized entry.
+///
+/// # Examples
+///
+/// ```
+/// # use indexmap::IndexMap;
+/// # use serde_derive::{Deserialize, Serialize};
+/// use indexmap::map::serde_entries::EntryFields;
+///
+/// struct Short;
+///
+/// impl EntryFields for Short {
+///     const KEY: &'static str = "k";
+///     const VALUE: &'static str = "v";
+/// }
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Data {
+///     #[serde(
+///         serialize_with = "indexmap::map::serde_entries::serialize_with_fields::<Short, _, _, _, _>",
+///         deserialize_with = "indexmap::map::serde_entries::deserialize_with_fields::<Short, _, _, _, _>"
+///     )]
+///     map: IndexMap<i32, u64>,
+///     // ...
+/// }
+/// ```
+pub trait EntryFields {
+    /// The name of the key field.
+    const KEY: &'static str;
+    /// The name of the value field.
+    const VALUE: &'static str;
+
+    #[doc(hidden)]
+    const FIELDS: &'static [&'static str] = &[Self::KEY, Self::VALUE];
+}
+
+/// The default [`EntryFields`], named `key` and `value`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyValue;
+
+impl EntryFields for KeyValue {
+    const KEY: &'static str = "key";
+    const VALUE: &'static str = "value";
+}
+
+/// Serializes an [`IndexMap`] as an ordered sequence of `key` and `value` structs.
+pub fn serialize<K, V, S, T>(map: &IndexMap<K, V, S>, serializer: T) -> Result<T::Ok, T::Error>
+where
+    K: Serialize,
+    V: Serialize,
+    T: Serializer,
+{
+    serialize_with_fields::<KeyValue, K, V, S, T>(map, serializer)
+}
+
+/// Deserializes an [`IndexMap`] from an ordered sequence of `key` and `value` structs.
+pub fn deserialize<'de, D, K, V, S>(deserializer: D) -> Result<IndexMap<K, V, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    deserialize_with_fields::<KeyValue, D, K, V, S>(deserializer)
+}
+
+/// Serializes an [`IndexMap`] as an ordered sequence of structs with the fields of `F`.
+pub fn serialize_with_fields<F, K, V, S, T>(
+    map: &IndexMap<K, V, S>,
+    serializer: T,
+) -> Result<T::Ok, T::Error>
+where
+    F: EntryFields,
+    K: Serialize,
+    V: Serialize,
+    T: Serializer,
+{
+    serializer.collect_seq(map.iter().map(|(key, value)| EntryRef::<K, V, F> {
+        key,
+        value,
+        marker: PhantomData,
+    }))
+}
+
+/// Deserializes an [`IndexMap`] from an ordered sequence of structs with the fields of `F`.
+///
+/// Duplicate keys are handled like the default `serde` implementation.
+pub fn deserialize_with_fields<'de, F, D, K, V, S>(
+    deserializer: D,
+) -> Result<IndexMap<K, V, S>, D::Error>
+where
+    F: EntryFields,
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_seq(EntriesVisitor::<K, V, S, F>(PhantomData))
+}
+
+/// An entry to serialize as a struct with the fields of `F`
+struct EntryRef<'a, K, V, F> {
+    key: &'a K,
+    value: &'a V,
+    marker: PhantomData<F>,
+}
+
+impl<K, V, F> Serialize for EntryRef<'_, K, V, F>
+where
+    K: Serialize,
+    V: Serialize,
+    F: EntryFields,
+{
+    fn serialize<T>(&self, serializer: T) -> Result<T::Ok, T::Error>
+    where
+        T: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Entry", 2)?;
+        state.serialize_field(F::KEY, self.key)?;
+        state.serialize_field(F::VALUE, self.value)?;
+        state.end()
+    }
+}
+
+/// Visitor to deserialize a sequence of entry structs into an `IndexMap`
+struct EntriesVisitor<K, V, S, F>(PhantomData<(K, V, S, F)>);
+
+impl<'de, K, V, S, F> Visitor<'de> for EntriesVisitor<K, V, S, F>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+    F: EntryFields,
+{
+    type Value = IndexMap<K, V, S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequence of map entries")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let capacity = cautious_capacity::<K, V>(seq.size_hint());
+        let mut map = IndexMap::with_capacity_and_hasher(capacity, S::default());
+
+        while let Some(entry) = seq.next_element::<Entry<K, V, F>>()? {
+            map.insert(entry.key, entry.value);
+        }
+
+        Ok(map)
+    }
+}
+
+/// An entry deserialized from a struct with the fields of `F`
+struct Entry<K, V, F> {
+    key: K,
+    value: V,
+    marker: PhantomData<F>,
+}
+
+impl<'de, K, V, F> Deserialize<'de> for Entry<K, V, F>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    F: EntryFields,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Entry", F::FIELDS, EntryVisitor(PhantomData))
+    }
+}
+
+struct EntryVisitor<K, V, F>(PhantomData<(K, V, F)>);
+
+impl<'de, K, V, F> Visitor<'de> for EntryVisitor<K, V, F>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    F: EntryFields,
+{
+    type Value = Entry<K, V, F>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "a map entry with `{}` and `{}`",
+            F::KEY,
+            F::VALUE
+        )
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let key = match seq.next_element()? {
+            Some(key) => key,
+            None => return Err(A::Error::invalid_length(0, &self)),
+        };
+        let value = match seq.next_element()? {
+            Some(value) => value,
+            None => return Err(A::Error::invalid_length(1, &self)),
+        };
+        Ok(Entry {
+            key,
+            value,
+            marker: PhantomData,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let (mut key, mut value) = (None, None);
+        while let Some(field) = map.next_key_seed(FieldSeed(F::FIELDS))? {
+            match field {
+                Some(0) if key.is_none() => key = Some(map.next_value()?),
+                Some(0) => return Err(A::Error::duplicate_field(F::KEY)),
+                Some(_) if value.is_none() => value = Some(map.next_value()?),
+                Some(_) => return Err(A::Error::duplicate_field(F::VALUE)),
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Entry {
+            key: key.ok_or_else(|| A::Error::missing_field(F::KEY))?,
+            value: value.ok_or_else(|| A::Error::missing_field(F::VALUE))?,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// Seed to deserialize a field identifier as its index in the list of names,
+/// or `None` for an unknown field
+pub(crate) struct FieldSeed<'a>(pub(crate) &'a [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for FieldSeed<'_> {
+    type Value = Option<usize>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldSeed<'_> {
+    type Value = Option<usize>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a field identifier")
+    }
+
+    fn visit_u64<E>(self, index: u64) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Some(index as usize).filter(|&i| i < self.0.len()))
+    }
+
+    fn visit_str<E>(self, name: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(self.0.iter().position(|&field| field == name))
+    }
+
+    fn visit_bytes<E>(self, name: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(self.0.iter().position(|field| field.as_bytes() == name))
+    }
+}
@@ -0,0 +1,178 @@
+//! Functions to serialize and deserialize an [`IndexMap`] with the hashes of its keys.
+//!
+//! Loading a large map spends most of its time hashing the keys again. This
+//! module serializes a fingerprint of the map's hasher, followed by a sequence of
+//! `(hash, key, value)` elements in order. When the deserialized map gets a
+//! default hasher with the same fingerprint, its hash table is rebuilt from the
+//! saved hashes without hashing any keys. Otherwise, as with a randomly seeded
+//! `RandomState`, the keys are hashed again like the default implementation.
+//!
+//! The saved hashes are trusted when the fingerprints match, so the input
+//! should come from a trusted source: wrong hashes or duplicate keys would not
+//! be detected, and would make lookups in the map fail.
+//!
+//! This module may be used in a field attribute for derived implementations:
+//!
+//! ```
+//! # use fxhash::FxBuildHasher;
+//! # use indexmap::IndexMap;
+//! # use serde_derive::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! struct Data {
+//!     #[serde(with = "indexmap::map::serde_hashed")]
+//!     map: IndexMap<String, u64, FxBuildHasher>,
+//!     // ...
+//! }
+//! ```
+//!
+//! Sets have the same in [`set::serde_hashed`][crate::set::serde_hashed], and
+//! `borsh` has the same in [`map::borsh_hashed`][crate::map::borsh_hashed].
+
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+
+use core::fmt::{self, Formatter};
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+
+use super::IndexMap;
+use crate::util::{cautious_capacity, fingerprint};
+use crate::vec::Vec;
+use crate::{Bucket, Entries, HashValue};
+
+/// Serializes an [`IndexMap`] with its hasher fingerprint and the hashes of its keys.
+pub fn serialize<K, V, S, T>(map: &IndexMap<K, V, S>, serializer: T) -> Result<T::Ok, T::Error>
+where
+    K: Serialize,
+    V: Serialize,
+    S: BuildHasher,
+    T: Serializer,
+{
+    let mut tuple = serializer.serialize_tuple(2)?;
+    tuple.serialize_element(&fingerprint(map.hasher()))?;
+    tuple.serialize_element(&HashedEntries(map.as_entries()))?;
+    tuple.end()
+}
+
+/// Deserializes an [`IndexMap`] with its hasher fingerprint and the hashes of its keys,
+/// which are only reused if the default hasher has the same fingerprint.
+pub fn deserialize<'de, D, K, V, S>(deserializer: D) -> Result<IndexMap<K, V, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_tuple(2, HashedVisitor(PhantomData))
+}
+
+/// The entries to serialize as `(hash, key, value)` elements
+struct HashedEntries<'a, K, V>(&'a [Bucket<K, V>]);
+
+impl<K, V> Serialize for HashedEntries<'_, K, V>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<T>(&self, serializer: T) -> Result<T::Ok, T::Error>
+    where
+        T: Serializer,
+    {
+        let entries = self.0.iter();
+        serializer.collect_seq(entries.map(|b| (b.hash.get(), &b.key, &b.value)))
+    }
+}
+
+/// Visitor to deserialize the fingerprint and the hashed entries of an `IndexMap`
+struct HashedVisitor<K, V, S>(PhantomData<(K, V, S)>);
+
+impl<'de, K, V, S> Visitor<'de> for HashedVisitor<K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    type Value = IndexMap<K, V, S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a hashed map")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let saved: u64 = match seq.next_element()? {
+            Some(saved) => saved,
+            None => return Err(A::Error::invalid_length(0, &self)),
+        };
+        let hash_builder = S::default();
+        let seed = HashedEntriesSeed {
+            trusted: fingerprint(&hash_builder) == saved,
+            hash_builder,
+            marker: PhantomData,
+        };
+        match seq.next_element_seed(seed)? {
+            Some(map) => Ok(map),
+            None => Err(A::Error::invalid_length(1, &self)),
+        }
+    }
+}
+
+/// Seed to deserialize the `(hash, key, value)` elements of an `IndexMap`,
+/// using the saved hashes if they are `trusted`
+struct HashedEntriesSeed<K, V, S> {
+    trusted: bool,
+    hash_builder: S,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<'de, K, V, S> DeserializeSeed<'de> for HashedEntriesSeed<K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = IndexMap<K, V, S>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, K, V, S> Visitor<'de> for HashedEntriesSeed<K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = IndexMap<K, V, S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequence of hashed map entries")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let capacity = cautious_capacity::<K, V>(seq.size_hint());
+        if self.trusted {
+            let mut entries = Vec::with_capacity(capacity);
+            while let Some((hash, key, value)) = seq.next_element::<(u64, K, V)>()? {
+                let hash = HashValue(hash as usize);
+                entries.push(Bucket { hash, key, value });
+            }
+            Ok(IndexMap::from_entries(entries, self.hash_builder))
+        } else {
+            let mut map = IndexMap::with_capacity_and_hasher(capacity, self.hash_builder);
+            while let Some((_, key, value)) = seq.next_element::<(u64, K, V)>()? {
+                map.insert(key, value);
+            }
+            Ok(map)
+        }
+    }
+}
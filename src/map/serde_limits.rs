@@ -0,0 +1,134 @@
+//! Functions to deserialize an [`IndexMap`] with a limit on its entries.
+//!
+//! The default `serde` implementation accepts as many entries as the input
+//! holds, allocating and hashing each of them. For untrusted input, this module
+//! fails with an error as soon as there are more entries than the limit.
+//!
+//! The limit may be a constant in a field attribute for derived implementations:
+//!
+//! ```
+//! # use indexmap::IndexMap;
+//! # use serde_derive::Deserialize;
+//! #[derive(Deserialize)]
+//! struct Data {
+//!     #[serde(deserialize_with = "indexmap::map::serde_limits::deserialize_max::<1000, _, _, _, _>")]
+//!     map: IndexMap<i32, u64>,
+//!     // ...
+//! }
+//! ```
+//!
+//! Or it can be chosen at runtime with the [`MaxEntries`] seed. Sets have the
+//! same functions in [`crate::set::serde_limits`], and sequenced maps have
+//! [`serde_seq::deserialize_max`][crate::map::serde_seq::deserialize_max].
+
+use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+
+use super::serde_dedup::Insert;
+use super::IndexMap;
+use crate::serde::{IndexMapVisitor, IndexSetVisitor};
+use crate::IndexSet;
+
+/// Deserializes an [`IndexMap`] of at most `N` entries.
+///
+/// This fails once the map has more than `N` entries, before inserting them.
+pub fn deserialize_max<'de, const N: usize, D, K, V, S>(
+    deserializer: D,
+) -> Result<IndexMap<K, V, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    MaxEntries::<IndexMap<K, V, S>>::new(N).deserialize(deserializer)
+}
+
+/// A [`DeserializeSeed`] for an [`IndexMap`] or [`IndexSet`] of at most `max`
+/// entries.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::map::serde_limits::MaxEntries;
+/// use indexmap::IndexMap;
+/// use serde::de::value::{Error, MapDeserializer};
+/// use serde::de::DeserializeSeed;
+///
+/// let input = || MapDeserializer::<_, Error>::new([(1, 2), (3, 4), (5, 6)].into_iter());
+///
+/// let map = MaxEntries::<IndexMap<i32, i32>>::new(3).deserialize(input()).unwrap();
+/// assert_eq!(map.len(), 3);
+///
+/// let result = MaxEntries::<IndexMap<i32, i32>>::new(2).deserialize(input());
+/// assert_eq!(result.unwrap_err().to_string(), "map has more than 2 entries");
+/// ```
+pub struct MaxEntries<T> {
+    max: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> MaxEntries<T> {
+    /// Create a seed that accepts at most `max` entries.
+    pub fn new(max: usize) -> Self {
+        MaxEntries {
+            max,
+            marker: PhantomData,
+        }
+    }
+
+    /// Return the maximum number of entries.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+}
+
+impl<T> Clone for MaxEntries<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for MaxEntries<T> {}
+
+impl<T> fmt::Debug for MaxEntries<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaxEntries")
+            .field("max", &self.max)
+            .finish()
+    }
+}
+
+impl<'de, K, V, S> DeserializeSeed<'de> for MaxEntries<IndexMap<K, V, S>>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    type Value = IndexMap<K, V, S>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(IndexMapVisitor::<K, V, S, Insert>::new(self.max))
+    }
+}
+
+impl<'de, T, S> DeserializeSeed<'de> for MaxEntries<IndexSet<T, S>>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+{
+    type Value = IndexSet<T, S>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(IndexSetVisitor::<T, S, Insert>::new(self.max))
+    }
+}
@@ -0,0 +1,128 @@
+//! Seeds to deserialize into an existing [`IndexMap`] or [`IndexSet`].
+//!
+//! The default `serde` implementation builds a new map each time. Its
+//! [`deserialize_in_place`][Deserialize::deserialize_in_place] replaces the
+//! contents of an existing map instead, reusing its allocations, while the
+//! [`Merge`] seed here adds the entries to those already in the map.
+//!
+//! Sequenced maps have the same in [`serde_seq`][crate::map::serde_seq].
+
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use core::fmt::{self, Formatter};
+use core::hash::{BuildHasher, Hash};
+
+use super::IndexMap;
+use crate::util::cautious_capacity;
+use crate::IndexSet;
+
+/// A [`DeserializeSeed`] that inserts into an existing [`IndexMap`] or [`IndexSet`].
+///
+/// Each entry is inserted with the normal [`IndexMap::insert`] or
+/// [`IndexSet::insert`], so a key that is already in the map keeps its
+/// position and gets the new value.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::map::serde_merge::Merge;
+/// use indexmap::{indexmap, IndexMap};
+/// use serde::de::value::{Error, MapDeserializer};
+/// use serde::de::DeserializeSeed;
+///
+/// let mut map: IndexMap<i32, i32> = indexmap! { 1 => 1, 2 => 2 };
+/// let input = MapDeserializer::<_, Error>::new([(3, 3), (1, 10)].into_iter());
+/// Merge::new(&mut map).deserialize(input).unwrap();
+/// assert_eq!(map, indexmap! { 1 => 10, 2 => 2, 3 => 3 });
+/// ```
+#[derive(Debug)]
+pub struct Merge<'a, T> {
+    target: &'a mut T,
+}
+
+impl<'a, T> Merge<'a, T> {
+    /// Create a seed that inserts into `target`.
+    pub fn new(target: &'a mut T) -> Self {
+        Merge { target }
+    }
+}
+
+impl<'de, K, V, S> DeserializeSeed<'de> for Merge<'_, IndexMap<K, V, S>>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, K, V, S> Visitor<'de> for Merge<'_, IndexMap<K, V, S>>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.target
+            .reserve(cautious_capacity::<K, V>(map.size_hint()));
+        while let Some((key, value)) = map.next_entry()? {
+            self.target.insert(key, value);
+        }
+        Ok(())
+    }
+}
+
+impl<'de, T, S> DeserializeSeed<'de> for Merge<'_, IndexSet<T, S>>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T, S> Visitor<'de> for Merge<'_, IndexSet<T, S>>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a set")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.target
+            .reserve(cautious_capacity::<T, ()>(seq.size_hint()));
+        while let Some(value) = seq.next_element()? {
+            self.target.insert(value);
+        }
+        Ok(())
+    }
+}
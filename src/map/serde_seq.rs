@@ -17,17 +17,29 @@
 //!     // ...
 //! }
 //! ```
+//!
+//! Duplicate keys are handled like the default `serde` implementation, unless
+//! one of the submodules [`duplicate_error`], [`first_wins`], or
+//! [`last_wins_move_to_end`] is used instead, with the policies of
+//! [`map::serde_dedup`][crate::map::serde_dedup].
+//!
+//! An existing map may also be reused with [`deserialize_in_place`], or extended
+//! with the [`Merge`] seed.
 
-use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, Error, SeqAccess, Visitor};
 use serde::ser::{Serialize, Serializer};
 
+use core::cmp;
 use core::fmt::{self, Formatter};
 use core::hash::{BuildHasher, Hash};
 use core::marker::PhantomData;
 
+use crate::map::serde_dedup::{DuplicatePolicy, Insert};
 use crate::map::Slice as MapSlice;
-use crate::serde::cautious_capacity;
+use crate::serde::InPlaceSeed;
 use crate::set::Slice as SetSlice;
+use crate::util::cautious_capacity;
+use crate::util::hash_key;
 use crate::IndexMap;
 
 /// Serializes a [`map::Slice`][MapSlice] as an ordered sequence.
@@ -83,14 +95,28 @@ where
     serializer.collect_seq(map)
 }
 
-/// Visitor to deserialize a *sequenced* `IndexMap`
-struct SeqVisitor<K, V, S>(PhantomData<(K, V, S)>);
+/// Visitor to deserialize a *sequenced* `IndexMap` of at most `max` entries,
+/// inserting with the policy `P`
+struct SeqVisitor<K, V, S, P> {
+    max: usize,
+    marker: PhantomData<(K, V, S, P)>,
+}
 
-impl<'de, K, V, S> Visitor<'de> for SeqVisitor<K, V, S>
+impl<K, V, S, P> SeqVisitor<K, V, S, P> {
+    fn new(max: usize) -> Self {
+        SeqVisitor {
+            max,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V, S, P> Visitor<'de> for SeqVisitor<K, V, S, P>
 where
     K: Deserialize<'de> + Eq + Hash,
     V: Deserialize<'de>,
     S: Default + BuildHasher,
+    P: DuplicatePolicy,
 {
     type Value = IndexMap<K, V, S>;
 
@@ -102,13 +128,23 @@ where
     where
         A: SeqAccess<'de>,
     {
-        let capacity = cautious_capacity::<K, V>(seq.size_hint());
+        let capacity = cmp::min(cautious_capacity::<K, V>(seq.size_hint()), self.max);
         let mut map = IndexMap::with_capacity_and_hasher(capacity, S::default());
 
+        let mut state = P::State::default();
+        let mut len = 0;
         while let Some((key, value)) = seq.next_element()? {
-            map.insert(key, value);
+            if len == self.max {
+                let msg = format_args!("map has more than {} entries", self.max);
+                return Err(A::Error::custom(msg));
+            }
+            len += 1;
+            if !P::insert(&mut state, &mut map, key, value) {
+                return Err(A::Error::custom("duplicate map key"));
+            }
         }
 
+        P::finish(state, &mut map);
         Ok(map)
     }
 }
@@ -134,5 +170,279 @@ where
     V: Deserialize<'de>,
     S: Default + BuildHasher,
 {
-    deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    deserializer.deserialize_seq(SeqVisitor::<K, V, S, Insert>::new(usize::MAX))
+}
+
+/// Deserializes an [`IndexMap`] from an ordered sequence, with the duplicate
+/// key policy `P`.
+///
+/// This is the generic form of the `deserialize` functions in the policy modules.
+pub fn deserialize_dedup<'de, P, D, K, V, S>(deserializer: D) -> Result<IndexMap<K, V, S>, D::Error>
+where
+    P: DuplicatePolicy,
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_seq(SeqVisitor::<K, V, S, P>::new(usize::MAX))
+}
+
+/// Deserializes an [`IndexMap`] from an ordered sequence of at most `N` entries.
+///
+/// This fails once the sequence has more than `N` elements, before inserting
+/// them, which bounds the memory used for untrusted input. It may be used in a
+/// field attribute for deriving [`Deserialize`]:
+///
+/// ```
+/// # use indexmap::IndexMap;
+/// # use serde_derive::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Data {
+///     #[serde(deserialize_with = "indexmap::map::serde_seq::deserialize_max::<1000, _, _, _, _>")]
+///     map: IndexMap<i32, u64>,
+///     // ...
+/// }
+/// ```
+pub fn deserialize_max<'de, const N: usize, D, K, V, S>(
+    deserializer: D,
+) -> Result<IndexMap<K, V, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_seq(SeqVisitor::<K, V, S, Insert>::new(N))
 }
+
+/// Deserializes an ordered sequence in place of an existing [`IndexMap`].
+///
+/// The result is the same as [`deserialize`], but the map's allocations are
+/// reused, and so are its keys and values with their own
+/// [`deserialize_in_place`][Deserialize::deserialize_in_place].
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::{indexmap, IndexMap};
+/// use serde::de::value::{Error, SeqDeserializer};
+///
+/// let mut map: IndexMap<i32, i32> = indexmap! { 1 => 1, 2 => 2, 3 => 3 };
+/// let input = SeqDeserializer::<_, Error>::new([vec![4, 4], vec![1, 10]].into_iter());
+/// indexmap::map::serde_seq::deserialize_in_place(input, &mut map).unwrap();
+/// assert_eq!(map, indexmap! { 4 => 4, 1 => 10 });
+/// ```
+pub fn deserialize_in_place<'de, D, K, V, S>(
+    deserializer: D,
+    map: &mut IndexMap<K, V, S>,
+) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    deserializer.deserialize_seq(SeqInPlaceVisitor(map))
+}
+
+/// Visitor to deserialize a *sequenced* `IndexMap` in place, reusing its entries and capacity
+struct SeqInPlaceVisitor<'a, K, V, S>(&'a mut IndexMap<K, V, S>);
+
+impl<'de, K, V, S> Visitor<'de> for SeqInPlaceVisitor<'_, K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequenced map")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let IndexMap { core, hash_builder } = self.0;
+        let additional = cautious_capacity::<K, V>(seq.size_hint()).saturating_sub(core.len());
+        core.reserve(additional);
+
+        let mut refill = core.refill();
+        loop {
+            if let Some((key, value)) = refill.next_slot() {
+                if seq.next_element_seed(PairInPlace { key, value })?.is_none() {
+                    break;
+                }
+                let hash = hash_key(hash_builder, key);
+                refill.insert_slot(hash);
+            } else if let Some((key, value)) = seq.next_element::<(K, V)>()? {
+                refill.insert(hash_key(hash_builder, &key), key, value);
+            } else {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Seed to deserialize a `(key, value)` element in place of an existing entry
+struct PairInPlace<'a, K, V> {
+    key: &'a mut K,
+    value: &'a mut V,
+}
+
+impl<'de, K, V> DeserializeSeed<'de> for PairInPlace<'_, K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(2, self)
+    }
+}
+
+impl<'de, K, V> Visitor<'de> for PairInPlace<'_, K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a tuple of size 2")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        if seq.next_element_seed(InPlaceSeed(self.key))?.is_none() {
+            return Err(A::Error::invalid_length(0, &"a tuple of size 2"));
+        }
+        if seq.next_element_seed(InPlaceSeed(self.value))?.is_none() {
+            return Err(A::Error::invalid_length(1, &"a tuple of size 2"));
+        }
+        Ok(())
+    }
+}
+
+/// A [`DeserializeSeed`] that inserts an ordered sequence into an existing [`IndexMap`].
+///
+/// This is the sequenced form of [`map::serde_merge::Merge`][crate::map::serde_merge::Merge],
+/// inserting each entry with the normal [`IndexMap::insert`].
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::map::serde_seq::Merge;
+/// use indexmap::{indexmap, IndexMap};
+/// use serde::de::value::{Error, SeqDeserializer};
+/// use serde::de::DeserializeSeed;
+///
+/// let mut map: IndexMap<i32, i32> = indexmap! { 1 => 1, 2 => 2 };
+/// let input = SeqDeserializer::<_, Error>::new([vec![3, 3], vec![1, 10]].into_iter());
+/// Merge::new(&mut map).deserialize(input).unwrap();
+/// assert_eq!(map, indexmap! { 1 => 10, 2 => 2, 3 => 3 });
+/// ```
+#[derive(Debug)]
+pub struct Merge<'a, K, V, S> {
+    map: &'a mut IndexMap<K, V, S>,
+}
+
+impl<'a, K, V, S> Merge<'a, K, V, S> {
+    /// Create a seed that inserts into `map`.
+    pub fn new(map: &'a mut IndexMap<K, V, S>) -> Self {
+        Merge { map }
+    }
+}
+
+impl<'de, K, V, S> DeserializeSeed<'de> for Merge<'_, K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, K, V, S> Visitor<'de> for Merge<'_, K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequenced map")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.map.reserve(cautious_capacity::<K, V>(seq.size_hint()));
+        while let Some((key, value)) = seq.next_element()? {
+            self.map.insert(key, value);
+        }
+        Ok(())
+    }
+}
+
+macro_rules! policy_module {
+    ($(#[$attr:meta])* $name:ident, $policy:ident) => {
+        $(#[$attr])*
+        pub mod $name {
+            use serde::de::{Deserialize, Deserializer};
+
+            use core::hash::{BuildHasher, Hash};
+
+            use crate::map::serde_dedup::$policy;
+            use crate::IndexMap;
+
+            pub use super::serialize;
+
+            #[doc = concat!("Deserializes an [`IndexMap`] from an ordered sequence, with the [`", stringify!($policy), "`] policy.")]
+            pub fn deserialize<'de, D, K, V, S>(deserializer: D) -> Result<IndexMap<K, V, S>, D::Error>
+            where
+                D: Deserializer<'de>,
+                K: Deserialize<'de> + Eq + Hash,
+                V: Deserialize<'de>,
+                S: Default + BuildHasher,
+            {
+                super::deserialize_dedup::<$policy, D, K, V, S>(deserializer)
+            }
+        }
+    };
+}
+
+policy_module!(
+    /// Sequenced (de)serialization of an [`IndexMap`], failing on duplicate keys.
+    duplicate_error,
+    DuplicateError
+);
+policy_module!(
+    /// Sequenced (de)serialization of an [`IndexMap`], keeping the first entry of duplicate keys.
+    first_wins,
+    FirstWins
+);
+policy_module!(
+    /// Sequenced (de)serialization of an [`IndexMap`], moving duplicate keys to their last position.
+    last_wins_move_to_end,
+    LastWinsMoveToEnd
+);
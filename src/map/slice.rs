@@ -35,7 +35,7 @@ impl<K, V> Slice<K, V> {
         unsafe { &mut *(entries as *mut [Bucket<K, V>] as *mut Self) }
     }
 
-    pub(super) fn from_boxed(entries: Box<[Bucket<K, V>]>) -> Box<Self> {
+    pub(crate) fn from_boxed(entries: Box<[Bucket<K, V>]>) -> Box<Self> {
         unsafe { Box::from_raw(Box::into_raw(entries) as *mut Self) }
     }
 
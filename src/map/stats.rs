@@ -0,0 +1,160 @@
+//! Memory usage and hash table statistics of an [`IndexMap`].
+
+use super::IndexMap;
+
+use alloc::vec::Vec;
+
+/// The bytes allocated by an [`IndexMap`] for its own storage.
+///
+/// This `struct` is created by the [`IndexMap::allocation_size`] method.
+/// Neither part includes any heap memory owned by the keys and values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocationSize {
+    pub(crate) entries: usize,
+    pub(crate) indices: usize,
+}
+
+impl AllocationSize {
+    /// Returns the bytes of the entries vector, including its spare capacity.
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// Returns the bytes of the hash table of indices, including its
+    /// control bytes and empty buckets.
+    pub fn indices(&self) -> usize {
+        self.indices
+    }
+
+    /// Returns the bytes of both allocations together.
+    pub fn total(&self) -> usize {
+        self.entries + self.indices
+    }
+}
+
+/// Statistics about the hash table of an [`IndexMap`].
+///
+/// This `struct` is created by the [`IndexMap::stats`] method.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableStats {
+    pub(crate) len: usize,
+    pub(crate) buckets: usize,
+    pub(crate) indices_capacity: usize,
+    pub(crate) entries_capacity: usize,
+    pub(crate) probe_distances: Vec<usize>,
+    pub(crate) distinct_hashes: usize,
+}
+
+impl TableStats {
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of buckets in the hash table, which is zero until
+    /// it allocates.
+    pub fn buckets(&self) -> usize {
+        self.buckets
+    }
+
+    /// Returns the fraction of buckets that are in use, or zero if there are
+    /// no buckets.
+    pub fn load_factor(&self) -> f64 {
+        if self.buckets == 0 {
+            0.0
+        } else {
+            self.len as f64 / self.buckets as f64
+        }
+    }
+
+    /// Returns the number of indices the hash table can hold without
+    /// reallocating.
+    pub fn indices_capacity(&self) -> usize {
+        self.indices_capacity
+    }
+
+    /// Returns the number of entries the entries vector can hold without
+    /// reallocating.
+    pub fn entries_capacity(&self) -> usize {
+        self.entries_capacity
+    }
+
+    /// Returns how many more entries the vector can hold than the hash table,
+    /// negative if the hash table has more room instead.
+    ///
+    /// The map's [`capacity`][IndexMap::capacity] is the smaller of the two,
+    /// so any mismatch is memory that can't be used before reallocating.
+    pub fn capacity_mismatch(&self) -> isize {
+        self.entries_capacity as isize - self.indices_capacity as isize
+    }
+
+    /// Returns a histogram of probe distances, where the count at index `d`
+    /// is the number of entries whose index is stored `d` buckets after the
+    /// bucket where the search for its hash starts.
+    ///
+    /// The table checks a whole group of buckets at a time, so short distances
+    /// are still found in the first probe. Long tails suggest a poor hasher.
+    pub fn probe_distances(&self) -> &[usize] {
+        &self.probe_distances
+    }
+
+    /// Returns the number of distinct hash values among the entries, which
+    /// is less than the length if some keys have fully colliding hashes.
+    pub fn distinct_hashes(&self) -> usize {
+        self.distinct_hashes
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Return the bytes allocated for the entries and for the hash table,
+    /// counting their capacity whether it is used or not.
+    ///
+    /// This doesn't include any memory owned by the keys and values
+    /// themselves, and the allocator may reserve a little more than
+    /// requested.
+    ///
+    /// Computes in **O(1)** time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map = IndexMap::<u64, u64>::new();
+    /// assert_eq!(map.allocation_size().total(), 0);
+    ///
+    /// map.reserve(100);
+    /// let size = map.allocation_size();
+    /// assert!(size.entries() >= 100 * 24);
+    /// assert!(size.indices() >= 100 * 8);
+    /// ```
+    pub fn allocation_size(&self) -> AllocationSize {
+        self.core.allocation_size()
+    }
+
+    /// Return statistics about the map's hash table, like its load factor
+    /// and how far its indices are from their ideal position.
+    ///
+    /// Computes in **O(n log n)** time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let map: IndexMap<i32, i32> = (0..100).map(|i| (i, i)).collect();
+    /// let stats = map.stats();
+    /// assert_eq!(stats.len(), 100);
+    /// assert!(stats.load_factor() > 0.0 && stats.load_factor() <= 1.0);
+    /// assert_eq!(stats.probe_distances().iter().sum::<usize>(), 100);
+    /// assert_eq!(stats.distinct_hashes(), 100);
+    /// ```
+    pub fn stats(&self) -> TableStats {
+        self.core.stats()
+    }
+}
@@ -725,3 +725,686 @@ fn test_partition_point() {
     assert_eq!(b.partition_point(|_, &x| x < 7), 4);
     assert_eq!(b.partition_point(|_, &x| x < 8), 5);
 }
+
+#[test]
+fn freeze_large() {
+    let map: IndexMap<u64, u64> = (0..100_000).map(|i| (i * 7919, i)).collect();
+    let mut frozen = map.freeze();
+    assert_eq!(frozen.len(), 100_000);
+    for i in 0..100_000 {
+        assert_eq!(
+            frozen.get_full(&(i * 7919)),
+            Some((i as usize, &(i * 7919), &i))
+        );
+        assert_eq!(frozen.get(&(i * 7919 + 1)), None);
+    }
+    *frozen.get_mut(&0).unwrap() = 42;
+    assert_eq!(frozen[0], 42);
+    assert_eq!(frozen.as_slice().last(), Some((&(99_999 * 7919), &99_999)));
+
+    let mut map = frozen.thaw();
+    assert_eq!(map.insert(1, 1), None);
+    assert_eq!(map.get_index_of(&(5 * 7919)), Some(5));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn freeze_million() {
+    let map: IndexMap<u64, ()> = (0..1_000_000).map(|i| (i, ())).collect();
+    let frozen = map.freeze();
+    for i in 0..1_000_000 {
+        assert_eq!(frozen.get_index_of(&i), Some(i as usize));
+    }
+    assert_eq!(frozen.get_index_of(&1_000_000), None);
+}
+
+#[test]
+fn freeze_empty() {
+    let frozen = IndexMap::<i32, i32>::new().freeze();
+    assert!(frozen.is_empty());
+    assert_eq!(frozen.get(&0), None);
+    assert!(frozen.thaw().is_empty());
+}
+
+#[test]
+fn parts_roundtrip() {
+    let map: IndexMap<u64, u64> = (0..100).map(|i| (i * 7, i)).collect();
+    let (entries, hasher) = map.clone().into_parts();
+    assert_eq!(entries.len(), 100);
+    assert!(entries.iter().map(|&(_, k, v)| (k, v)).eq(map.clone()));
+
+    let mut rebuilt = IndexMap::from_parts(entries.clone(), hasher.clone());
+    assert!(rebuilt.iter().eq(&map));
+    assert_eq!(rebuilt.get_index_of(&(50 * 7)), Some(50));
+    assert_eq!(rebuilt.insert(7, 0), Some(1));
+    assert_eq!(rebuilt.insert(1, 1), None);
+
+    let rebuilt = IndexMap::from_parts_unchecked(entries, hasher);
+    assert_eq!(rebuilt, map);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "wrong hash"]
+fn from_parts_wrong_hash() {
+    let (mut entries, hasher) = IndexMap::from([(1, 1), (2, 2)]).into_parts();
+    entries[1].0 ^= 1;
+    IndexMap::from_parts(entries, hasher);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "duplicate key"]
+fn from_parts_duplicate_key() {
+    let (mut entries, hasher) = IndexMap::from([(1, 1), (2, 2)]).into_parts();
+    entries.push(entries[0]);
+    IndexMap::from_parts(entries, hasher);
+}
+
+#[test]
+fn allocation_size() {
+    let mut map = IndexMap::<u64, u64>::new();
+    assert_eq!(map.allocation_size().total(), 0);
+
+    map.extend((0..10).map(|i| (i, i)));
+    let size = map.allocation_size();
+    let bucket_size = mem::size_of::<Bucket<u64, u64>>();
+    assert_eq!(size.entries(), map.stats().entries_capacity() * bucket_size);
+    // At least one `usize` and one control byte per bucket.
+    assert!(size.indices() >= map.stats().buckets() * (mem::size_of::<usize>() + 1));
+    assert_eq!(size.total(), size.entries() + size.indices());
+
+    map.clear();
+    map.shrink_to_fit();
+    assert_eq!(map.allocation_size().total(), 0);
+}
+
+#[test]
+fn table_stats() {
+    let map: IndexMap<u32, ()> = IndexMap::new();
+    let stats = map.stats();
+    assert!(stats.is_empty());
+    assert_eq!(stats.buckets(), 0);
+    assert_eq!(stats.load_factor(), 0.0);
+    assert_eq!(stats.probe_distances(), &[] as &[usize]);
+
+    let map: IndexMap<u32, ()> = (0..100).map(|i| (i, ())).collect();
+    let stats = map.stats();
+    assert_eq!(stats.len(), 100);
+    assert!(stats.buckets() >= stats.indices_capacity());
+    assert_eq!(stats.load_factor(), 100.0 / stats.buckets() as f64);
+    assert_eq!(
+        stats.capacity_mismatch(),
+        stats.entries_capacity() as isize - stats.indices_capacity() as isize
+    );
+    assert_eq!(stats.probe_distances().iter().sum::<usize>(), 100);
+    assert_eq!(stats.distinct_hashes(), 100);
+
+    #[derive(Default)]
+    struct ZeroHasher;
+
+    impl Hasher for ZeroHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    // Every key collides, so they fill consecutive buckets from the first.
+    let map: IndexMap<u32, (), ::core::hash::BuildHasherDefault<ZeroHasher>> =
+        (0..10).map(|i| (i, ())).collect();
+    let stats = map.stats();
+    assert_eq!(stats.distinct_hashes(), 1);
+    assert_eq!(stats.probe_distances(), &[1; 10]);
+}
+
+#[test]
+#[cfg(feature = "deepsize")]
+fn deep_size_of() {
+    use deepsize::DeepSizeOf;
+    use std::string::ToString;
+
+    let map: IndexMap<String, u64> = (0..10).map(|i| (i.to_string(), i)).collect();
+    let strings: usize = map.keys().map(String::capacity).sum();
+    assert_eq!(
+        map.deep_size_of(),
+        mem::size_of_val(&map) + map.allocation_size().total() + strings
+    );
+}
+
+#[test]
+#[cfg(feature = "get-size")]
+fn get_size() {
+    use get_size::GetSize;
+    use std::string::ToString;
+
+    let map: IndexMap<String, u64> = (0..10).map(|i| (i.to_string(), i)).collect();
+    let strings: usize = map.keys().map(String::capacity).sum();
+    assert_eq!(map.get_heap_size(), map.allocation_size().total() + strings);
+}
+
+#[derive(Clone, Default)]
+struct Collide;
+
+impl Hasher for Collide {
+    fn finish(&self) -> u64 {
+        7
+    }
+
+    fn write(&mut self, _: &[u8]) {}
+}
+
+type Weak = Fallback<::core::hash::BuildHasherDefault<Collide>>;
+
+#[test]
+fn guarded_reseed() {
+    // The first 20 keys collide before the guard is used.
+    let map: IndexMap<u32, u32, Weak> = (0..20).map(|i| (i, i)).collect();
+    let mut map = map.guard(4);
+    assert_eq!(map.max_probes(), 4);
+    // Lookups are not watched.
+    assert_eq!(map.get(&10), Some(&10));
+    assert_eq!(map.reseeds(), 0);
+    *map.entry(10).or_default() += 1;
+    assert_eq!(map.reseeds(), 1);
+    assert_eq!(map.max_probes(), 8);
+    assert!(map.hasher().is_fallback());
+
+    let clone = map.clone();
+    assert_eq!((clone.reseeds(), clone.max_probes()), (1, 8));
+
+    map.extend((20..1000).map(|i| (i, i)));
+    assert_eq!(map.shift_insert(0, 1000, 1000), None);
+    assert_eq!(map.insert_full(500, 0), (501, Some(500)));
+    assert_eq!(map.reseeds(), 1);
+    let map = map.unguard();
+    map.check_parts();
+    assert_eq!(map.len(), 1001);
+    for (i, (&k, &v)) in map.iter().enumerate() {
+        assert_eq!(k, if i == 0 { 1000 } else { i as u32 - 1 });
+        assert_eq!(map.get_index_of(&k), Some(i));
+        assert_eq!(v, if k == 500 { 0 } else { k + u32::from(k == 10) });
+    }
+    assert!(map.stats().distinct_hashes() > 990);
+}
+
+#[test]
+fn guarded_insert_paths() {
+    let mut extended = IndexMap::<u32, (), Weak>::default().guard(8);
+    extended.extend((0..100).map(|i| (i, ())));
+    assert_eq!(extended.reseeds(), 1);
+
+    let mut shifted = IndexMap::<u32, (), Weak>::default().guard(8);
+    for i in 0..100 {
+        shifted.shift_insert(0, i, ());
+    }
+    assert_eq!(shifted.reseeds(), 1);
+    assert!(shifted.keys().copied().eq((0..100).rev()));
+    shifted.check_parts();
+
+    let mut sorted = IndexMap::<u32, (), Weak>::default().guard(8);
+    for i in (0..100).rev() {
+        sorted.insert_sorted(i, ());
+    }
+    assert_eq!(sorted.reseeds(), 1);
+    assert!(sorted.keys().copied().eq(0..100));
+    sorted.check_parts();
+}
+
+#[test]
+fn guarded_random_state() {
+    let mut map = IndexMap::new().guard(0);
+    assert_eq!(map.insert(1, 'a'), None);
+    assert_eq!(map.reseeds(), 0);
+    // Any comparison is too much with a zero limit.
+    assert_eq!(map.insert(1, 'b'), Some('a'));
+    assert_eq!(map.reseeds(), 1);
+    assert_eq!(map.max_probes(), 1);
+    assert_eq!(map[&1], 'b');
+    map.check_parts();
+}
+
+#[test]
+fn guarded_then_rollback() {
+    let map: IndexMap<u32, u32, Weak> = (0..20).map(|i| (i, i)).collect();
+    let mut map = map.guard(4);
+    map.insert(20, 20);
+    assert_eq!(map.reseeds(), 1);
+    let mut map = map.unguard();
+
+    // Nothing reseeds while the transaction is open, so the undo log stays valid.
+    let result = map.transaction(|tx| {
+        for i in 21..200 {
+            tx.insert(i, i);
+        }
+        tx.shift_insert(0, 200, 200);
+        tx.swap_remove(&5);
+        Err::<(), _>("undo")
+    });
+    assert_eq!(result, Err("undo"));
+    map.check_parts();
+    assert!(map.keys().copied().eq(0..21));
+    for i in 0..21 {
+        assert_eq!(map.get_index_of(&i), Some(i as usize));
+    }
+}
+
+#[test]
+fn guarded_then_splice() {
+    let map: IndexMap<u32, u32, Weak> = (0..20).map(|i| (i, i)).collect();
+    let mut map = map.guard(4);
+    map.insert(20, 20);
+    let mut map = map.unguard();
+
+    let removed: Vec<_> = map.splice(5..10, (15..40).map(|i| (i, 0))).collect();
+    assert!(removed.iter().map(|&(k, _)| k).eq(5..10));
+    map.check_parts();
+    assert_eq!(map.len(), 35);
+    assert!(map.keys().copied().take(5).eq(0..5));
+    // Keys that were in the tail are updated there.
+    assert!(map.keys().copied().skip(5).eq((21..40).chain(10..21)));
+}
+
+#[test]
+fn guard_is_not_stored_in_the_map() {
+    use ::core::mem::size_of;
+
+    assert_eq!(
+        size_of::<IndexMap<u32, u32>>(),
+        size_of::<IndexMapCore<u32, u32>>() + size_of::<RandomState>()
+    );
+    assert_eq!(
+        size_of::<GuardedIndexMap<u32, u32>>(),
+        size_of::<IndexMap<u32, u32>>() + 2 * size_of::<usize>()
+    );
+}
+
+#[test]
+fn with_new_hasher() {
+    let mut map: IndexMap<u32, u32, fnv::FnvBuildHasher> = (0..100).map(|i| (i * 3, i)).collect();
+    map.swap_remove(&30);
+    let capacity = map.capacity();
+    let entries = map.as_entries().as_ptr();
+
+    let map = map.with_new_hasher(RandomState::new());
+    assert_eq!(map.capacity(), capacity);
+    assert_eq!(map.as_entries().as_ptr(), entries);
+    map.check_parts();
+    assert_eq!(map.get_index_of(&(99 * 3)), Some(10));
+    assert_eq!(map.get(&30), None);
+}
+
+#[test]
+fn hashed_lookups() {
+    use std::string::ToString;
+
+    let hasher = RandomState::new();
+    let mut maps: Vec<IndexMap<String, usize>> = (0..5usize)
+        .map(|i| {
+            let mut map = IndexMap::with_hasher(hasher.clone());
+            map.extend((0..i * 10).map(|j| (j.to_string(), i)));
+            map
+        })
+        .collect();
+
+    let key = crate::Hashed::new("15", &hasher);
+    let found: Vec<_> = maps.iter().map(|map| map.get_hashed(key)).collect();
+    assert_eq!(found, [None, None, Some(&2), Some(&3), Some(&4)]);
+    assert_eq!(maps[2].get_index_of_hashed(key), Some(15));
+    assert!(maps[4].contains_key_hashed(key));
+    *maps[3].get_mut_hashed(key).unwrap() = 30;
+    assert_eq!(maps[3]["15"], 30);
+
+    assert_eq!(
+        maps[2].swap_remove_full_hashed(key),
+        Some((15, "15".into(), 2))
+    );
+    assert_eq!(maps[2].get_index_of("19"), Some(15));
+    assert_eq!(maps[3].shift_remove_hashed(key), Some(30));
+    assert_eq!(maps[3].get_index_of("19"), Some(18));
+    assert_eq!(maps[4].swap_remove_hashed(key), Some(4));
+    assert_eq!(maps[1].shift_remove_full_hashed(key), None);
+
+    let owned = crate::Hashed::new(String::from("15"), &hasher);
+    assert_eq!(owned.hash(), key.hash());
+    assert!(!maps[0].contains_key_hashed(owned.as_ref()));
+    assert_eq!(maps[0].insert_full_hashed(owned.clone(), 0), (0, None));
+    assert_eq!(maps[0].insert_hashed(owned.clone(), 1), Some(0));
+    *maps[0].entry_hashed(owned.clone()).or_default() += 1;
+    assert_eq!(maps[0]["15"], 2);
+    maps[1].entry_hashed(owned).or_insert(5);
+    assert_eq!(maps[1].get_index_of("15"), Some(10));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "different hasher"]
+fn hashed_wrong_hasher() {
+    let map: IndexMap<i32, i32> = (0..10).map(|i| (i, i)).collect();
+    map.get_hashed(crate::Hashed::new(&1, &RandomState::new()));
+}
+
+#[test]
+fn cursor_insert() {
+    // Check every position and key against a plain vector of keys.
+    for len in 0..5 {
+        for index in 0..=len {
+            for key in 0..=len {
+                for after in [false, true] {
+                    let mut map: IndexMap<usize, ()> = (0..len).map(|k| (k, ())).collect();
+                    let mut expected: Vec<usize> = (0..len).collect();
+                    let current = expected.get(index).copied();
+
+                    let mut cursor = map.cursor_mut_at(index);
+                    if after {
+                        cursor.insert_after(key, ());
+                    } else {
+                        cursor.insert_before(key, ());
+                    }
+                    assert_eq!(cursor.current().map(|(&k, _)| k), current);
+
+                    if current != Some(key) {
+                        expected.retain(|&k| k != key);
+                        let at = match current {
+                            Some(c) => expected.iter().position(|&k| k == c).unwrap(),
+                            None => expected.len(),
+                        };
+                        let at = match (current, after) {
+                            (None, true) => 0,
+                            (Some(_), true) => at + 1,
+                            (_, false) => at,
+                        };
+                        expected.insert(at, key);
+                    }
+                    assert!(map.keys().eq(&expected), "{len} {index} {key} {after}");
+                    map.check_parts();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cursor_walk() {
+    let mut map: IndexMap<i32, i32> = (0..5).map(|i| (i, i * 10)).collect();
+    let mut cursor = map.cursor_mut_at_key(&4).unwrap();
+    assert_eq!(cursor.peek_next(), None);
+    cursor.move_next();
+    assert_eq!(cursor.index(), None);
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.peek_next(), Some((&0, &mut 0)));
+    assert_eq!(cursor.peek_prev(), Some((&4, &mut 40)));
+    assert_eq!(cursor.remove_current(), None);
+    cursor.move_next();
+    assert_eq!(cursor.index(), Some(0));
+    cursor.move_prev();
+    cursor.move_prev();
+    assert_eq!(cursor.index(), Some(4));
+
+    cursor.move_prev();
+    *cursor.current().unwrap().1 += 1;
+    assert_eq!(cursor.remove_current(), Some((3, 31)));
+    assert_eq!(cursor.current(), Some((&4, &mut 40)));
+    assert_eq!(cursor.remove_current(), Some((4, 40)));
+    assert_eq!(cursor.index(), None);
+    assert!(cursor.split_after().keys().eq(&[0, 1, 2]));
+    assert_eq!(cursor.index(), None);
+    assert!(map.is_empty());
+    map.check_parts();
+
+    let mut map: IndexMap<i32, i32> = (0..5).map(|i| (i, i)).collect();
+    assert!(map.cursor_mut_at_key(&5).is_none());
+    let rest = map.cursor_mut_at_key(&1).unwrap().split_after();
+    assert!(map.keys().eq(&[0, 1]));
+    assert!(rest.keys().eq(&[2, 3, 4]));
+    map.check_parts();
+    assert_eq!(rest.get_index_of(&3), Some(1));
+}
+
+#[test]
+#[should_panic = "index out of bounds"]
+fn cursor_out_of_bounds() {
+    let mut map: IndexMap<i32, i32> = IndexMap::new();
+    map.cursor_mut_at(1);
+}
+
+#[test]
+fn remove_many() {
+    // Compare with removing one at a time, for both the sweep and the
+    // per-entry index updates.
+    for len in [0, 1, 2, 7, 40] {
+        for (start, step) in [0, len * 3 / 4]
+            .iter()
+            .flat_map(|&s| (1..5).map(move |t| (s, t)))
+        {
+            let indices: Vec<usize> = (start..len).step_by(step).rev().collect();
+            let new = || -> IndexMap<usize, usize> { (0..len).map(|i| (i * 10, i)).collect() };
+
+            let mut expected = new();
+            let mut removed: Vec<_> = indices
+                .iter()
+                .map(|&i| expected.shift_remove_index(i).unwrap())
+                .collect();
+            removed.reverse();
+            let mut map = new();
+            assert_eq!(map.shift_remove_indices(&indices), removed);
+            assert!(map.iter().eq(&expected));
+            map.check_parts();
+
+            let mut map = new();
+            let keys: Vec<usize> = indices.iter().map(|&i| i * 10).collect();
+            assert_eq!(map.shift_remove_many(&keys), removed);
+            assert!(map.iter().eq(&expected));
+            map.check_parts();
+
+            // The kept entries from the end fill the holes in order.
+            let end = len - indices.len();
+            let mut fill = (end..len).filter(|i| !indices.contains(i));
+            let expected: Vec<usize> = (0..end)
+                .map(|i| match indices.contains(&i) {
+                    true => fill.next().unwrap(),
+                    false => i,
+                })
+                .collect();
+            let expected: IndexMap<usize, usize> = expected.iter().map(|&i| (i * 10, i)).collect();
+            let mut map = new();
+            assert_eq!(map.swap_remove_many(&keys), removed);
+            assert!(map.iter().eq(&expected));
+            map.check_parts();
+
+            let mut map = new();
+            let mut shuffled = indices.clone();
+            shuffled.extend(indices.iter().skip(1).step_by(2));
+            shuffled.sort_by_key(|&i| i % 3);
+            assert_eq!(map.swap_remove_indices(&shuffled), removed);
+            assert!(map.iter().eq(&expected));
+            map.check_parts();
+        }
+    }
+}
+
+#[test]
+#[should_panic = "index out of bounds"]
+fn shift_remove_indices_out_of_bounds() {
+    let mut map: IndexMap<i32, i32> = (0..3).map(|i| (i, i)).collect();
+    map.shift_remove_indices(&[1, 3]);
+}
+
+#[test]
+fn replace_key() {
+    let mut map: IndexMap<i32, char> = IndexMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+    assert_eq!(map.replace_key(1, 2), Ok(2));
+    assert_eq!(map.replace_key(1, 20), Ok(2));
+    let err = map.replace_key(0, 3).unwrap_err();
+    assert_eq!((err.index(), *err.key()), (2, 3));
+    assert!(map.iter().eq([(&1, &'a'), (&20, &'b'), (&3, &'c')]));
+    map.check_parts();
+
+    assert_eq!(map.rename_key(&20, 2), Ok(20));
+    assert_eq!(map.rename_key(&20, 4), Err(RenameError::NotFound(4)));
+    match map.rename_key(&1, 2) {
+        Err(RenameError::Conflict(err)) => assert_eq!((err.index(), err.into_key()), (1, 2)),
+        result => panic!("unexpected {:?}", result),
+    }
+    assert_eq!(map.get_index_of(&2), Some(1));
+    assert_eq!(map.get(&20), None);
+    map.check_parts();
+}
+
+#[test]
+#[should_panic = "index out of bounds"]
+fn replace_key_out_of_bounds() {
+    let mut map: IndexMap<i32, i32> = (0..3).map(|i| (i, i)).collect();
+    let _ = map.replace_key(3, 5);
+}
+
+#[test]
+fn rehash_keys() {
+    let mut map: IndexMap<i32, i32> = (0..10).map(|i| (i, i)).collect();
+    assert_eq!(map.rehash_keys(), []);
+
+    // Map keys onto 0..4 so that only the first of each class survives.
+    map.retain2(|key, _| {
+        *key = (*key * 7) % 4;
+        true
+    });
+    let removed = map.rehash_keys();
+    let expected = [(0, 0), (3, 1), (2, 2), (1, 3)];
+    assert!(map.iter().map(|(&k, &v)| (k, v)).eq(expected));
+    map.check_parts();
+    for &(index, key, value) in &removed {
+        assert_eq!(map.get_index(index).map(|(&k, _)| k), Some(key));
+        assert_eq!((value * 7) % 4, key);
+    }
+    let values: Vec<_> = removed.iter().map(|&(_, _, v)| v).collect();
+    assert_eq!(values, [4, 5, 6, 7, 8, 9]);
+
+    // Editing a key without making a duplicate keeps every entry.
+    *map.get_index_mut2(1).unwrap().0 = 10;
+    assert_eq!(map.get(&10), None);
+    assert_eq!(map.rehash_keys(), []);
+    assert_eq!(map.get_index_of(&10), Some(1));
+    map.check_parts();
+}
+
+#[derive(Default)]
+struct Events(Vec<(char, usize, usize)>);
+
+impl IndexObserver for Events {
+    fn inserted(&mut self, index: usize) {
+        self.0.push(('+', index, index));
+    }
+
+    fn removed(&mut self, index: usize) {
+        self.0.push(('-', index, index));
+    }
+
+    fn relocated(&mut self, old: usize, new: usize) {
+        self.0.push(('>', old, new));
+    }
+}
+
+#[test]
+fn observed_entries() {
+    let mut map: IndexMap<char, i32> = ('a'..='d').zip(0..).collect();
+    let mut view = map.observed(Events::default());
+
+    *view.entry('e').or_default() += 1;
+    *view.entry('a').or_default() += 1;
+    assert_eq!(view.observer().0, [('+', 4, 4)]);
+
+    view.observer_mut().0.clear();
+    match view.entry('x') {
+        ObservedEntry::Vacant(entry) => entry.shift_insert(1, 9),
+        ObservedEntry::Occupied(_) => unreachable!(),
+    };
+    let expected = [
+        ('>', 1, 2),
+        ('>', 2, 3),
+        ('>', 3, 4),
+        ('>', 4, 5),
+        ('+', 1, 1),
+    ];
+    assert_eq!(view.observer().0, expected);
+
+    view.observer_mut().0.clear();
+    match view.entry('b') {
+        ObservedEntry::Occupied(entry) => entry.swap_remove_entry(),
+        ObservedEntry::Vacant(_) => unreachable!(),
+    };
+    assert_eq!(view.observer().0, [('-', 2, 2), ('>', 5, 2)]);
+
+    view.observer_mut().0.clear();
+    match view.raw_entry_mut_v1().from_key(&'x') {
+        ObservedRawEntryMut::Occupied(entry) => entry.move_index(3),
+        ObservedRawEntryMut::Vacant(_) => unreachable!(),
+    };
+    assert_eq!(view.observer().0, [('>', 1, 3), ('>', 2, 1), ('>', 3, 2)]);
+
+    view.observer_mut().0.clear();
+    match view.raw_entry_mut_v1().from_key(&'a') {
+        ObservedRawEntryMut::Occupied(entry) => entry.shift_remove(),
+        ObservedRawEntryMut::Vacant(_) => unreachable!(),
+    };
+    let expected = [
+        ('-', 0, 0),
+        ('>', 1, 0),
+        ('>', 2, 1),
+        ('>', 3, 2),
+        ('>', 4, 3),
+    ];
+    assert_eq!(view.observer().0, expected);
+
+    drop(view);
+    assert!(map.keys().eq(&['e', 'c', 'x', 'd']));
+    map.check_parts();
+}
+
+#[test]
+fn observed_composite_ops() {
+    let mut map: IndexMap<char, i32> = ('a'..='d').zip(0..).collect();
+    let mut view = map.observed(Events::default());
+
+    // The drained pairs are already gone when the iterator is returned.
+    std::mem::forget(view.drain(1..2));
+    assert_eq!(view.observer().0, [('-', 1, 1), ('>', 2, 1), ('>', 3, 2)]);
+    assert!(view.keys().eq(&['a', 'c', 'd']));
+
+    view.observer_mut().0.clear();
+    assert!(view.split_off(2).keys().eq(&['d']));
+    assert_eq!(view.observer().0, [('-', 2, 2)]);
+
+    view.observer_mut().0.clear();
+    let mut cursor = view.cursor_mut_at(1);
+    assert_eq!(cursor.insert_before('x', 9), None);
+    assert_eq!(cursor.insert_after('a', 5), Some(0));
+    assert_eq!(cursor.insert_after('c', 6), Some(2));
+    let expected = [
+        ('>', 1, 2),
+        ('+', 1, 1),
+        ('>', 0, 2),
+        ('>', 1, 0),
+        ('>', 2, 1),
+    ];
+    assert_eq!(view.observer().0, expected);
+    assert!(view.keys().eq(&['x', 'c', 'a']));
+
+    // A patch is reported as a whole, not as its intermediate steps.
+    view.observer_mut().0.clear();
+    let target: IndexMap<char, i32> = [('a', 5), ('c', 6), ('y', 7)].into_iter().collect();
+    let patch = view.diff(&target);
+    view.apply_patch(patch);
+    assert_eq!(view.observer().0, [('-', 0, 0), ('>', 2, 0), ('+', 2, 2)]);
+
+    view.observer_mut().0.clear();
+    match view.get_index_entry(0) {
+        Some(entry) => entry.swap_indices(2),
+        None => unreachable!(),
+    }
+    assert_eq!(view.observer().0, [('>', 0, 2), ('>', 2, 0)]);
+
+    drop(view);
+    assert!(map.keys().eq(&['y', 'c', 'a']));
+    map.check_parts();
+}
@@ -0,0 +1,495 @@
+//! All-or-nothing batches of edits on an [`IndexMap`].
+
+use super::core::IndexMapCore;
+use super::{Bucket, Entries, IndexMap};
+use crate::util::permute;
+use crate::Equivalent;
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+use core::ops::Deref;
+
+/// A pending batch of edits on an [`IndexMap`], which is rolled back unless
+/// it is committed.
+///
+/// This `struct` is created by the [`IndexMap::checkpoint`] method, and it is
+/// also used by [`IndexMap::transaction`]. See their documentation for more.
+///
+/// Every edit records how to undo it, so a rollback restores the exact prior
+/// contents and order of the map, reusing the stored hashes of the entries
+/// instead of hashing their keys again. Since the replaced and removed data
+/// is kept in that undo log until the transaction ends, the methods that
+/// would return it from the map only return a reference to it here.
+///
+/// The transaction dereferences to the map for read-only access.
+pub struct Transaction<'a, K, V, S> {
+    map: &'a mut IndexMap<K, V, S>,
+    log: Vec<Undo<K, V>>,
+}
+
+/// The inverse of a single edit.
+enum Undo<K, V> {
+    /// A new entry was inserted at this index.
+    Inserted(usize),
+    /// The value at this index was replaced.
+    Replaced(usize, V),
+    /// This entry was removed from its index, and the last entry took its place.
+    SwapRemoved(usize, Bucket<K, V>),
+    /// This entry was removed from its index, shifting all that followed.
+    ShiftRemoved(usize, Bucket<K, V>),
+    /// These entries were removed from the end.
+    Truncated(Vec<Bucket<K, V>>),
+    /// These entries were removed from their indices, in ascending order.
+    Retained(Vec<(usize, Bucket<K, V>)>),
+    /// An entry was moved from one index to another.
+    Moved(usize, usize),
+    /// Two entries were swapped.
+    Swapped(usize, usize),
+    /// The entries were reordered, so the new index `i` holds the old entry `order[i]`.
+    Permuted(Vec<usize>),
+}
+
+impl<K, V> Undo<K, V> {
+    fn apply(self, core: &mut IndexMapCore<K, V>) {
+        match self {
+            Undo::Inserted(index) => {
+                core.shift_remove_index(index);
+            }
+            Undo::Replaced(index, value) => core.as_entries_mut()[index].value = value,
+            Undo::SwapRemoved(index, entry) => {
+                let last = core.insert_unique(entry.hash, entry.key, entry.value);
+                core.swap_indices(index, last);
+            }
+            Undo::ShiftRemoved(index, entry) => {
+                core.shift_insert_unique(index, entry.hash, entry.key, entry.value);
+            }
+            Undo::Truncated(entries) => {
+                for entry in entries {
+                    core.insert_unique(entry.hash, entry.key, entry.value);
+                }
+            }
+            Undo::Retained(removed) => {
+                // Merge the removed entries back between the kept ones.
+                let kept: Vec<_> = core.drain(..).collect();
+                let mut kept = kept.into_iter();
+                let mut removed = removed.into_iter().peekable();
+                for index in 0..kept.len() + removed.len() {
+                    let entry = match removed.next_if(|&(i, _)| i == index) {
+                        Some((_, entry)) => entry,
+                        None => kept.next().expect("kept entry"),
+                    };
+                    core.insert_unique(entry.hash, entry.key, entry.value);
+                }
+            }
+            Undo::Moved(from, to) => core.move_index(to, from),
+            Undo::Swapped(a, b) => core.swap_indices(a, b),
+            Undo::Permuted(order) => {
+                let mut inverse = alloc::vec![0; order.len()];
+                for (new, &old) in order.iter().enumerate() {
+                    inverse[old] = new;
+                }
+                core.with_entries(|entries| permute(entries, &inverse));
+            }
+        }
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S> {
+    /// Starts a batch of edits on the map, which are all undone again unless
+    /// [`commit`][Transaction::commit] is called.
+    ///
+    /// Dropping the transaction without committing it rolls it back, even
+    /// during a panic. Leaking it leaves the map with the edits made so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map = IndexMap::from([("a", 1), ("b", 2), ("c", 3)]);
+    ///
+    /// let mut tx = map.checkpoint();
+    /// tx.shift_remove("a");
+    /// tx.insert("b", 20);
+    /// tx.insert("d", 4);
+    /// assert_eq!(tx.keys().collect::<Vec<_>>(), [&"b", &"c", &"d"]);
+    /// tx.rollback();
+    ///
+    /// assert_eq!(map, IndexMap::from([("a", 1), ("b", 2), ("c", 3)]));
+    /// assert_eq!(map.keys().collect::<Vec<_>>(), [&"a", &"b", &"c"]);
+    /// ```
+    pub fn checkpoint(&mut self) -> Transaction<'_, K, V, S> {
+        Transaction {
+            map: self,
+            log: Vec::new(),
+        }
+    }
+
+    /// Runs a batch of edits on the map, keeping them only if `f` returns `Ok`.
+    ///
+    /// If `f` returns `Err` or panics, the map is restored to its exact prior
+    /// contents and order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map = IndexMap::from([("a", 1), ("b", 2)]);
+    ///
+    /// let result = map.transaction(|tx| {
+    ///     tx.swap_remove("a");
+    ///     match tx.get_mut("b") {
+    ///         Some(b) if *b > 10 => Ok(()),
+    ///         _ => Err("no big b"),
+    ///     }
+    /// });
+    /// assert_eq!(result, Err("no big b"));
+    /// assert_eq!(map, IndexMap::from([("a", 1), ("b", 2)]));
+    ///
+    /// map.transaction(|tx| {
+    ///     tx.swap_remove("a");
+    ///     Ok::<_, ()>(())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(map, IndexMap::from([("b", 2)]));
+    /// ```
+    pub fn transaction<T, E, F>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&mut Transaction<'_, K, V, S>) -> Result<T, E>,
+    {
+        let mut tx = self.checkpoint();
+        let result = f(&mut tx);
+        if result.is_ok() {
+            tx.commit();
+        }
+        result
+    }
+}
+
+impl<K, V, S> Deref for Transaction<'_, K, V, S> {
+    type Target = IndexMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.map
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for Transaction<'_, K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction")
+            .field("map", &*self.map)
+            .field("edits", &self.log.len())
+            .finish()
+    }
+}
+
+impl<K, V, S> Drop for Transaction<'_, K, V, S> {
+    fn drop(&mut self) {
+        self.undo_all();
+    }
+}
+
+impl<K, V, S> Transaction<'_, K, V, S> {
+    fn undo_all(&mut self) {
+        while let Some(undo) = self.log.pop() {
+            undo.apply(&mut self.map.core);
+        }
+    }
+
+    /// Return the value that the last edit kept in the log.
+    fn logged_value(&self) -> &V {
+        match self.log.last() {
+            Some(Undo::Replaced(_, value)) => value,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Return the entry that the last edit kept in the log.
+    fn logged_entry(&self) -> (usize, &K, &V) {
+        match self.log.last() {
+            Some(Undo::SwapRemoved(index, entry) | Undo::ShiftRemoved(index, entry)) => {
+                (*index, &entry.key, &entry.value)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Keeps all edits made by the transaction.
+    pub fn commit(mut self) {
+        self.log.clear();
+    }
+
+    /// Undoes all edits made by the transaction.
+    ///
+    /// This is the same as dropping the transaction.
+    pub fn rollback(mut self) {
+        self.undo_all();
+    }
+
+    /// Get a key-value pair by index, with mutable access to the value.
+    ///
+    /// The value is cloned first, so it can be restored on rollback.
+    ///
+    /// See [`IndexMap::get_index_mut`].
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)>
+    where
+        V: Clone,
+    {
+        let value = self.map.get_index(index)?.1.clone();
+        self.log.push(Undo::Replaced(index, value));
+        self.map.get_index_mut(index)
+    }
+
+    /// Remove the last key-value pair.
+    ///
+    /// See [`IndexMap::pop`].
+    pub fn pop(&mut self) -> Option<(&K, &V)> {
+        let index = self.map.len().checked_sub(1)?;
+        self.swap_remove_index(index)
+    }
+
+    /// Remove all key-value pairs in the map.
+    ///
+    /// See [`IndexMap::clear`].
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Shortens the map, keeping the first `len` elements and dropping the rest.
+    ///
+    /// See [`IndexMap::truncate`].
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.map.len() {
+            let removed = self.map.core.drain(len..).collect();
+            self.log.push(Undo::Truncated(removed));
+        }
+    }
+
+    /// Remove the key-value pair by index, swapping it with the last element.
+    ///
+    /// See [`IndexMap::swap_remove_index`].
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(&K, &V)> {
+        let hash = self.map.as_entries().get(index)?.hash;
+        let (key, value) = self.map.core.swap_remove_index(index)?;
+        let entry = Bucket { hash, key, value };
+        self.log.push(Undo::SwapRemoved(index, entry));
+        let (_, key, value) = self.logged_entry();
+        Some((key, value))
+    }
+
+    /// Remove the key-value pair by index, shifting all elements that follow it.
+    ///
+    /// See [`IndexMap::shift_remove_index`].
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(&K, &V)> {
+        let hash = self.map.as_entries().get(index)?.hash;
+        let (key, value) = self.map.core.shift_remove_index(index)?;
+        let entry = Bucket { hash, key, value };
+        self.log.push(Undo::ShiftRemoved(index, entry));
+        let (_, key, value) = self.logged_entry();
+        Some((key, value))
+    }
+
+    /// Moves the position of a key-value pair from one index to another.
+    ///
+    /// See [`IndexMap::move_index`].
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.map.move_index(from, to);
+        if from != to {
+            self.log.push(Undo::Moved(from, to));
+        }
+    }
+
+    /// Swaps the position of two key-value pairs in the map.
+    ///
+    /// See [`IndexMap::swap_indices`].
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.map.swap_indices(a, b);
+        if a != b {
+            self.log.push(Undo::Swapped(a, b));
+        }
+    }
+
+    /// Keep the key-value pairs where the closure `keep` returns `true`.
+    ///
+    /// Unlike [`IndexMap::retain`], the closure cannot modify the values.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let keep: Vec<bool> = self.map.iter().map(|(k, v)| keep(k, v)).collect();
+        if keep.iter().all(|&keep| keep) {
+            return;
+        }
+
+        let entries: Vec<_> = self.map.core.drain(..).collect();
+        let mut removed = Vec::new();
+        for (index, (entry, keep)) in entries.into_iter().zip(keep).enumerate() {
+            if keep {
+                self.map
+                    .core
+                    .insert_unique(entry.hash, entry.key, entry.value);
+            } else {
+                removed.push((index, entry));
+            }
+        }
+        self.log.push(Undo::Retained(removed));
+    }
+
+    /// Reverses the order of the map’s key-value pairs in place.
+    ///
+    /// See [`IndexMap::reverse`].
+    pub fn reverse(&mut self) {
+        self.map.reverse();
+        let len = self.map.len();
+        self.log.push(Undo::Permuted((0..len).rev().collect()));
+    }
+
+    /// Sort the map’s key-value pairs by the default ordering of the keys.
+    ///
+    /// See [`IndexMap::sort_keys`].
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| Ord::cmp(k1, k2));
+    }
+
+    /// Sort the map’s key-value pairs in place using the comparison
+    /// function `cmp`.
+    ///
+    /// See [`IndexMap::sort_by`].
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        let entries = self.map.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_by(|&i, &j| {
+            let (a, b) = (&entries[i], &entries[j]);
+            cmp(&a.key, &a.value, &b.key, &b.value)
+        });
+        self.map.with_entries(|entries| permute(entries, &order));
+        self.log.push(Undo::Permuted(order));
+    }
+}
+
+impl<K, V, S> Transaction<'_, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Insert a key-value pair in the map, returning the old value if the key
+    /// was already present.
+    ///
+    /// See [`IndexMap::insert`].
+    pub fn insert(&mut self, key: K, value: V) -> Option<&V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Insert a key-value pair in the map, and get their index, along with
+    /// the old value if the key was already present.
+    ///
+    /// See [`IndexMap::insert_full`].
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<&V>) {
+        let hash = self.map.hash(&key);
+        match self.map.core.insert_full(hash, key, value) {
+            (index, Some(old)) => {
+                self.log.push(Undo::Replaced(index, old));
+                (index, Some(self.logged_value()))
+            }
+            (index, None) => {
+                self.log.push(Undo::Inserted(index));
+                (index, None)
+            }
+        }
+    }
+
+    /// Insert a key-value pair in the map at the given index, returning the
+    /// old value if the key was already present.
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    ///
+    /// See [`IndexMap::shift_insert`].
+    pub fn shift_insert(&mut self, index: usize, key: K, value: V) -> Option<&V> {
+        match self.map.get_index_of(&key) {
+            Some(from) => {
+                self.move_index(from, index);
+                let old = mem::replace(&mut self.map[index], value);
+                self.log.push(Undo::Replaced(index, old));
+                Some(self.logged_value())
+            }
+            None => {
+                let hash = self.map.hash(&key);
+                self.map.core.shift_insert_unique(index, hash, key, value);
+                self.log.push(Undo::Inserted(index));
+                None
+            }
+        }
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is present.
+    ///
+    /// The value is cloned first, so it can be restored on rollback.
+    ///
+    /// See [`IndexMap::get_mut`].
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+        V: Clone,
+    {
+        let index = self.map.get_index_of(key)?;
+        self.get_index_mut(index).map(|(_, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key`, swapping it with the
+    /// last element, and return its value.
+    ///
+    /// See [`IndexMap::swap_remove`].
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.swap_remove_full(key).map(|(_, _, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key`, shifting all elements
+    /// that follow it, and return its value.
+    ///
+    /// See [`IndexMap::shift_remove`].
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.shift_remove_full(key).map(|(_, _, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key`, swapping it with the
+    /// last element, and return it and the index it had.
+    ///
+    /// See [`IndexMap::swap_remove_full`].
+    pub fn swap_remove_full<Q>(&mut self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of(key)?;
+        self.swap_remove_index(index)?;
+        Some(self.logged_entry())
+    }
+
+    /// Remove the key-value pair equivalent to `key`, shifting all elements
+    /// that follow it, and return it and the index it had.
+    ///
+    /// See [`IndexMap::shift_remove_full`].
+    pub fn shift_remove_full<Q>(&mut self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of(key)?;
+        self.shift_remove_index(index)?;
+        Some(self.logged_entry())
+    }
+}
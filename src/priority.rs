@@ -0,0 +1,510 @@
+//! [`IndexPriorityQueue`] is a priority queue whose items are also indexed
+//! by key, so they can be found, reprioritized and removed.
+
+use crate::map::{Iter, Keys};
+use crate::{Bucket, Entries, Equivalent, IndexMap};
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+/// A max-priority queue of unique keys, each with a priority.
+///
+/// The keys and priorities are stored in an [`IndexMap`], and a binary heap
+/// over their indices orders them by priority, so [`push`][Self::push],
+/// [`pop`][Self::pop], [`change_priority`][Self::change_priority] and
+/// [`remove`][Self::remove] take **O(log n)** time, while looking up a key
+/// takes **O(1)** time as in the map.
+///
+/// Iteration visits the items in insertion order, like the map, except that
+/// removing an item moves the last item into its place like
+/// [`IndexMap::swap_remove`]. Items with equal priorities are popped in an
+/// unspecified order.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::priority::IndexPriorityQueue;
+///
+/// let mut queue = IndexPriorityQueue::new();
+/// queue.push("low", 1);
+/// queue.push("high", 10);
+/// queue.push("mid", 5);
+/// assert!(queue.contains_key("mid"));
+///
+/// queue.change_priority("low", 20);
+/// assert_eq!(queue.peek(), Some((&"low", &20)));
+/// assert_eq!(queue.remove("high"), Some(("high", 10)));
+/// assert_eq!(queue.pop(), Some(("low", 20)));
+/// assert_eq!(queue.pop(), Some(("mid", 5)));
+/// assert_eq!(queue.pop(), None);
+/// ```
+#[cfg(feature = "std")]
+pub struct IndexPriorityQueue<K, P, S = RandomState> {
+    map: IndexMap<K, P, S>,
+    heap: Heap,
+}
+#[cfg(not(feature = "std"))]
+pub struct IndexPriorityQueue<K, P, S> {
+    map: IndexMap<K, P, S>,
+    heap: Heap,
+}
+
+/// The binary heap of entry indices, which does not depend on K, P or S.
+#[derive(Clone)]
+struct Heap {
+    /// entry indices, in heap order.
+    slots: Vec<usize>,
+    /// the slot in `slots` of each entry index.
+    positions: Vec<usize>,
+}
+
+impl Heap {
+    const fn new() -> Self {
+        Heap {
+            slots: Vec::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    fn with_capacity(n: usize) -> Self {
+        Heap {
+            slots: Vec::with_capacity(n),
+            positions: Vec::with_capacity(n),
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.slots.swap(a, b);
+        self.positions[self.slots[a]] = a;
+        self.positions[self.slots[b]] = b;
+    }
+
+    /// Add a new entry index, which must be the next one.
+    fn push<K, P: Ord>(&mut self, entries: &[Bucket<K, P>]) {
+        let index = self.positions.len();
+        self.positions.push(self.slots.len());
+        self.slots.push(index);
+        self.sift_up(entries, self.slots.len() - 1);
+    }
+
+    /// Restore the heap order after the priority of `index` changed.
+    fn update<K, P: Ord>(&mut self, entries: &[Bucket<K, P>], index: usize) {
+        let slot = self.sift_up(entries, self.positions[index]);
+        self.sift_down(entries, slot);
+    }
+
+    /// Remove entry `index` after it was swap-removed from the entries.
+    fn swap_remove<K, P: Ord>(&mut self, entries: &[Bucket<K, P>], index: usize) {
+        let slot = self.positions[index];
+        let last_slot = self.slots.len() - 1;
+        if slot != last_slot {
+            self.swap(slot, last_slot);
+        }
+        self.slots.pop();
+
+        // The last entry moves to `index`.
+        self.positions.swap_remove(index);
+        if let Some(&moved) = self.positions.get(index) {
+            self.slots[moved] = index;
+        }
+
+        if slot < self.slots.len() {
+            let slot = self.sift_up(entries, slot);
+            self.sift_down(entries, slot);
+        }
+    }
+
+    fn sift_up<K, P: Ord>(&mut self, entries: &[Bucket<K, P>], mut slot: usize) -> usize {
+        while slot > 0 {
+            let parent = (slot - 1) / 2;
+            if entries[self.slots[slot]].value <= entries[self.slots[parent]].value {
+                break;
+            }
+            self.swap(slot, parent);
+            slot = parent;
+        }
+        slot
+    }
+
+    fn sift_down<K, P: Ord>(&mut self, entries: &[Bucket<K, P>], mut slot: usize) {
+        let priority = |slot: usize, slots: &[usize]| &entries[slots[slot]].value;
+        loop {
+            let mut largest = slot;
+            for child in [2 * slot + 1, 2 * slot + 2] {
+                if child < self.slots.len()
+                    && priority(child, &self.slots) > priority(largest, &self.slots)
+                {
+                    largest = child;
+                }
+            }
+            if largest == slot {
+                break;
+            }
+            self.swap(slot, largest);
+            slot = largest;
+        }
+    }
+}
+
+impl<K, P, S> Clone for IndexPriorityQueue<K, P, S>
+where
+    K: Clone,
+    P: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        IndexPriorityQueue {
+            map: self.map.clone(),
+            heap: self.heap.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, P: fmt::Debug, S> fmt::Debug for IndexPriorityQueue<K, P, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.map, f)
+    }
+}
+
+impl<K, P, S: Default> Default for IndexPriorityQueue<K, P, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<K, P> IndexPriorityQueue<K, P> {
+    /// Create a new queue. (Does not allocate.)
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Create a new queue with capacity for `n` items.
+    /// (Does not allocate if `n` is zero.)
+    pub fn with_capacity(n: usize) -> Self {
+        Self::with_capacity_and_hasher(n, RandomState::new())
+    }
+}
+
+impl<K, P, S> IndexPriorityQueue<K, P, S> {
+    /// Create a new queue with `hash_builder`.
+    ///
+    /// This function is `const`, so it
+    /// can be called in `static` contexts.
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        IndexPriorityQueue {
+            map: IndexMap::with_hasher(hash_builder),
+            heap: Heap::new(),
+        }
+    }
+
+    /// Create a new queue with capacity for `n` items, and with
+    /// `hash_builder`. (Does not allocate if `n` is zero.)
+    pub fn with_capacity_and_hasher(n: usize, hash_builder: S) -> Self {
+        IndexPriorityQueue {
+            map: IndexMap::with_capacity_and_hasher(n, hash_builder),
+            heap: Heap::with_capacity(n),
+        }
+    }
+
+    /// Return the number of items in the queue.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the queue contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return a reference to the queue's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    /// Return the keys and priorities of the queue as a map, in its
+    /// iteration order.
+    pub fn as_map(&self) -> &IndexMap<K, P, S> {
+        &self.map
+    }
+
+    /// Return an iterator over the keys and priorities, in insertion order.
+    pub fn iter(&self) -> Iter<'_, K, P> {
+        self.map.iter()
+    }
+
+    /// Return an iterator over the keys, in insertion order.
+    pub fn keys(&self) -> Keys<'_, K, P> {
+        self.map.keys()
+    }
+
+    /// Remove all items from the queue.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.heap.slots.clear();
+        self.heap.positions.clear();
+    }
+
+    /// Return the item with the greatest priority, or `None` if the queue
+    /// is empty.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        let &index = self.heap.slots.first()?;
+        self.map.get_index(index)
+    }
+
+    /// Remove and return the item with the greatest priority, or `None` if
+    /// the queue is empty.
+    ///
+    /// Computes in **O(log n)** time (average).
+    pub fn pop(&mut self) -> Option<(K, P)>
+    where
+        P: Ord,
+    {
+        let &index = self.heap.slots.first()?;
+        Some(self.swap_remove_index(index))
+    }
+
+    fn swap_remove_index(&mut self, index: usize) -> (K, P)
+    where
+        P: Ord,
+    {
+        let removed = self.map.swap_remove_index(index).expect("index not found");
+        self.heap.swap_remove(self.map.as_entries(), index);
+        removed
+    }
+}
+
+impl<K, P, S> IndexPriorityQueue<K, P, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Return `true` if an equivalent to `key` exists in the queue.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Return a reference to the priority of `key`, if it is present.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get<Q>(&self, key: &Q) -> Option<&P>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get(key)
+    }
+
+    /// Return the key and priority equivalent to `key`, if it is present.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &P)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get_key_value(key)
+    }
+}
+
+impl<K, P, S> IndexPriorityQueue<K, P, S>
+where
+    K: Hash + Eq,
+    P: Ord,
+    S: BuildHasher,
+{
+    /// Insert `key` with `priority` into the queue.
+    ///
+    /// If an equivalent key already exists in the queue, its priority is
+    /// changed and the old priority is returned instead. The key itself is
+    /// not updated.
+    ///
+    /// Computes in **O(log n)** time (average).
+    pub fn push(&mut self, key: K, priority: P) -> Option<P> {
+        let (index, old) = self.map.insert_full(key, priority);
+        match old {
+            None => self.heap.push(self.map.as_entries()),
+            Some(_) => self.heap.update(self.map.as_entries(), index),
+        }
+        old
+    }
+
+    /// Change the priority of `key`, returning the old priority, or `None`
+    /// if it is not in the queue.
+    ///
+    /// Computes in **O(log n)** time (average).
+    pub fn change_priority<Q>(&mut self, key: &Q, priority: P) -> Option<P>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let (index, _, value) = self.map.get_full_mut(key)?;
+        let old = mem::replace(value, priority);
+        self.heap.update(self.map.as_entries(), index);
+        Some(old)
+    }
+
+    /// Change the priority of `key` in place with `f`, returning `false` if
+    /// it is not in the queue.
+    ///
+    /// Computes in **O(log n)** time (average).
+    pub fn change_priority_by<Q, F>(&mut self, key: &Q, f: F) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+        F: FnOnce(&mut P),
+    {
+        match self.map.get_full_mut(key) {
+            Some((index, _, value)) => {
+                f(value);
+                self.heap.update(self.map.as_entries(), index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove `key` from the queue and return it with its priority, or `None`
+    /// if it is not in the queue.
+    ///
+    /// Like [`IndexMap::swap_remove`], this moves the last item into the
+    /// place of the removed one in the iteration order.
+    ///
+    /// Computes in **O(log n)** time (average).
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, P)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of(key)?;
+        Some(self.swap_remove_index(index))
+    }
+}
+
+impl<'a, K, P, S> IntoIterator for &'a IndexPriorityQueue<K, P, S> {
+    type Item = (&'a K, &'a P);
+    type IntoIter = Iter<'a, K, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, P, S> Extend<(K, P)> for IndexPriorityQueue<K, P, S>
+where
+    K: Hash + Eq,
+    P: Ord,
+    S: BuildHasher,
+{
+    /// Push all items of the iterator, changing the priorities of keys that
+    /// are already in the queue.
+    fn extend<I: IntoIterator<Item = (K, P)>>(&mut self, iterable: I) {
+        for (key, priority) in iterable {
+            self.push(key, priority);
+        }
+    }
+}
+
+impl<K, P, S> FromIterator<(K, P)> for IndexPriorityQueue<K, P, S>
+where
+    K: Hash + Eq,
+    P: Ord,
+    S: BuildHasher + Default,
+{
+    /// Create a queue from the iterator, where later priorities of a key
+    /// replace earlier ones.
+    fn from_iter<I: IntoIterator<Item = (K, P)>>(iterable: I) -> Self {
+        let iter = iterable.into_iter();
+        let (low, _) = iter.size_hint();
+        let mut queue = Self::with_capacity_and_hasher(low, S::default());
+        queue.extend(iter);
+        queue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(queue: &IndexPriorityQueue<u32, u32>) {
+        let heap = &queue.heap;
+        let entries = queue.map.as_entries();
+        assert_eq!(heap.slots.len(), entries.len());
+        assert_eq!(heap.positions.len(), entries.len());
+        for (slot, &index) in heap.slots.iter().enumerate() {
+            assert_eq!(heap.positions[index], slot);
+            if slot > 0 {
+                assert!(entries[index].value <= entries[heap.slots[(slot - 1) / 2]].value);
+            }
+        }
+    }
+
+    #[test]
+    fn against_sorted() {
+        let mut queue = IndexPriorityQueue::new();
+        let mut expected = Vec::new();
+        let mut x = 1u32;
+        for _ in 0..1000 {
+            // A simple linear congruential generator.
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let key = (x >> 16) % 64;
+            let priority = (x >> 8) % 100;
+            match x % 4 {
+                0 => {
+                    let old = queue.remove(&key);
+                    let found = expected.iter().position(|&(k, _)| k == key);
+                    assert_eq!(old, found.map(|i| expected.swap_remove(i)));
+                }
+                1 => {
+                    let max = expected.iter().map(|&(_, p)| p).max();
+                    let popped = queue.pop();
+                    assert_eq!(popped.map(|(_, p)| p), max);
+                    if let Some((k, _)) = popped {
+                        let i = expected.iter().position(|&(e, _)| e == k).unwrap();
+                        expected.swap_remove(i);
+                    }
+                }
+                _ => {
+                    let old = queue.push(key, priority);
+                    match expected.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, p)) => assert_eq!(old, Some(mem::replace(p, priority))),
+                        None => {
+                            assert_eq!(old, None);
+                            expected.push((key, priority));
+                        }
+                    }
+                }
+            }
+            check(&queue);
+            assert!(queue
+                .iter()
+                .map(|(&k, &p)| (k, p))
+                .eq(expected.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn change_priority() {
+        let mut queue: IndexPriorityQueue<u32, u32> = (0..10).map(|i| (i, i)).collect();
+        assert_eq!(queue.change_priority(&0, 100), Some(0));
+        assert_eq!(queue.change_priority(&10, 100), None);
+        assert!(queue.change_priority_by(&9, |p| *p = 0));
+        check(&queue);
+        assert_eq!(queue.get(&9), Some(&0));
+        assert_eq!(queue.peek(), Some((&0, &100)));
+
+        let popped: Vec<u32> = core::iter::from_fn(|| queue.pop())
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(popped, [0, 8, 7, 6, 5, 4, 3, 2, 1, 9]);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,443 @@
+//! [`proptest`] strategies for [`IndexMap`] and [`IndexSet`].
+//!
+//! Unlike collecting a `Vec` of entries into a map, these strategies always
+//! generate as many unique keys as the size they choose, redrawing any key
+//! that is already in the map. Their shrinking keeps the insertion order:
+//! entries are removed first, then each remaining value is shrunk in place.
+//! Keys are never shrunk, so they stay unique and in the same order.
+//!
+//! ```
+//! use indexmap::proptest::index_map;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn len_in_range(map in index_map(any::<u8>(), any::<u32>(), 2..10)) {
+//!         prop_assert!((2..10).contains(&map.len()));
+//!     }
+//! }
+//! # len_in_range();
+//! ```
+//!
+//! The `sorted_*` variants generate their keys in sorted order instead, which
+//! shrinking also keeps. Maps and sets use [`RandomState`] by default, and
+//! another hasher can be chosen with `with_hasher`.
+
+use ::proptest::collection::SizeRange;
+use ::proptest::strategy::{Just, NewTree, Strategy, ValueTree};
+use ::proptest::test_runner::TestRunner;
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use std::collections::hash_map::RandomState;
+
+use crate::map::Entry;
+use crate::{IndexMap, IndexSet};
+
+/// Create a strategy for an [`IndexMap`] with keys from `key` and values from
+/// `value`, whose length is in `size`.
+///
+/// Duplicate keys are redrawn, so the key strategy must be able to produce
+/// enough distinct keys for the largest size, or the test will fail with too
+/// many local rejects.
+///
+/// ***Panics*** if `size` is empty.
+pub fn index_map<K, V>(key: K, value: V, size: impl Into<SizeRange>) -> IndexMapStrategy<K, V>
+where
+    K: Strategy,
+    K::Value: Hash + Eq,
+    V: Strategy,
+{
+    let size = size.into();
+    assert!(size.start() < size.end_excl(), "empty size range");
+    IndexMapStrategy {
+        key,
+        value,
+        size,
+        sort: None,
+        hash_builder: RandomState::new(),
+    }
+}
+
+/// Create a strategy like [`index_map`], whose keys are in sorted order.
+///
+/// ***Panics*** if `size` is empty.
+pub fn sorted_index_map<K, V>(
+    key: K,
+    value: V,
+    size: impl Into<SizeRange>,
+) -> IndexMapStrategy<K, V>
+where
+    K: Strategy,
+    K::Value: Ord + Hash,
+    V: Strategy,
+{
+    IndexMapStrategy {
+        sort: Some(Ord::cmp),
+        ..index_map(key, value, size)
+    }
+}
+
+/// Create a strategy for an [`IndexSet`] with values from `element`, whose
+/// length is in `size`.
+///
+/// Like [`index_map`], duplicate values are redrawn. Shrinking only removes
+/// values, since shrinking one could make it equal to another.
+///
+/// ***Panics*** if `size` is empty.
+pub fn index_set<T>(element: T, size: impl Into<SizeRange>) -> IndexSetStrategy<T>
+where
+    T: Strategy,
+    T::Value: Hash + Eq,
+{
+    IndexSetStrategy(index_map(element, Just(()), size))
+}
+
+/// Create a strategy like [`index_set`], whose values are in sorted order.
+///
+/// ***Panics*** if `size` is empty.
+pub fn sorted_index_set<T>(element: T, size: impl Into<SizeRange>) -> IndexSetStrategy<T>
+where
+    T: Strategy,
+    T::Value: Ord + Hash,
+{
+    IndexSetStrategy(sorted_index_map(element, Just(()), size))
+}
+
+type Compare<T> = fn(&T, &T) -> Ordering;
+
+/// A [`Strategy`] for an [`IndexMap`].
+///
+/// This `struct` is created by the [`index_map`] and [`sorted_index_map`]
+/// functions. See their documentation for more.
+pub struct IndexMapStrategy<K: Strategy, V, S = RandomState> {
+    key: K,
+    value: V,
+    size: SizeRange,
+    sort: Option<Compare<K::Value>>,
+    hash_builder: S,
+}
+
+impl<K, V, S> Clone for IndexMapStrategy<K, V, S>
+where
+    K: Strategy + Clone,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        IndexMapStrategy {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            size: self.size.clone(),
+            sort: self.sort,
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<K: Strategy, V, S> IndexMapStrategy<K, V, S> {
+    /// Use `hash_builder` for the generated maps, cloning it for each one.
+    pub fn with_hasher<H>(self, hash_builder: H) -> IndexMapStrategy<K, V, H>
+    where
+        H: BuildHasher + Clone,
+    {
+        IndexMapStrategy {
+            key: self.key,
+            value: self.value,
+            size: self.size,
+            sort: self.sort,
+            hash_builder,
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for IndexMapStrategy<K, V, S>
+where
+    K: Strategy,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexMapStrategy")
+            .field("key", &self.key)
+            .field("value", &self.value)
+            .field("size", &self.size)
+            .field("sorted", &self.sort.is_some())
+            .finish()
+    }
+}
+
+impl<K, V, S> Strategy for IndexMapStrategy<K, V, S>
+where
+    K: Strategy,
+    K::Value: Hash + Eq,
+    V: Strategy,
+    S: BuildHasher + Clone,
+{
+    type Tree = IndexMapValueTree<K::Tree, V::Tree, S>;
+    type Value = IndexMap<K::Value, V::Value, S>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let (start, end) = self.size.start_end_incl();
+        let len = (start..=end).new_tree(runner)?.current();
+
+        let mut keys = IndexMap::with_capacity_and_hasher(len, self.hash_builder.clone());
+        while keys.len() < len {
+            let key = self.key.new_tree(runner)?;
+            match keys.entry(key.current()) {
+                Entry::Occupied(_) => runner.reject_local("duplicate key")?,
+                Entry::Vacant(entry) => {
+                    entry.insert(key);
+                }
+            }
+        }
+        if let Some(cmp) = self.sort {
+            keys.sort_by(|k1, _, k2, _| cmp(k1, k2));
+        }
+
+        let mut entries = Vec::with_capacity(len);
+        for key in keys.into_values() {
+            entries.push((key, self.value.new_tree(runner)?));
+        }
+        Ok(IndexMapValueTree {
+            entries,
+            included: vec![true; len],
+            len,
+            min_len: start,
+            hash_builder: self.hash_builder.clone(),
+            shrink: Shrink::Remove(0),
+            prev_shrink: None,
+        })
+    }
+}
+
+/// The next step of shrinking, on the entry at the given index.
+#[derive(Clone, Copy, Debug)]
+enum Shrink {
+    Remove(usize),
+    Value(usize),
+}
+
+/// A [`ValueTree`] for an [`IndexMap`], created by [`IndexMapStrategy`].
+#[derive(Clone, Debug)]
+pub struct IndexMapValueTree<K, V, S> {
+    entries: Vec<(K, V)>,
+    included: Vec<bool>,
+    len: usize,
+    min_len: usize,
+    hash_builder: S,
+    shrink: Shrink,
+    prev_shrink: Option<Shrink>,
+}
+
+impl<K, V, S> ValueTree for IndexMapValueTree<K, V, S>
+where
+    K: ValueTree,
+    K::Value: Hash + Eq,
+    V: ValueTree,
+    S: BuildHasher + Clone,
+{
+    type Value = IndexMap<K::Value, V::Value, S>;
+
+    fn current(&self) -> Self::Value {
+        let mut map = IndexMap::with_capacity_and_hasher(self.len, self.hash_builder.clone());
+        let entries = self.entries.iter().zip(&self.included);
+        map.extend(
+            entries
+                .filter(|(_, &included)| included)
+                .map(|((key, value), _)| (key.current(), value.current())),
+        );
+        map
+    }
+
+    fn simplify(&mut self) -> bool {
+        // Remove each entry in turn while the map is above its minimum length,
+        // then shrink the values of those that remain, from first to last.
+        if let Shrink::Remove(i) = self.shrink {
+            if i < self.entries.len() && self.len > self.min_len {
+                self.included[i] = false;
+                self.len -= 1;
+                self.prev_shrink = Some(self.shrink);
+                self.shrink = Shrink::Remove(i + 1);
+                return true;
+            }
+            self.shrink = Shrink::Value(0);
+        }
+
+        while let Shrink::Value(i) = self.shrink {
+            if i >= self.entries.len() {
+                return false;
+            }
+            if self.included[i] && self.entries[i].1.simplify() {
+                self.prev_shrink = Some(self.shrink);
+                return true;
+            }
+            self.shrink = Shrink::Value(i + 1);
+        }
+        unreachable!()
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.prev_shrink {
+            None => false,
+            Some(Shrink::Remove(i)) => {
+                // Put the entry back, which can't be complicated any further.
+                self.included[i] = true;
+                self.len += 1;
+                self.prev_shrink = None;
+                true
+            }
+            Some(Shrink::Value(i)) => {
+                if self.entries[i].1.complicate() {
+                    true
+                } else {
+                    self.prev_shrink = None;
+                    false
+                }
+            }
+        }
+    }
+}
+
+/// A [`Strategy`] for an [`IndexSet`].
+///
+/// This `struct` is created by the [`index_set`] and [`sorted_index_set`]
+/// functions. See their documentation for more.
+pub struct IndexSetStrategy<T: Strategy, S = RandomState>(IndexMapStrategy<T, Just<()>, S>);
+
+impl<T: Strategy + Clone, S: Clone> Clone for IndexSetStrategy<T, S> {
+    fn clone(&self) -> Self {
+        IndexSetStrategy(self.0.clone())
+    }
+}
+
+impl<T: Strategy, S> IndexSetStrategy<T, S> {
+    /// Use `hash_builder` for the generated sets, cloning it for each one.
+    pub fn with_hasher<H>(self, hash_builder: H) -> IndexSetStrategy<T, H>
+    where
+        H: BuildHasher + Clone,
+    {
+        IndexSetStrategy(self.0.with_hasher(hash_builder))
+    }
+}
+
+impl<T: Strategy, S> fmt::Debug for IndexSetStrategy<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexSetStrategy")
+            .field("element", &self.0.key)
+            .field("size", &self.0.size)
+            .field("sorted", &self.0.sort.is_some())
+            .finish()
+    }
+}
+
+impl<T, S> Strategy for IndexSetStrategy<T, S>
+where
+    T: Strategy,
+    T::Value: Hash + Eq,
+    S: BuildHasher + Clone,
+{
+    type Tree = IndexSetValueTree<T::Tree, S>;
+    type Value = IndexSet<T::Value, S>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.0.new_tree(runner).map(IndexSetValueTree)
+    }
+}
+
+/// A [`ValueTree`] for an [`IndexSet`], created by [`IndexSetStrategy`].
+#[derive(Clone, Debug)]
+pub struct IndexSetValueTree<T, S>(IndexMapValueTree<T, Just<()>, S>);
+
+impl<T, S> ValueTree for IndexSetValueTree<T, S>
+where
+    T: ValueTree,
+    T::Value: Hash + Eq,
+    S: BuildHasher + Clone,
+{
+    type Value = IndexSet<T::Value, S>;
+
+    fn current(&self) -> Self::Value {
+        IndexSet {
+            map: self.0.current(),
+        }
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.0.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.0.complicate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::proptest::prelude::*;
+    use ::proptest::test_runner::Config;
+    use fnv::FnvBuildHasher;
+
+    #[test]
+    fn unique_keys_fill_len() {
+        let mut runner = TestRunner::deterministic();
+        // Only 8 distinct keys, so collisions are frequent.
+        let strategy = index_map(0..8u8, any::<u8>(), 8..=8);
+        for _ in 0..100 {
+            let map = strategy.new_tree(&mut runner).unwrap().current();
+            assert_eq!(map.len(), 8);
+        }
+    }
+
+    #[test]
+    fn sorted() {
+        let mut runner = TestRunner::deterministic();
+        let strategy = sorted_index_set(any::<i32>(), 0..50).with_hasher(FnvBuildHasher::default());
+        for _ in 0..100 {
+            let mut tree = strategy.new_tree(&mut runner).unwrap();
+            let is_sorted =
+                |set: IndexSet<i32, _>| Vec::from_iter(set).windows(2).all(|w| w[0] < w[1]);
+            assert!(is_sorted(tree.current()));
+            while tree.simplify() {
+                assert!(is_sorted(tree.current()));
+            }
+        }
+    }
+
+    #[test]
+    fn shrink_keeps_order() {
+        let mut runner = TestRunner::deterministic();
+        let strategy = index_map(any::<u16>(), 0..100u32, 1..20);
+        for _ in 0..100 {
+            let mut tree = strategy.new_tree(&mut runner).unwrap();
+            let keys: Vec<u16> = tree.current().into_keys().collect();
+            while tree.simplify() {
+                let map = tree.current();
+                assert!(!map.is_empty());
+                let mut rest = keys.iter();
+                assert!(map.keys().all(|key| rest.any(|k| k == key)));
+            }
+        }
+    }
+
+    #[test]
+    fn minimal_failure() {
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: None,
+            ..Config::default()
+        });
+        let strategy = index_map(any::<u8>(), 0..1000u32, 0..20);
+        let result = runner.run(&strategy, |map| {
+            prop_assert!(map.values().all(|&v| v < 500));
+            Ok(())
+        });
+        match result {
+            Err(::proptest::test_runner::TestError::Fail(_, map)) => {
+                assert_eq!(map.len(), 1);
+                assert_eq!(map[0], 500);
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+}
@@ -14,7 +14,7 @@ use core::fmt;
 use core::hash::{BuildHasher, Hash};
 use core::ops::RangeBounds;
 
-use crate::map::Slice;
+use crate::map::{IndexObserver, Observed, Slice};
 use crate::Bucket;
 use crate::Entries;
 use crate::IndexMap;
@@ -494,6 +494,84 @@ where
     }
 }
 
+impl<K, V, S, O> Observed<'_, K, V, S, O>
+where
+    K: Sync,
+    V: Sync,
+    O: IndexObserver,
+{
+    /// Sort the map’s key-value pairs in parallel, by the default ordering of the keys,
+    /// and report the moved entries.
+    ///
+    /// See [`IndexMap::par_sort_keys`].
+    pub fn par_sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.par_sort_by(|k1, _, k2, _| K::cmp(k1, k2));
+    }
+
+    /// Sort the map’s key-value pairs in place and in parallel, using the comparison
+    /// function `cmp`, and report the moved entries.
+    ///
+    /// See [`IndexMap::par_sort_by`].
+    pub fn par_sort_by<F>(&mut self, cmp: F)
+    where
+        F: Fn(&K, &V, &K, &V) -> Ordering + Sync,
+    {
+        let entries = self.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.par_sort_by(|&a, &b| {
+            let (a, b) = (&entries[a], &entries[b]);
+            cmp(&a.key, &a.value, &b.key, &b.value)
+        });
+        self.permute(&order);
+    }
+
+    /// Sort the map's key-value pairs in parallel, by the default ordering of the keys,
+    /// and report the moved entries.
+    ///
+    /// See [`IndexMap::par_sort_unstable_keys`].
+    pub fn par_sort_unstable_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.par_sort_unstable_by(|k1, _, k2, _| K::cmp(k1, k2));
+    }
+
+    /// Sort the map's key-value pairs in place and in parallel, using the comparison
+    /// function `cmp`, and report the moved entries.
+    ///
+    /// See [`IndexMap::par_sort_unstable_by`].
+    pub fn par_sort_unstable_by<F>(&mut self, cmp: F)
+    where
+        F: Fn(&K, &V, &K, &V) -> Ordering + Sync,
+    {
+        let entries = self.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.par_sort_unstable_by(|&a, &b| {
+            let (a, b) = (&entries[a], &entries[b]);
+            cmp(&a.key, &a.value, &b.key, &b.value)
+        });
+        self.permute(&order);
+    }
+
+    /// Sort the map’s key-value pairs in place and in parallel, using a sort-key extraction
+    /// function, and report the moved entries.
+    ///
+    /// See [`IndexMap::par_sort_by_cached_key`].
+    pub fn par_sort_by_cached_key<T, F>(&mut self, sort_key: F)
+    where
+        T: Ord + Send,
+        F: Fn(&K, &V) -> T + Sync,
+    {
+        let entries = self.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.par_sort_by_cached_key(|&i| sort_key(&entries[i].key, &entries[i].value));
+        self.permute(&order);
+    }
+}
+
 /// A parallel mutable iterator over the values of an [`IndexMap`].
 ///
 /// This `struct` is created by the [`IndexMap::par_values_mut`] method.
@@ -649,6 +727,37 @@ mod tests {
         assert!(values.contains(&'c'));
     }
 
+    #[test]
+    fn observed_sort() {
+        struct Moves(Vec<(usize, usize)>);
+
+        impl IndexObserver for Moves {
+            fn inserted(&mut self, _: usize) {
+                unreachable!()
+            }
+
+            fn removed(&mut self, _: usize) {
+                unreachable!()
+            }
+
+            fn relocated(&mut self, old: usize, new: usize) {
+                self.0.push((old, new));
+            }
+        }
+
+        let mut map: IndexMap<_, _> = [(3, 'a'), (1, 'b'), (2, 'c'), (4, 'd')].into();
+        let mut view = map.observed(Moves(Vec::new()));
+        view.par_sort_keys();
+        assert_eq!(view.observer().0, [(1, 0), (2, 1), (0, 2)]);
+
+        view.observer_mut().0.clear();
+        view.par_sort_by_cached_key(|_, &v| core::cmp::Reverse(v));
+        assert_eq!(view.observer().0, [(3, 0), (0, 2), (2, 3)]);
+
+        drop(view);
+        assert!(map.keys().eq(&[4, 2, 1, 3]));
+    }
+
     #[test]
     fn values_mut() {
         let vec = vec![(1, 1), (2, 2), (3, 3)];
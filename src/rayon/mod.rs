@@ -8,6 +8,8 @@ use crate::vec::Vec;
 
 pub mod map;
 pub mod set;
+#[cfg(feature = "std")]
+mod sync;
 
 // This form of intermediate collection is also how Rayon collects `HashMap`.
 // Note that the order will also be preserved!
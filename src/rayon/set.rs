@@ -14,7 +14,8 @@ use core::fmt;
 use core::hash::{BuildHasher, Hash};
 use core::ops::RangeBounds;
 
-use crate::set::Slice;
+use crate::map::IndexObserver;
+use crate::set::{Observed, Slice};
 use crate::Entries;
 use crate::IndexSet;
 
@@ -560,6 +561,77 @@ where
     }
 }
 
+impl<T, S, O> Observed<'_, T, S, O>
+where
+    T: Sync,
+    O: IndexObserver,
+{
+    /// Sort the set’s values in parallel by their default ordering, and report the
+    /// moved values.
+    ///
+    /// See [`IndexSet::par_sort`].
+    pub fn par_sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.par_sort_by(T::cmp);
+    }
+
+    /// Sort the set’s values in place and in parallel, using the comparison function `cmp`,
+    /// and report the moved values.
+    ///
+    /// See [`IndexSet::par_sort_by`].
+    pub fn par_sort_by<F>(&mut self, cmp: F)
+    where
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let entries = self.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.par_sort_by(|&a, &b| cmp(&entries[a].key, &entries[b].key));
+        self.permute(&order);
+    }
+
+    /// Sort the set's values in parallel by their default ordering, and report the
+    /// moved values.
+    ///
+    /// See [`IndexSet::par_sort_unstable`].
+    pub fn par_sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.par_sort_unstable_by(T::cmp);
+    }
+
+    /// Sort the set’s values in place and in parallel, using the comparison function `cmp`,
+    /// and report the moved values.
+    ///
+    /// See [`IndexSet::par_sort_unstable_by`].
+    pub fn par_sort_unstable_by<F>(&mut self, cmp: F)
+    where
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let entries = self.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.par_sort_unstable_by(|&a, &b| cmp(&entries[a].key, &entries[b].key));
+        self.permute(&order);
+    }
+
+    /// Sort the set’s values in place and in parallel, using a key extraction function,
+    /// and report the moved values.
+    ///
+    /// See [`IndexSet::par_sort_by_cached_key`].
+    pub fn par_sort_by_cached_key<K, F>(&mut self, sort_key: F)
+    where
+        K: Ord + Send,
+        F: Fn(&T) -> K + Sync,
+    {
+        let entries = self.as_entries();
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.par_sort_by_cached_key(|&i| sort_key(&entries[i].key));
+        self.permute(&order);
+    }
+}
+
 impl<T, S> FromParallelIterator<T> for IndexSet<T, S>
 where
     T: Eq + Hash + Send,
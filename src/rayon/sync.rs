@@ -0,0 +1,107 @@
+use super::collect;
+use rayon::prelude::*;
+
+use crate::sync::{ConcurrentIndexMap, ConcurrentIndexSet};
+
+use core::hash::{BuildHasher, Hash};
+
+impl<K, V, S> FromParallelIterator<(K, V)> for ConcurrentIndexMap<K, V, S>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher + Default + Send,
+{
+    fn from_par_iter<I>(iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let mut map = Self::default();
+        map.par_extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> ParallelExtend<(K, V)> for ConcurrentIndexMap<K, V, S>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher + Send,
+{
+    /// Extend the map with the key-value pairs in their order, keeping the
+    /// existing values of duplicated keys.
+    ///
+    /// The pairs are collected from the parallel iterator first, and then
+    /// inserted serially on the calling thread. To insert from the worker
+    /// threads instead, in no particular order, call
+    /// [`ConcurrentIndexMap::get_or_insert_full`] from them.
+    fn par_extend<I>(&mut self, iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        for vec in collect(iter) {
+            self.extend(vec);
+        }
+    }
+}
+
+impl<T, S> FromParallelIterator<T> for ConcurrentIndexSet<T, S>
+where
+    T: Eq + Hash + Send,
+    S: BuildHasher + Default + Send,
+{
+    fn from_par_iter<I>(iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let mut set = Self::default();
+        set.par_extend(iter);
+        set
+    }
+}
+
+impl<T, S> ParallelExtend<T> for ConcurrentIndexSet<T, S>
+where
+    T: Eq + Hash + Send,
+    S: BuildHasher + Send,
+{
+    /// Extend the set with the values in their order.
+    ///
+    /// The values are collected from the parallel iterator first, and then
+    /// inserted serially on the calling thread. To insert from the worker
+    /// threads instead, in no particular order, call
+    /// [`ConcurrentIndexSet::insert_full`] from them.
+    fn par_extend<I>(&mut self, iter: I)
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        for vec in collect(iter) {
+            self.extend(vec);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn par_extend_keeps_order() {
+        let mut set: ConcurrentIndexSet<u32> = (0..1000).into_par_iter().map(|x| x % 700).collect();
+        set.par_extend((500..1500).into_par_iter());
+        assert!(set.iter().copied().eq(0..1500));
+    }
+
+    #[test]
+    fn insert_from_workers() {
+        let set = ConcurrentIndexSet::<u32>::new();
+        let indices: Vec<usize> = (0..10_000)
+            .into_par_iter()
+            .map(|x| set.insert_full(x % 1000).0)
+            .collect();
+        assert_eq!(set.len(), 1000);
+        for (x, index) in (0..10_000).zip(indices) {
+            assert_eq!(set.get_index(index), Some(&(x % 1000)));
+        }
+    }
+}
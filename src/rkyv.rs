@@ -0,0 +1,948 @@
+//! Zero-copy archives of [`IndexMap`] and [`IndexSet`] with [`rkyv`].
+//!
+//! An archived map keeps the entries in their order together with the stored
+//! hash of each key, plus an open-addressing index table over those hashes,
+//! so that [`ArchivedIndexMap`] can look up keys directly in the archived bytes
+//! without hashing every key again when it's loaded.
+//!
+//! Hashed lookups build a hasher with `S::default()`, so the map's hasher must
+//! produce the same hashes in every process, like [`BuildHasherDefault`] does.
+//! The archive records a fingerprint of the hasher that built it, and if the
+//! default hasher doesn't match, as with a `RandomState`, lookups fall back to
+//! comparing the keys one by one.
+//!
+//! Archives can be validated with `bytecheck` through
+//! [`rkyv::check_archived_root`] before they are used. Validation checks the
+//! index table but not the stored hashes or that the keys are unique, so a
+//! crafted archive may answer lookups wrongly, but [`Deserialize`] always
+//! hashes and inserts the keys again to build a consistent map.
+//!
+//! ```
+//! use fxhash::FxBuildHasher;
+//! use indexmap::IndexMap;
+//!
+//! type Table = IndexMap<String, u32, FxBuildHasher>;
+//!
+//! let map: Table = [("one", 1), ("two", 2), ("three", 3)]
+//!     .into_iter()
+//!     .map(|(k, v)| (k.to_string(), v))
+//!     .collect();
+//! let bytes = rkyv::to_bytes::<_, 256>(&map).unwrap();
+//!
+//! let archived = rkyv::check_archived_root::<Table>(&bytes).unwrap();
+//! assert_eq!(archived.get("two"), Some(&2));
+//! assert_eq!(archived.get_index_of("three"), Some(2));
+//! assert!(archived.keys().map(|k| k.as_str()).eq(["one", "two", "three"]));
+//! ```
+//!
+//! [`BuildHasherDefault`]: core::hash::BuildHasherDefault
+
+// `rkyv` resolves archived values by writing through raw pointers.
+#![allow(unsafe_code)]
+#![cfg_attr(docsrs, doc(cfg(any(feature = "rkyv", feature = "rkyv-custom-size"))))]
+
+use rkyv::bytecheck::{CheckBytes, Error, ErrorBox, StructCheckError};
+use rkyv::ser::{ScratchSpace, Serializer};
+use rkyv::validation::ArchiveContext;
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::{out_field, Archive, Archived, Deserialize, Fallible, FixedUsize, Serialize};
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ptr;
+use core::slice;
+
+use crate::util::{fingerprint, mix};
+use crate::{Bucket, Entries, Equivalent, HashValue, IndexMap, IndexSet};
+
+/// Marks an unused slot of the index table.
+const EMPTY: FixedUsize = FixedUsize::MAX;
+
+/// Read an archived primitive, whatever its endianness.
+#[inline]
+fn native<A: Copy + Into<T>, T>(archived: &A) -> T {
+    (*archived).into()
+}
+
+#[inline]
+fn probe_start(hash: u64, mask: usize) -> usize {
+    mix(hash) as usize & mask
+}
+
+/// Build an index table over the stored hashes, with linear probing.
+fn build_table<K, V>(entries: &[Bucket<K, V>]) -> Vec<FixedUsize> {
+    if entries.is_empty() {
+        return Vec::new();
+    }
+    assert!(
+        entries.len() < EMPTY as usize,
+        "too many entries to archive: {}",
+        entries.len()
+    );
+
+    // Keep the load factor at most 1/2, so that probe sequences stay short.
+    let mut table = alloc::vec![EMPTY; (2 * entries.len()).next_power_of_two()];
+    let mask = table.len() - 1;
+    for (i, bucket) in entries.iter().enumerate() {
+        let mut pos = probe_start(bucket.hash.get(), mask);
+        while table[pos] != EMPTY {
+            pos = (pos + 1) & mask;
+        }
+        table[pos] = i as FixedUsize;
+    }
+    table
+}
+
+/// An archived entry of an [`ArchivedIndexMap`], with the stored hash of its key.
+#[repr(C)]
+pub struct ArchivedBucket<K, V> {
+    hash: Archived<u64>,
+    key: K,
+    value: V,
+}
+
+impl<K: Archive, V: Archive> Archive for Bucket<K, V> {
+    type Archived = ArchivedBucket<K::Archived, V::Archived>;
+    type Resolver = (K::Resolver, V::Resolver);
+
+    #[inline]
+    unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+        let (fp, fo) = out_field!(out.hash);
+        self.hash.get().resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.key);
+        self.key.resolve(pos + fp, resolver.0, fo);
+        let (fp, fo) = out_field!(out.value);
+        self.value.resolve(pos + fp, resolver.1, fo);
+    }
+}
+
+impl<K, V, W> Serialize<W> for Bucket<K, V>
+where
+    K: Serialize<W>,
+    V: Serialize<W>,
+    W: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize(&self, serializer: &mut W) -> Result<Self::Resolver, W::Error> {
+        Ok((
+            self.key.serialize(serializer)?,
+            self.value.serialize(serializer)?,
+        ))
+    }
+}
+
+impl<K, V, C> CheckBytes<C> for ArchivedBucket<K, V>
+where
+    K: CheckBytes<C>,
+    V: CheckBytes<C>,
+    C: ?Sized,
+{
+    type Error = StructCheckError;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        K::check_bytes(ptr::addr_of!((*value).key), context).map_err(|e| StructCheckError {
+            field_name: "key",
+            inner: ErrorBox::new(e),
+        })?;
+        V::check_bytes(ptr::addr_of!((*value).value), context).map_err(|e| StructCheckError {
+            field_name: "value",
+            inner: ErrorBox::new(e),
+        })?;
+        Ok(&*value)
+    }
+}
+
+/// An archived [`IndexMap`].
+///
+/// This is the [`Archive::Archived`] type of `IndexMap<K, V, S>`, where `K` and
+/// `V` here are the archived key and value types. It keeps the order of the
+/// entries, and hashed lookups use the hashes stored when it was archived.
+///
+/// See the [module documentation][self] for more.
+#[repr(C)]
+pub struct ArchivedIndexMap<K, V, S> {
+    fingerprint: Archived<u64>,
+    entries: ArchivedVec<ArchivedBucket<K, V>>,
+    indices: ArchivedVec<Archived<FixedUsize>>,
+    marker: PhantomData<fn() -> S>,
+}
+
+impl<K, V, S> ArchivedIndexMap<K, V, S> {
+    /// Return the number of key-value pairs in the map.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.entries.iter(),
+        }
+    }
+
+    /// Return an iterator over the keys of the map, in their order
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            iter: self.entries.iter(),
+        }
+    }
+
+    /// Return an iterator over the values of the map, in their order
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            iter: self.entries.iter(),
+        }
+    }
+
+    /// Get a key-value pair by index
+    ///
+    /// Valid indices are *0 <= index < self.len()*
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|b| (&b.key, &b.value))
+    }
+
+    /// Get the first key-value pair
+    ///
+    /// Computes in **O(1)** time.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.entries.first().map(|b| (&b.key, &b.value))
+    }
+
+    /// Get the last key-value pair
+    ///
+    /// Computes in **O(1)** time.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(|b| (&b.key, &b.value))
+    }
+}
+
+impl<K, V, S> ArchivedIndexMap<K, V, S>
+where
+    S: BuildHasher + Default,
+{
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let hash_builder = S::default();
+        if fingerprint(&hash_builder) != native::<_, u64>(&self.fingerprint) {
+            // The stored hashes came from a different hasher.
+            return self.entries.iter().position(|b| key.equivalent(&b.key));
+        }
+
+        let mut h = hash_builder.build_hasher();
+        key.hash(&mut h);
+        let hash = HashValue(h.finish() as usize).get();
+
+        let table = self.indices.as_slice();
+        if table.is_empty() {
+            return None;
+        }
+        let mask = table.len() - 1;
+        let mut pos = probe_start(hash, mask);
+        loop {
+            let i: FixedUsize = native(&table[pos]);
+            if i == EMPTY {
+                return None;
+            }
+            let bucket = &self.entries[i as usize];
+            if native::<_, u64>(&bucket.hash) == hash && key.equivalent(&bucket.key) {
+                return Some(i as usize);
+            }
+            pos = (pos + 1) & mask;
+        }
+    }
+
+    /// Return `true` if an equivalent to `key` exists in the map.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Return a reference to the value stored for `key`, if it is present,
+    /// else `None`.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.find(key).map(|i| &self.entries[i].value)
+    }
+
+    /// Return references to the key-value pair stored for `key`,
+    /// if it is present, else `None`.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.find(key).map(|i| {
+            let bucket = &self.entries[i];
+            (&bucket.key, &bucket.value)
+        })
+    }
+
+    /// Return item index, key and value
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.find(key).map(|i| {
+            let bucket = &self.entries[i];
+            (i, &bucket.key, &bucket.value)
+        })
+    }
+
+    /// Return item index, if it exists in the map
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.find(key)
+    }
+}
+
+impl<K, V, S> fmt::Debug for ArchivedIndexMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a ArchivedIndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The resolver for an archived [`IndexMap`] or [`IndexSet`].
+pub struct IndexMapResolver {
+    fingerprint: u64,
+    entries: VecResolver,
+    indices: VecResolver,
+    table_len: usize,
+}
+
+impl<K, V, S> Archive for IndexMap<K, V, S>
+where
+    K: Archive,
+    V: Archive,
+{
+    type Archived = ArchivedIndexMap<K::Archived, V::Archived, S>;
+    type Resolver = IndexMapResolver;
+
+    unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+        let (fp, fo) = out_field!(out.fingerprint);
+        resolver.fingerprint.resolve(pos + fp, (), fo);
+        let (fp, fo) = out_field!(out.entries);
+        ArchivedVec::resolve_from_len(self.len(), pos + fp, resolver.entries, fo);
+        let (fp, fo) = out_field!(out.indices);
+        ArchivedVec::resolve_from_len(resolver.table_len, pos + fp, resolver.indices, fo);
+    }
+}
+
+impl<K, V, S, W> Serialize<W> for IndexMap<K, V, S>
+where
+    K: Serialize<W>,
+    V: Serialize<W>,
+    S: BuildHasher,
+    W: Serializer + ScratchSpace + ?Sized,
+{
+    fn serialize(&self, serializer: &mut W) -> Result<Self::Resolver, W::Error> {
+        let entries = self.as_entries();
+        let table = build_table(entries);
+        Ok(IndexMapResolver {
+            fingerprint: fingerprint(&self.hash_builder),
+            entries: ArchivedVec::serialize_from_iter::<Bucket<K, V>, _, _, _>(
+                entries.iter(),
+                serializer,
+            )?,
+            indices: ArchivedVec::serialize_from_slice(&table, serializer)?,
+            table_len: table.len(),
+        })
+    }
+}
+
+impl<K, V, S, D> Deserialize<IndexMap<K, V, S>, D> for ArchivedIndexMap<K::Archived, V::Archived, S>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    S: BuildHasher + Default,
+    D: Fallible + ?Sized,
+{
+    /// Every key is hashed and inserted again, since validation doesn't check
+    /// the stored hashes or that the keys are unique. Like [`IndexMap::extend`],
+    /// a repeated key keeps its first position and its last value.
+    fn deserialize(&self, deserializer: &mut D) -> Result<IndexMap<K, V, S>, D::Error> {
+        let mut map = IndexMap::with_capacity_and_hasher(self.len(), S::default());
+        for bucket in self.entries.iter() {
+            let key = bucket.key.deserialize(deserializer)?;
+            let value = bucket.value.deserialize(deserializer)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+/// An error from validating an [`ArchivedIndexMap`] or [`ArchivedIndexSet`].
+#[derive(Debug)]
+pub enum ArchivedIndexMapError {
+    /// The archived entries are invalid.
+    Entries(ErrorBox<dyn Error>),
+    /// The archived index table is invalid.
+    Indices(ErrorBox<dyn Error>),
+    /// The index table doesn't refer to each entry exactly once.
+    Table,
+}
+
+impl fmt::Display for ArchivedIndexMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchivedIndexMapError::Entries(e) => write!(f, "invalid entries: {}", e),
+            ArchivedIndexMapError::Indices(e) => write!(f, "invalid index table: {}", e),
+            ArchivedIndexMapError::Table => f.write_str("index table doesn't match the entries"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArchivedIndexMapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArchivedIndexMapError::Entries(e) | ArchivedIndexMapError::Indices(e) => {
+                Some(e.as_error())
+            }
+            ArchivedIndexMapError::Table => None,
+        }
+    }
+}
+
+/// Check that the table has an empty slot, and holds each index in `0..len` once.
+fn check_table(len: usize, table: &[Archived<FixedUsize>]) -> Result<(), ArchivedIndexMapError> {
+    let valid_len = if len == 0 {
+        table.is_empty()
+    } else {
+        table.len().is_power_of_two() && table.len() > len
+    };
+    if !valid_len {
+        return Err(ArchivedIndexMapError::Table);
+    }
+
+    let mut seen = alloc::vec![false; len];
+    let mut count = 0;
+    for slot in table {
+        let i: FixedUsize = native(slot);
+        if i == EMPTY {
+            continue;
+        }
+        match seen.get_mut(i as usize) {
+            Some(seen) if !*seen => *seen = true,
+            _ => return Err(ArchivedIndexMapError::Table),
+        }
+        count += 1;
+    }
+    if count != len {
+        return Err(ArchivedIndexMapError::Table);
+    }
+    Ok(())
+}
+
+impl<K, V, S, C> CheckBytes<C> for ArchivedIndexMap<K, V, S>
+where
+    K: CheckBytes<C>,
+    V: CheckBytes<C>,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = ArchivedIndexMapError;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        let entries = ArchivedVec::check_bytes(ptr::addr_of!((*value).entries), context)
+            .map_err(|e| ArchivedIndexMapError::Entries(ErrorBox::new(e)))?;
+        let indices = ArchivedVec::check_bytes(ptr::addr_of!((*value).indices), context)
+            .map_err(|e| ArchivedIndexMapError::Indices(ErrorBox::new(e)))?;
+        check_table(entries.len(), indices.as_slice())?;
+        Ok(&*value)
+    }
+}
+
+/// An iterator over the entries of an [`ArchivedIndexMap`].
+///
+/// This `struct` is created by the [`ArchivedIndexMap::iter`] method.
+/// See its documentation for more.
+pub struct Iter<'a, K, V> {
+    iter: slice::Iter<'a, ArchivedBucket<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|b| (&b.key, &b.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|b| (&b.key, &b.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the keys of an [`ArchivedIndexMap`].
+///
+/// This `struct` is created by the [`ArchivedIndexMap::keys`] method.
+/// See its documentation for more.
+pub struct Keys<'a, K, V> {
+    iter: slice::Iter<'a, ArchivedBucket<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|b| &b.key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|b| &b.key)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Keys {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for Keys<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the values of an [`ArchivedIndexMap`].
+///
+/// This `struct` is created by the [`ArchivedIndexMap::values`] method.
+/// See its documentation for more.
+pub struct Values<'a, K, V> {
+    iter: slice::Iter<'a, ArchivedBucket<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|b| &b.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|b| &b.value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Values {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<K, V: fmt::Debug> fmt::Debug for Values<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An archived [`IndexSet`].
+///
+/// This is the [`Archive::Archived`] type of `IndexSet<T, S>`, where `T` here
+/// is the archived value type. See [`ArchivedIndexMap`] for more.
+#[repr(transparent)]
+pub struct ArchivedIndexSet<T, S> {
+    map: ArchivedIndexMap<T, (), S>,
+}
+
+impl<T, S> ArchivedIndexSet<T, S> {
+    /// Return the number of elements in the set.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return an iterator over the values of the set, in their order
+    pub fn iter(&self) -> SetIter<'_, T> {
+        SetIter {
+            iter: self.map.keys(),
+        }
+    }
+
+    /// Get a value by index
+    ///
+    /// Valid indices are *0 <= index < self.len()*
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.map.get_index(index).map(|(x, _)| x)
+    }
+
+    /// Get the first value
+    ///
+    /// Computes in **O(1)** time.
+    pub fn first(&self) -> Option<&T> {
+        self.map.first().map(|(x, _)| x)
+    }
+
+    /// Get the last value
+    ///
+    /// Computes in **O(1)** time.
+    pub fn last(&self) -> Option<&T> {
+        self.map.last().map(|(x, _)| x)
+    }
+}
+
+impl<T, S> ArchivedIndexSet<T, S>
+where
+    S: BuildHasher + Default,
+{
+    /// Return `true` if an equivalent to `value` exists in the set.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Return a reference to the value stored in the set, if it is present,
+    /// else `None`.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.get_key_value(value).map(|(x, _)| x)
+    }
+
+    /// Return item index and value
+    pub fn get_full<Q>(&self, value: &Q) -> Option<(usize, &T)>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.get_full(value).map(|(i, x, _)| (i, x))
+    }
+
+    /// Return item index, if it exists in the set
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.get_index_of(value)
+    }
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for ArchivedIndexSet<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a ArchivedIndexSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = SetIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S> Archive for IndexSet<T, S>
+where
+    T: Archive,
+{
+    type Archived = ArchivedIndexSet<T::Archived, S>;
+    type Resolver = IndexMapResolver;
+
+    unsafe fn resolve(&self, pos: usize, resolver: Self::Resolver, out: *mut Self::Archived) {
+        let (fp, fo) = out_field!(out.map);
+        self.map.resolve(pos + fp, resolver, fo);
+    }
+}
+
+impl<T, S, W> Serialize<W> for IndexSet<T, S>
+where
+    T: Serialize<W>,
+    S: BuildHasher,
+    W: Serializer + ScratchSpace + ?Sized,
+{
+    fn serialize(&self, serializer: &mut W) -> Result<Self::Resolver, W::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+impl<T, S, D> Deserialize<IndexSet<T, S>, D> for ArchivedIndexSet<T::Archived, S>
+where
+    T: Archive + Hash + Eq,
+    T::Archived: Deserialize<T, D>,
+    S: BuildHasher + Default,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<IndexSet<T, S>, D::Error> {
+        Ok(IndexSet {
+            map: self.map.deserialize(deserializer)?,
+        })
+    }
+}
+
+impl<T, S, C> CheckBytes<C> for ArchivedIndexSet<T, S>
+where
+    T: CheckBytes<C>,
+    C: ArchiveContext + ?Sized,
+    C::Error: Error,
+{
+    type Error = ArchivedIndexMapError;
+
+    unsafe fn check_bytes<'a>(
+        value: *const Self,
+        context: &mut C,
+    ) -> Result<&'a Self, Self::Error> {
+        ArchivedIndexMap::check_bytes(ptr::addr_of!((*value).map), context)?;
+        Ok(&*value)
+    }
+}
+
+/// An iterator over the values of an [`ArchivedIndexSet`].
+///
+/// This `struct` is created by the [`ArchivedIndexSet::iter`] method.
+/// See its documentation for more.
+pub struct SetIter<'a, T> {
+    iter: Keys<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for SetIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for SetIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for SetIter<'_, T> {}
+
+impl<T> FusedIterator for SetIter<'_, T> {}
+
+impl<T> Clone for SetIter<'_, T> {
+    fn clone(&self) -> Self {
+        SetIter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SetIter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use fxhash::FxBuildHasher;
+    use std::collections::hash_map::RandomState;
+
+    type FxMap = IndexMap<String, u32, FxBuildHasher>;
+
+    #[test]
+    fn map_lookups() {
+        let map: FxMap = (0..500).map(|i| (i.to_string(), i)).collect();
+        let bytes = rkyv::to_bytes::<_, 4096>(&map).unwrap();
+        let archived = rkyv::check_archived_root::<FxMap>(&bytes).unwrap();
+
+        assert_eq!(archived.len(), map.len());
+        assert!(archived.values().eq(map.values()));
+        for (i, (key, value)) in map.iter().enumerate() {
+            let found = archived.get_full(key.as_str());
+            assert_eq!(
+                found.map(|(i, k, &v)| (i, k.as_str(), v)),
+                Some((i, &**key, *value))
+            );
+        }
+        assert_eq!(archived.get("500"), None);
+
+        let back: FxMap = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(back, map);
+        assert_eq!(back.get_index_of("42"), Some(42));
+    }
+
+    #[test]
+    fn random_hasher_fallback() {
+        let set: IndexSet<u64, RandomState> = (0..100).rev().collect();
+        let bytes = rkyv::to_bytes::<_, 1024>(&set).unwrap();
+        let archived = rkyv::check_archived_root::<IndexSet<u64, RandomState>>(&bytes).unwrap();
+
+        assert_eq!(archived.get_full(&7), Some((92, &7)));
+        assert!(!archived.contains(&100));
+        let back: IndexSet<u64> = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert!(back.iter().eq(&set));
+        assert_eq!(back.get_index_of(&7), Some(92));
+    }
+
+    #[test]
+    fn empty() {
+        let map = FxMap::default();
+        let bytes = rkyv::to_bytes::<_, 64>(&map).unwrap();
+        let archived = rkyv::check_archived_root::<FxMap>(&bytes).unwrap();
+        assert!(archived.is_empty());
+        assert_eq!(archived.get("a"), None);
+    }
+
+    #[test]
+    fn invalid_table() {
+        let set: IndexSet<u32, FxBuildHasher> = (0..4).collect();
+        let mut bytes = rkyv::to_bytes::<_, 256>(&set).unwrap();
+        let table: Vec<FixedUsize> = build_table(set.as_entries());
+        let size = core::mem::size_of::<FixedUsize>();
+        let start = bytes
+            .windows(size * table.len())
+            .position(|w| {
+                w.chunks(size)
+                    .map(|c| FixedUsize::from_ne_bytes(c.try_into().unwrap()))
+                    .eq(table.iter().copied())
+            })
+            .unwrap();
+
+        // Point two slots at the same entry.
+        let used = table.iter().position(|&i| i != EMPTY).unwrap();
+        let empty = table.iter().position(|&i| i == EMPTY).unwrap();
+        let slot = |pos: usize| start + size * pos..start + size * (pos + 1);
+        let entry = bytes[slot(used)].to_vec();
+        bytes[slot(empty)].copy_from_slice(&entry);
+        assert!(rkyv::check_archived_root::<IndexSet<u32, FxBuildHasher>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let set: IndexSet<u32, FxBuildHasher> = [0x1234_5678, 0x0bad_cafe].into_iter().collect();
+        let mut bytes = rkyv::to_bytes::<_, 256>(&set).unwrap();
+        let pos = |bytes: &[u8], key: u32| {
+            let key = key.to_ne_bytes();
+            bytes.windows(key.len()).position(|w| w == key).unwrap()
+        };
+
+        // Rewrite the second key as the first, which validation doesn't notice.
+        let second = pos(&bytes, 0x0bad_cafe);
+        bytes[second..second + 4].copy_from_slice(&0x1234_5678u32.to_ne_bytes());
+        let archived = rkyv::check_archived_root::<IndexSet<u32, FxBuildHasher>>(&bytes).unwrap();
+        assert_eq!(archived.len(), 2);
+
+        let back: IndexSet<u32, FxBuildHasher> =
+            archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(back.len(), 1);
+        assert_eq!(back.get_index_of(&0x1234_5678), Some(0));
+        assert_eq!(back.get_index_of(&0x0bad_cafe), None);
+    }
+}
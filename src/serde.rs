@@ -2,32 +2,21 @@
 
 use serde::de::value::{MapDeserializer, SeqDeserializer};
 use serde::de::{
-    Deserialize, Deserializer, Error, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+    Deserialize, DeserializeSeed, Deserializer, Error, IgnoredAny, IntoDeserializer, MapAccess,
+    SeqAccess, Visitor,
 };
-use serde::ser::{Serialize, Serializer};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 
+use core::cmp;
 use core::fmt::{self, Formatter};
 use core::hash::{BuildHasher, Hash};
 use core::marker::PhantomData;
-use core::{cmp, mem};
 
-use crate::{Bucket, IndexMap, IndexSet};
-
-/// Limit our preallocated capacity from a deserializer `size_hint()`.
-///
-/// We do account for the `Bucket` overhead from its saved `hash` field, but we don't count the
-/// `RawTable` allocation or the fact that its raw capacity will be rounded up to a power of two.
-/// The "max" is an arbitrary choice anyway, not something that needs precise adherence.
-///
-/// This is based on the internal `serde::de::size_hint::cautious(hint)` function.
-pub(crate) fn cautious_capacity<K, V>(hint: Option<usize>) -> usize {
-    const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
-
-    cmp::min(
-        hint.unwrap_or(0),
-        MAX_PREALLOC_BYTES / mem::size_of::<Bucket<K, V>>(),
-    )
-}
+use crate::interner::{Interner, Symbol};
+use crate::map::serde_dedup::{DuplicatePolicy, Insert};
+use crate::map::Diff;
+use crate::util::{cautious_capacity, hash_key};
+use crate::{IndexMap, IndexSet};
 
 impl<K, V, S> Serialize for IndexMap<K, V, S>
 where
@@ -42,13 +31,27 @@ where
     }
 }
 
-struct IndexMapVisitor<K, V, S>(PhantomData<(K, V, S)>);
+/// Visitor to deserialize an `IndexMap` of at most `max` entries, inserting with the policy `P`
+pub(crate) struct IndexMapVisitor<K, V, S, P> {
+    max: usize,
+    marker: PhantomData<(K, V, S, P)>,
+}
 
-impl<'de, K, V, S> Visitor<'de> for IndexMapVisitor<K, V, S>
+impl<K, V, S, P> IndexMapVisitor<K, V, S, P> {
+    pub(crate) fn new(max: usize) -> Self {
+        IndexMapVisitor {
+            max,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, V, S, P> Visitor<'de> for IndexMapVisitor<K, V, S, P>
 where
     K: Deserialize<'de> + Eq + Hash,
     V: Deserialize<'de>,
     S: Default + BuildHasher,
+    P: DuplicatePolicy,
 {
     type Value = IndexMap<K, V, S>;
 
@@ -60,13 +63,23 @@ where
     where
         A: MapAccess<'de>,
     {
-        let capacity = cautious_capacity::<K, V>(map.size_hint());
+        let capacity = cmp::min(cautious_capacity::<K, V>(map.size_hint()), self.max);
         let mut values = IndexMap::with_capacity_and_hasher(capacity, S::default());
 
+        let mut state = P::State::default();
+        let mut len = 0;
         while let Some((key, value)) = map.next_entry()? {
-            values.insert(key, value);
+            if len == self.max {
+                let msg = format_args!("map has more than {} entries", self.max);
+                return Err(A::Error::custom(msg));
+            }
+            len += 1;
+            if !P::insert(&mut state, &mut values, key, value) {
+                return Err(A::Error::custom("duplicate map key"));
+            }
         }
 
+        P::finish(state, &mut values);
         Ok(values)
     }
 }
@@ -81,7 +94,74 @@ where
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_map(IndexMapVisitor(PhantomData))
+        deserializer.deserialize_map(IndexMapVisitor::<K, V, S, Insert>::new(usize::MAX))
+    }
+
+    fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(IndexMapInPlaceVisitor(place))
+    }
+}
+
+/// Seed to deserialize a value in place with [`Deserialize::deserialize_in_place`]
+pub(crate) struct InPlaceSeed<'a, T>(pub(crate) &'a mut T);
+
+impl<'de, T> DeserializeSeed<'de> for InPlaceSeed<'_, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_in_place(deserializer, self.0)
+    }
+}
+
+/// Visitor to deserialize an `IndexMap` in place, reusing its entries and capacity
+struct IndexMapInPlaceVisitor<'a, K, V, S>(&'a mut IndexMap<K, V, S>);
+
+impl<'de, K, V, S> Visitor<'de> for IndexMapInPlaceVisitor<'_, K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let IndexMap { core, hash_builder } = self.0;
+        let additional = cautious_capacity::<K, V>(map.size_hint()).saturating_sub(core.len());
+        core.reserve(additional);
+
+        let mut refill = core.refill();
+        loop {
+            if let Some((key, value)) = refill.next_slot() {
+                if map.next_key_seed(InPlaceSeed(key))?.is_none() {
+                    break;
+                }
+                map.next_value_seed(InPlaceSeed(value))?;
+                let hash = hash_key(hash_builder, key);
+                refill.insert_slot(hash);
+            } else if let Some((key, value)) = map.next_entry::<K, V>()? {
+                refill.insert(hash_key(hash_builder, &key), key, value);
+            } else {
+                break;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -111,12 +191,26 @@ where
     }
 }
 
-struct IndexSetVisitor<T, S>(PhantomData<(T, S)>);
+/// Visitor to deserialize an `IndexSet` of at most `max` values, inserting with the policy `P`
+pub(crate) struct IndexSetVisitor<T, S, P> {
+    max: usize,
+    marker: PhantomData<(T, S, P)>,
+}
 
-impl<'de, T, S> Visitor<'de> for IndexSetVisitor<T, S>
+impl<T, S, P> IndexSetVisitor<T, S, P> {
+    pub(crate) fn new(max: usize) -> Self {
+        IndexSetVisitor {
+            max,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, S, P> Visitor<'de> for IndexSetVisitor<T, S, P>
 where
     T: Deserialize<'de> + Eq + Hash,
     S: Default + BuildHasher,
+    P: DuplicatePolicy,
 {
     type Value = IndexSet<T, S>;
 
@@ -128,13 +222,23 @@ where
     where
         A: SeqAccess<'de>,
     {
-        let capacity = cautious_capacity::<T, ()>(seq.size_hint());
+        let capacity = cmp::min(cautious_capacity::<T, ()>(seq.size_hint()), self.max);
         let mut values = IndexSet::with_capacity_and_hasher(capacity, S::default());
 
+        let mut state = P::State::default();
+        let mut len = 0;
         while let Some(value) = seq.next_element()? {
-            values.insert(value);
+            if len == self.max {
+                let msg = format_args!("set has more than {} values", self.max);
+                return Err(A::Error::custom(msg));
+            }
+            len += 1;
+            if !P::insert(&mut state, &mut values.map, value, ()) {
+                return Err(A::Error::custom("duplicate set value"));
+            }
         }
 
+        P::finish(state, &mut values.map);
         Ok(values)
     }
 }
@@ -148,7 +252,55 @@ where
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_seq(IndexSetVisitor(PhantomData))
+        deserializer.deserialize_seq(IndexSetVisitor::<T, S, Insert>::new(usize::MAX))
+    }
+
+    fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(IndexSetInPlaceVisitor(place))
+    }
+}
+
+/// Visitor to deserialize an `IndexSet` in place, reusing its values and capacity
+struct IndexSetInPlaceVisitor<'a, T, S>(&'a mut IndexSet<T, S>);
+
+impl<'de, T, S> Visitor<'de> for IndexSetInPlaceVisitor<'_, T, S>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a set")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let IndexMap { core, hash_builder } = &mut self.0.map;
+        let additional = cautious_capacity::<T, ()>(seq.size_hint()).saturating_sub(core.len());
+        core.reserve(additional);
+
+        let mut refill = core.refill();
+        loop {
+            if let Some((value, ())) = refill.next_slot() {
+                if seq.next_element_seed(InPlaceSeed(value))?.is_none() {
+                    break;
+                }
+                let hash = hash_key(hash_builder, value);
+                refill.insert_slot(hash);
+            } else if let Some(value) = seq.next_element::<T>()? {
+                refill.insert(hash_key(hash_builder, &value), value, ());
+            } else {
+                break;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -164,3 +316,262 @@ where
         SeqDeserializer::new(self.into_iter())
     }
 }
+
+impl<K, V> Serialize for Diff<K, V>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<T>(&self, serializer: T) -> Result<T::Ok, T::Error>
+    where
+        T: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Diff", 4)?;
+        state.serialize_field("removed", &self.removed)?;
+        state.serialize_field("changed", &self.changed)?;
+        state.serialize_field("moved", &self.moved)?;
+        state.serialize_field("inserted", &self.inserted)?;
+        state.end()
+    }
+}
+
+const DIFF_FIELDS: &[&str] = &["removed", "changed", "moved", "inserted"];
+
+enum DiffField {
+    Removed,
+    Changed,
+    Moved,
+    Inserted,
+    Ignore,
+}
+
+impl<'de> Deserialize<'de> for DiffField {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DiffFieldVisitor;
+
+        impl<'de> Visitor<'de> for DiffFieldVisitor {
+            type Value = DiffField;
+
+            fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+                write!(formatter, "a diff field identifier")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(match value {
+                    "removed" => DiffField::Removed,
+                    "changed" => DiffField::Changed,
+                    "moved" => DiffField::Moved,
+                    "inserted" => DiffField::Inserted,
+                    _ => DiffField::Ignore,
+                })
+            }
+        }
+
+        deserializer.deserialize_identifier(DiffFieldVisitor)
+    }
+}
+
+struct DiffVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for DiffVisitor<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = Diff<K, V>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a map diff")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        fn next<'de, T, A>(seq: &mut A, index: usize) -> Result<T, A::Error>
+        where
+            T: Deserialize<'de>,
+            A: SeqAccess<'de>,
+        {
+            match seq.next_element()? {
+                Some(value) => Ok(value),
+                None => Err(A::Error::invalid_length(index, &"a map diff")),
+            }
+        }
+
+        Ok(Diff {
+            removed: next(&mut seq, 0)?,
+            changed: next(&mut seq, 1)?,
+            moved: next(&mut seq, 2)?,
+            inserted: next(&mut seq, 3)?,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        fn set<'de, T, A>(
+            field: &mut Option<T>,
+            name: &'static str,
+            map: &mut A,
+        ) -> Result<(), A::Error>
+        where
+            T: Deserialize<'de>,
+            A: MapAccess<'de>,
+        {
+            match field {
+                Some(_) => Err(A::Error::duplicate_field(name)),
+                None => {
+                    *field = Some(map.next_value()?);
+                    Ok(())
+                }
+            }
+        }
+
+        let (mut removed, mut changed, mut moved, mut inserted) = (None, None, None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                DiffField::Removed => set(&mut removed, "removed", &mut map)?,
+                DiffField::Changed => set(&mut changed, "changed", &mut map)?,
+                DiffField::Moved => set(&mut moved, "moved", &mut map)?,
+                DiffField::Inserted => set(&mut inserted, "inserted", &mut map)?,
+                DiffField::Ignore => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Diff {
+            removed: removed.ok_or_else(|| A::Error::missing_field("removed"))?,
+            changed: changed.ok_or_else(|| A::Error::missing_field("changed"))?,
+            moved: moved.ok_or_else(|| A::Error::missing_field("moved"))?,
+            inserted: inserted.ok_or_else(|| A::Error::missing_field("inserted"))?,
+        })
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for Diff<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Diff", DIFF_FIELDS, DiffVisitor(PhantomData))
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: Serializer,
+    {
+        serializer.serialize_u32(self.as_u32())
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        u32::deserialize(deserializer).map(Symbol::from)
+    }
+}
+
+impl<S> Serialize for Interner<S> {
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: Serializer,
+    {
+        serializer.collect_seq(self.strings())
+    }
+}
+
+struct InternerVisitor<S>(PhantomData<S>);
+
+impl<'de, S> Visitor<'de> for InternerVisitor<S>
+where
+    S: Default + BuildHasher,
+{
+    type Value = Interner<S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequence of unique strings")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let capacity = cautious_capacity::<usize, ()>(seq.size_hint());
+        let mut interner = Interner::with_capacity_and_hasher(capacity, 0, S::default());
+
+        while let Some(()) = seq.next_element_seed(InternSeed(&mut interner))? {}
+
+        Ok(interner)
+    }
+}
+
+/// Interns a deserialized string without allocating it on its own, rejecting
+/// duplicates since they would shift all later symbols.
+struct InternSeed<'a, S>(&'a mut Interner<S>);
+
+impl<'de, S> DeserializeSeed<'de> for InternSeed<'_, S>
+where
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de, S> Visitor<'de> for InternSeed<'_, S>
+where
+    S: BuildHasher,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a string")
+    }
+
+    fn visit_str<E>(self, string: &str) -> Result<(), E>
+    where
+        E: Error,
+    {
+        if self.0.intern_full(string).1 {
+            Ok(())
+        } else {
+            Err(E::custom(format_args!(
+                "duplicate interned string {:?}",
+                string
+            )))
+        }
+    }
+}
+
+impl<'de, S> Deserialize<'de> for Interner<S>
+where
+    S: Default + BuildHasher,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(InternerVisitor(PhantomData))
+    }
+}
@@ -1,17 +1,47 @@
 //! A hash set implemented using [`IndexMap`]
 
+mod cursor;
 mod iter;
 mod mutable;
+mod observer;
 mod slice;
 
+pub mod raw_entry_v1;
+
+#[cfg(feature = "borsh")]
+#[cfg_attr(docsrs, doc(cfg(feature = "borsh")))]
+pub mod borsh_hashed;
+#[cfg(feature = "borsh")]
+#[cfg_attr(docsrs, doc(cfg(feature = "borsh")))]
+pub mod borsh_limits;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_dedup;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_entries;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_hashed;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_limits;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_merge;
+
 #[cfg(test)]
 mod tests;
 
+pub use self::cursor::CursorMut;
 pub use self::iter::{
     Difference, Drain, Intersection, IntoIter, Iter, Splice, SymmetricDifference, Union,
 };
 pub use self::mutable::MutableValues;
+pub use self::observer::{Observed, ObservedCursorMut};
+pub use self::raw_entry_v1::RawEntryApiV1;
 pub use self::slice::Slice;
+pub use crate::map::{AllocationSize, IndexObserver, TableStats};
 
 #[cfg(feature = "rayon")]
 pub use crate::rayon::set as rayon;
@@ -28,7 +58,7 @@ use core::fmt;
 use core::hash::{BuildHasher, Hash};
 use core::ops::{BitAnd, BitOr, BitXor, Index, RangeBounds, Sub};
 
-use super::{Entries, Equivalent, IndexMap};
+use super::{Entries, Equivalent, HashValue, IndexMap};
 
 type Bucket<T> = super::Bucket<T, ()>;
 
@@ -189,6 +219,31 @@ impl<T, S> IndexSet<T, S> {
         }
     }
 
+    /// Create a set from the parts of [`into_parts`][Self::into_parts], without
+    /// hashing any values.
+    ///
+    /// Each entry is a `(hash, value)`, in order. The hashes must be those that
+    /// `hash_builder` computes for the values, and the values must be unique,
+    /// but neither is checked. Otherwise, the set's lookups will be wrong, and
+    /// other methods may panic, but this is not undefined behavior.
+    ///
+    /// See [`from_parts`][Self::from_parts] to check the parts in debug builds.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn from_parts_unchecked(entries: Vec<(u64, T)>, hash_builder: S) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|(hash, key)| Bucket {
+                hash: HashValue(hash as usize),
+                key,
+                value: (),
+            })
+            .collect();
+        IndexSet {
+            map: IndexMap::from_entries(entries, hash_builder),
+        }
+    }
+
     /// Return the number of elements the set can hold without reallocating.
     ///
     /// This number is a lower bound; the set might be able to hold more,
@@ -199,6 +254,24 @@ impl<T, S> IndexSet<T, S> {
         self.map.capacity()
     }
 
+    /// Return the bytes allocated for the values and for the hash table,
+    /// counting their capacity whether it is used or not.
+    ///
+    /// See [`IndexMap::allocation_size`] for more.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn allocation_size(&self) -> AllocationSize {
+        self.map.allocation_size()
+    }
+
+    /// Return statistics about the set's hash table, like its load factor
+    /// and how far its indices are from their ideal position.
+    ///
+    /// Computes in **O(n log n)** time.
+    pub fn stats(&self) -> TableStats {
+        self.map.stats()
+    }
+
     /// Return a reference to the set's `BuildHasher`.
     pub fn hasher(&self) -> &S {
         self.map.hasher()
@@ -501,6 +574,40 @@ where
     {
         Splice::new(self, range, replace_with.into_iter())
     }
+
+    /// Create a set from the parts of [`into_parts`][Self::into_parts], without
+    /// hashing any values in release builds.
+    ///
+    /// This is like [`from_parts_unchecked`][Self::from_parts_unchecked], but
+    /// debug builds check that each hash matches its value and that the values
+    /// are unique.
+    ///
+    /// ***Panics*** in debug builds if the parts are not consistent.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn from_parts(entries: Vec<(u64, T)>, hash_builder: S) -> Self {
+        let set = Self::from_parts_unchecked(entries, hash_builder);
+        if cfg!(debug_assertions) {
+            set.map.check_parts();
+        }
+        set
+    }
+
+    /// Convert the set to use another hasher, hashing all values again.
+    ///
+    /// This reuses the allocations of the values and of the hash table, only
+    /// recomputing the saved hashes, so the order and indices of the values
+    /// are unchanged.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn with_new_hasher<S2>(self, hash_builder: S2) -> IndexSet<T, S2>
+    where
+        S2: BuildHasher,
+    {
+        IndexSet {
+            map: self.map.with_new_hasher(hash_builder),
+        }
+    }
 }
 
 impl<T, S> IndexSet<T, S>
@@ -670,6 +777,56 @@ where
     {
         self.map.shift_remove_full(value).map(|(i, x, ())| (i, x))
     }
+
+    /// Remove the values equivalent to any of `values`, and return them in the
+    /// order they had in the set.
+    ///
+    /// Like [`swap_remove`][Self::swap_remove], the vacated positions are
+    /// filled by the remaining values from the end of the set, keeping their
+    /// relative order. **This perturbs the position of what used to be the
+    /// last elements!**
+    ///
+    /// Values that are not in the set are ignored.
+    ///
+    /// Computes in **O(m log m)** time (average), for *m* values.
+    pub fn swap_remove_many<'q, Q, I>(&mut self, values: I) -> Vec<T>
+    where
+        Q: ?Sized + Hash + Equivalent<T> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let removed = self.map.swap_remove_many(values);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+
+    /// Remove the values equivalent to any of `values`, and return them in the
+    /// order they had in the set.
+    ///
+    /// Like [`shift_remove`][Self::shift_remove], the values are removed by
+    /// shifting all of the elements that follow them, preserving their
+    /// relative order, but the indices are all corrected at once.
+    /// **This perturbs the index of all of those elements!**
+    ///
+    /// Values that are not in the set are ignored.
+    ///
+    /// Computes in **O(n + m log m)** time (average), for *m* values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexSet;
+    ///
+    /// let mut set: IndexSet<i32> = (0..6).collect();
+    /// assert_eq!(set.shift_remove_many(&[4, 1, 9]), [1, 4]);
+    /// assert!(set.iter().eq(&[0, 2, 3, 5]));
+    /// ```
+    pub fn shift_remove_many<'q, Q, I>(&mut self, values: I) -> Vec<T>
+    where
+        Q: ?Sized + Hash + Equivalent<T> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let removed = self.map.shift_remove_many(values);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
 }
 
 impl<T, S> IndexSet<T, S> {
@@ -860,6 +1017,33 @@ impl<T, S> IndexSet<T, S> {
         Slice::from_boxed(self.into_entries().into_boxed_slice())
     }
 
+    /// Take the set apart into its ordered values, with their hashes, and its
+    /// `BuildHasher`.
+    ///
+    /// Each entry is a `(hash, value)`, so the set can be rebuilt with
+    /// [`from_parts`][Self::from_parts] without hashing the values again.
+    ///
+    /// Computes in **O(n)** time.
+    ///
+    /// ```
+    /// use indexmap::IndexSet;
+    ///
+    /// let set = IndexSet::from(["a", "b"]);
+    /// let (entries, hasher) = set.into_parts();
+    /// let set = IndexSet::from_parts(entries, hasher);
+    /// assert!(set.contains("b"));
+    /// ```
+    pub fn into_parts(self) -> (Vec<(u64, T)>, S) {
+        let entries = self
+            .map
+            .core
+            .into_entries()
+            .into_iter()
+            .map(|bucket| (bucket.hash.get(), bucket.key))
+            .collect();
+        (entries, self.map.hash_builder)
+    }
+
     /// Get a value by index
     ///
     /// Valid indices are *0 <= index < self.len()*
@@ -920,6 +1104,37 @@ impl<T, S> IndexSet<T, S> {
         self.map.shift_remove_index(index).map(|(x, ())| x)
     }
 
+    /// Remove the values at all of these indices, and return them in the
+    /// order they had in the set.
+    ///
+    /// Like [`swap_remove_index`][Self::swap_remove_index], the vacated
+    /// positions are filled by the remaining values from the end of the set,
+    /// keeping their relative order. Repeated indices are only removed once.
+    ///
+    /// ***Panics*** if any index is out of bounds.
+    ///
+    /// Computes in **O(m log m)** time (average), for *m* indices.
+    pub fn swap_remove_indices(&mut self, indices: &[usize]) -> Vec<T> {
+        let removed = self.map.swap_remove_indices(indices);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+
+    /// Remove the values at all of these indices, and return them in the
+    /// order they had in the set.
+    ///
+    /// Like [`shift_remove_index`][Self::shift_remove_index], the values are
+    /// removed by shifting all of the elements that follow them, but the
+    /// indices are all corrected at once. Repeated indices are only removed
+    /// once.
+    ///
+    /// ***Panics*** if any index is out of bounds.
+    ///
+    /// Computes in **O(n + m log m)** time (average), for *m* indices.
+    pub fn shift_remove_indices(&mut self, indices: &[usize]) -> Vec<T> {
+        let removed = self.map.shift_remove_indices(indices);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+
     /// Moves the position of a value from one index to another
     /// by shifting all other values in-between.
     ///
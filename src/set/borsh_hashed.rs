@@ -0,0 +1,78 @@
+//! Functions to serialize and deserialize an [`IndexSet`] with `borsh` and the
+//! hashes of its values.
+//!
+//! These work like those for maps in [`crate::map::borsh_hashed`], with a `u64`
+//! hash before each value.
+
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use core::hash::{BuildHasher, Hash};
+
+use super::{Bucket, IndexSet};
+use crate::borsh::check_zst;
+use crate::util::{cautious_capacity, fingerprint};
+use crate::vec::Vec;
+use crate::{Entries, HashValue, IndexMap};
+
+/// Serializes an [`IndexSet`] with its hasher fingerprint and the hashes of its values.
+pub fn serialize<W, T, S>(set: &IndexSet<T, S>, writer: &mut W) -> Result<()>
+where
+    W: Write,
+    T: BorshSerialize,
+    S: BuildHasher,
+{
+    check_zst::<T>()?;
+
+    fingerprint(set.hasher()).serialize(writer)?;
+    u32::try_from(set.len())
+        .map_err(|_| ErrorKind::InvalidData)?
+        .serialize(writer)?;
+
+    for bucket in set.map.as_entries() {
+        bucket.hash.get().serialize(writer)?;
+        bucket.key.serialize(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Deserializes an [`IndexSet`] with its hasher fingerprint and the hashes of its values,
+/// which are only reused if the default hasher has the same fingerprint.
+pub fn deserialize<R, T, S>(reader: &mut R) -> Result<IndexSet<T, S>>
+where
+    R: Read,
+    T: BorshDeserialize + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    check_zst::<T>()?;
+
+    let saved = u64::deserialize_reader(reader)?;
+    let len = u32::deserialize_reader(reader)? as usize;
+    let capacity = cautious_capacity::<T, ()>(Some(len));
+    let hash_builder = S::default();
+
+    if fingerprint(&hash_builder) == saved {
+        let mut entries = Vec::with_capacity(capacity);
+        for _ in 0..len {
+            let hash = HashValue(u64::deserialize_reader(reader)? as usize);
+            let key = T::deserialize_reader(reader)?;
+            entries.push(Bucket {
+                hash,
+                key,
+                value: (),
+            });
+        }
+        let map = IndexMap::from_entries(entries, hash_builder);
+        Ok(IndexSet { map })
+    } else {
+        let mut set = IndexSet::with_capacity_and_hasher(capacity, hash_builder);
+        for _ in 0..len {
+            u64::deserialize_reader(reader)?;
+            if !set.insert(T::deserialize_reader(reader)?) {
+                return Err(Error::new(ErrorKind::InvalidData, "duplicate set value"));
+            }
+        }
+        Ok(set)
+    }
+}
@@ -0,0 +1,31 @@
+//! Functions to deserialize an [`IndexSet`] with `borsh` and a limit on its values.
+//!
+//! These work like those for maps in [`crate::map::borsh_limits`].
+
+use borsh::io::{Read, Result};
+use borsh::BorshDeserialize;
+
+use core::hash::{BuildHasher, Hash};
+
+use super::IndexSet;
+use crate::borsh::deserialize_set;
+
+/// Deserializes an [`IndexSet`] of at most `N` values.
+pub fn deserialize_max<const N: usize, R, T, S>(reader: &mut R) -> Result<IndexSet<T, S>>
+where
+    R: Read,
+    T: BorshDeserialize + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    deserialize_set(reader, N)
+}
+
+/// Deserializes an [`IndexSet`] of at most `max` values.
+pub fn deserialize_with_max<R, T, S>(reader: &mut R, max: usize) -> Result<IndexSet<T, S>>
+where
+    R: Read,
+    T: BorshDeserialize + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    deserialize_set(reader, max)
+}
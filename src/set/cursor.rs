@@ -0,0 +1,153 @@
+use super::IndexSet;
+use crate::map;
+use crate::Equivalent;
+
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+/// A cursor over an [`IndexSet`] that can insert and remove values around
+/// its position.
+///
+/// The cursor points either at a value or at a "ghost" position after the
+/// last value, which wraps around to the first value.
+///
+/// This `struct` is created by the [`IndexSet::cursor_mut_at`] and
+/// [`IndexSet::cursor_mut_at_value`] methods. See their documentation for more.
+pub struct CursorMut<'a, T, S> {
+    inner: map::CursorMut<'a, T, (), S>,
+}
+
+impl<T, S> IndexSet<T, S> {
+    /// Returns a cursor pointing at the value at `index`, or at the ghost
+    /// position if `index` is equal to the length of the set.
+    ///
+    /// ***Panics*** if `index` is greater than the length of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexSet;
+    ///
+    /// let mut set = IndexSet::from(["a", "c", "d"]);
+    /// let mut cursor = set.cursor_mut_at(3);
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.current(), Some(&"d"));
+    /// assert!(cursor.insert_before("b"));
+    /// assert!(!cursor.insert_before("a"));
+    /// assert_eq!(cursor.peek_prev(), Some(&"a"));
+    /// assert!(set.iter().eq(&["c", "b", "a", "d"]));
+    /// ```
+    pub fn cursor_mut_at(&mut self, index: usize) -> CursorMut<'_, T, S> {
+        assert!(index <= self.len(), "IndexSet: index out of bounds");
+        CursorMut {
+            inner: self.map.cursor_mut_at(index),
+        }
+    }
+
+    /// Returns a cursor pointing at `value`, if it is present.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn cursor_mut_at_value<Q>(&mut self, value: &Q) -> Option<CursorMut<'_, T, S>>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+        S: BuildHasher,
+    {
+        let inner = self.map.cursor_mut_at_key(value)?;
+        Some(CursorMut { inner })
+    }
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for CursorMut<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CursorMut").field(&self.inner).finish()
+    }
+}
+
+impl<'a, T, S> CursorMut<'a, T, S> {
+    /// Returns the index of the current value, or `None` at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.inner.index()
+    }
+
+    /// Returns the current value, or `None` at the ghost position.
+    pub fn current(&mut self) -> Option<&T> {
+        Some(self.inner.current()?.0)
+    }
+
+    /// Returns the value after the current one, which is the first value at
+    /// the ghost position.
+    pub fn peek_next(&mut self) -> Option<&T> {
+        Some(self.inner.peek_next()?.0)
+    }
+
+    /// Returns the value before the current one, which is the last value at
+    /// the ghost position.
+    pub fn peek_prev(&mut self) -> Option<&T> {
+        Some(self.inner.peek_prev()?.0)
+    }
+
+    /// Moves the cursor to the next value, or to the ghost position after
+    /// the last value, or from there to the first value.
+    pub fn move_next(&mut self) {
+        self.inner.move_next();
+    }
+
+    /// Moves the cursor to the previous value, or to the ghost position
+    /// before the first value, or from there to the last value.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev();
+    }
+
+    /// Removes the current value and returns it, by shifting all of the
+    /// values that follow it. The cursor then points at the next value.
+    ///
+    /// Returns `None` at the ghost position.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn remove_current(&mut self) -> Option<T> {
+        Some(self.inner.remove_current()?.0)
+    }
+
+    /// Splits the set after the current value, returning a new set with all
+    /// of the following values.
+    ///
+    /// At the ghost position, all values are moved to the new set.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn split_after(&mut self) -> IndexSet<T, S>
+    where
+        S: Clone,
+    {
+        IndexSet {
+            map: self.inner.split_after(),
+        }
+    }
+}
+
+impl<'a, T, S> CursorMut<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Inserts a value before the current one, which is at the end of the set
+    /// at the ghost position. The cursor keeps its value.
+    ///
+    /// If an equivalent value already exists in the set, it is moved there
+    /// and `false` is returned. The value itself is not updated.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn insert_before(&mut self, value: T) -> bool {
+        self.inner.insert_before(value, ()).is_none()
+    }
+
+    /// Inserts a value after the current one, which is at the start of the
+    /// set at the ghost position. The cursor keeps its value.
+    ///
+    /// If an equivalent value already exists in the set, it is moved there
+    /// and `false` is returned. The value itself is not updated.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn insert_after(&mut self, value: T) -> bool {
+        self.inner.insert_after(value, ()).is_none()
+    }
+}
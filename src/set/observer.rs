@@ -0,0 +1,467 @@
+mod cursor;
+
+pub use self::cursor::ObservedCursorMut;
+
+use super::IndexSet;
+use crate::map::{self, IndexObserver};
+use crate::Equivalent;
+
+use alloc::vec::{self, Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::ops::{Deref, RangeBounds};
+
+/// A mutable view of an [`IndexSet`] that reports every change of the
+/// value indices to an [`IndexObserver`].
+///
+/// This `struct` is created by the [`IndexSet::observed`] method.
+/// See its documentation for more.
+///
+/// The view dereferences to the set for read-only access, and each of its
+/// own mutating methods reports its changes once they are applied. Changes
+/// made to the set directly, while it is not borrowed by a view, are not
+/// reported.
+pub struct Observed<'a, T, S, O> {
+    set: &'a mut IndexSet<T, S>,
+    observer: O,
+}
+
+impl<T, S> IndexSet<T, S> {
+    /// Returns a mutable view of the set that reports all index changes to
+    /// `observer`.
+    ///
+    /// See [`IndexObserver`] for the meaning of its notifications.
+    pub fn observed<O: IndexObserver>(&mut self, observer: O) -> Observed<'_, T, S, O> {
+        Observed {
+            set: self,
+            observer,
+        }
+    }
+}
+
+impl<T, S, O> Deref for Observed<'_, T, S, O> {
+    type Target = IndexSet<T, S>;
+
+    fn deref(&self) -> &Self::Target {
+        self.set
+    }
+}
+
+impl<T: fmt::Debug, S, O> fmt::Debug for Observed<'_, T, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Observed").field(&*self.set).finish()
+    }
+}
+
+impl<'a, T, S, O> Observed<'a, T, S, O>
+where
+    O: IndexObserver,
+{
+    fn map(&mut self) -> map::Observed<'_, T, (), S, &mut O> {
+        self.set.map.observed(&mut self.observer)
+    }
+
+    /// Reorder the values so that the new index `i` holds the old value `order[i]`.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn permute(&mut self, order: &[usize]) {
+        self.map().permute(order);
+    }
+
+    /// Returns a reference to the observer.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Returns a mutable reference to the observer.
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Consumes the view, returning the observer.
+    pub fn into_observer(self) -> O {
+        self.observer
+    }
+
+    /// Remove the last value.
+    ///
+    /// See [`IndexSet::pop`].
+    pub fn pop(&mut self) -> Option<T> {
+        self.map().pop().map(|(x, ())| x)
+    }
+
+    /// Remove all elements in the set.
+    ///
+    /// See [`IndexSet::clear`].
+    pub fn clear(&mut self) {
+        self.map().clear();
+    }
+
+    /// Shortens the set, keeping the first `len` elements and dropping the rest.
+    ///
+    /// See [`IndexSet::truncate`].
+    pub fn truncate(&mut self, len: usize) {
+        self.map().truncate(len);
+    }
+
+    /// Clears the given index range, returning those values in an iterator.
+    ///
+    /// Unlike [`IndexSet::drain`], the values are removed right away, so the
+    /// change can be reported before this returns.
+    pub fn drain<R>(&mut self, range: R) -> vec::IntoIter<T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let drained: Vec<_> = self.map().drain(range).map(|(x, ())| x).collect();
+        drained.into_iter()
+    }
+
+    /// Splits the set in two at the given index, and reports the values
+    /// that were moved to the new set as removed.
+    ///
+    /// See [`IndexSet::split_off`].
+    pub fn split_off(&mut self, at: usize) -> IndexSet<T, S>
+    where
+        S: Clone,
+    {
+        IndexSet {
+            map: self.map().split_off(at),
+        }
+    }
+
+    /// Returns a cursor pointing at the value at `index`, which reports the
+    /// index changes of its edits.
+    ///
+    /// See [`IndexSet::cursor_mut_at`].
+    pub fn cursor_mut_at(&mut self, index: usize) -> ObservedCursorMut<'_, T, S, O> {
+        assert!(index <= self.set.len(), "IndexSet: index out of bounds");
+        let inner = self.set.map.cursor_mut_at(index);
+        ObservedCursorMut::new(map::ObservedCursorMut::new(inner, &mut self.observer))
+    }
+
+    /// Remove the value by index, swapping it with the last element.
+    ///
+    /// See [`IndexSet::swap_remove_index`].
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<T> {
+        self.map().swap_remove_index(index).map(|(x, ())| x)
+    }
+
+    /// Remove the value by index, shifting all elements that follow it.
+    ///
+    /// See [`IndexSet::shift_remove_index`].
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<T> {
+        self.map().shift_remove_index(index).map(|(x, ())| x)
+    }
+
+    /// Moves the position of a value from one index to another.
+    ///
+    /// See [`IndexSet::move_index`].
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.map().move_index(from, to);
+    }
+
+    /// Swaps the position of two values in the set.
+    ///
+    /// See [`IndexSet::swap_indices`].
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.map().swap_indices(a, b);
+    }
+
+    /// Remove the values at all of these indices, filling their places with
+    /// the values from the end of the set.
+    ///
+    /// See [`IndexSet::swap_remove_indices`].
+    pub fn swap_remove_indices(&mut self, indices: &[usize]) -> Vec<T> {
+        let removed = self.map().swap_remove_indices(indices);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+
+    /// Remove the values at all of these indices, shifting all elements that
+    /// follow them.
+    ///
+    /// See [`IndexSet::shift_remove_indices`].
+    pub fn shift_remove_indices(&mut self, indices: &[usize]) -> Vec<T> {
+        let removed = self.map().shift_remove_indices(indices);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+
+    /// Keep the values where the closure `keep` returns `true`.
+    ///
+    /// See [`IndexSet::retain`].
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.map().retain(move |x, &mut ()| keep(x));
+    }
+
+    /// Reverses the order of the set’s values in place.
+    ///
+    /// See [`IndexSet::reverse`].
+    pub fn reverse(&mut self) {
+        self.map().reverse();
+    }
+
+    /// Sort the set’s values by their default ordering.
+    ///
+    /// See [`IndexSet::sort`].
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.map().sort_keys();
+    }
+
+    /// Sort the set’s values in place using the comparison function `cmp`.
+    ///
+    /// See [`IndexSet::sort_by`].
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.map().sort_by(move |a, _, b, _| cmp(a, b));
+    }
+
+    /// Sort the set's values by their default ordering, but may not preserve
+    /// the order of equal elements.
+    ///
+    /// See [`IndexSet::sort_unstable`].
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.map().sort_unstable_keys();
+    }
+
+    /// Sort the set's values in place using the comparison function `cmp`, but
+    /// may not preserve the order of equal elements.
+    ///
+    /// See [`IndexSet::sort_unstable_by`].
+    pub fn sort_unstable_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.map().sort_unstable_by(move |a, _, b, _| cmp(a, b));
+    }
+
+    /// Sort the set’s values in place using a sort-key extraction function.
+    ///
+    /// See [`IndexSet::sort_by_cached_key`].
+    pub fn sort_by_cached_key<K, F>(&mut self, mut sort_key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.map().sort_by_cached_key(move |x, _| sort_key(x));
+    }
+}
+
+impl<'a, T, S, O> Observed<'a, T, S, O>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+    O: IndexObserver,
+{
+    /// Insert the value into the set.
+    ///
+    /// See [`IndexSet::insert`].
+    pub fn insert(&mut self, value: T) -> bool {
+        self.insert_full(value).1
+    }
+
+    /// Insert the value into the set, and get its index.
+    ///
+    /// See [`IndexSet::insert_full`].
+    pub fn insert_full(&mut self, value: T) -> (usize, bool) {
+        let (index, existing) = self.map().insert_full(value, ());
+        (index, existing.is_none())
+    }
+
+    /// Adds a value to the set, replacing the existing value, if any, that is
+    /// equal to the given one.
+    ///
+    /// See [`IndexSet::replace`].
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        self.replace_full(value).1
+    }
+
+    /// Adds a value to the set, replacing the existing value, if any, that is
+    /// equal to the given one, along with its index.
+    ///
+    /// Replacing a value keeps its index, so only a new value is reported.
+    ///
+    /// See [`IndexSet::replace_full`].
+    pub fn replace_full(&mut self, value: T) -> (usize, Option<T>) {
+        let (index, replaced) = self.set.replace_full(value);
+        if replaced.is_none() {
+            self.observer.inserted(index);
+        }
+        (index, replaced)
+    }
+
+    /// Returns a cursor pointing at `value`, if it is present, which reports
+    /// the index changes of its edits.
+    ///
+    /// See [`IndexSet::cursor_mut_at_value`].
+    pub fn cursor_mut_at_value<Q>(&mut self, value: &Q) -> Option<ObservedCursorMut<'_, T, S, O>>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        let inner = self.set.map.cursor_mut_at_key(value)?;
+        let inner = map::ObservedCursorMut::new(inner, &mut self.observer);
+        Some(ObservedCursorMut::new(inner))
+    }
+
+    /// Insert the value into the set at its ordered position among sorted values.
+    ///
+    /// See [`IndexSet::insert_sorted`].
+    pub fn insert_sorted(&mut self, value: T) -> (usize, bool)
+    where
+        T: Ord,
+    {
+        let (index, existing) = self.map().insert_sorted(value, ());
+        (index, existing.is_none())
+    }
+
+    /// Insert the value into the set at the given index.
+    ///
+    /// See [`IndexSet::shift_insert`].
+    pub fn shift_insert(&mut self, index: usize, value: T) -> bool {
+        self.map().shift_insert(index, value, ()).is_none()
+    }
+
+    /// Insert the value into the set before the value at the given index, or
+    /// at the end if the index is equal to the length of the set.
+    ///
+    /// See [`map::Observed::insert_before`].
+    pub fn insert_before(&mut self, index: usize, value: T) -> (usize, bool) {
+        let (index, existing) = self.map().insert_before(index, value, ());
+        (index, existing.is_none())
+    }
+
+    /// Removes the given range of values and inserts the values of
+    /// `replace_with` in their place, returning the removed values.
+    ///
+    /// See [`map::Observed::splice`].
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Vec<T>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        let replace_with = replace_with.into_iter().map(|x| (x, ()));
+        let removed = self.map().splice(range, replace_with);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+
+    /// Moves all values from `other` into the set, leaving `other` empty.
+    ///
+    /// See [`map::Observed::append`].
+    pub fn append<S2>(&mut self, other: &mut IndexSet<T, S2>) {
+        self.map().append(&mut other.map);
+    }
+
+    /// Remove the value from the set, swapping it with the last element.
+    ///
+    /// See [`IndexSet::swap_remove`].
+    pub fn swap_remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.swap_remove_full(value).is_some()
+    }
+
+    /// Remove the value from the set, shifting all elements that follow it.
+    ///
+    /// See [`IndexSet::shift_remove`].
+    pub fn shift_remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.shift_remove_full(value).is_some()
+    }
+
+    /// Removes and returns the value in the set, if any, that is equal to the
+    /// given one, swapping it with the last element.
+    ///
+    /// See [`IndexSet::swap_take`].
+    pub fn swap_take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.swap_remove_full(value).map(|(_, x)| x)
+    }
+
+    /// Removes and returns the value in the set, if any, that is equal to the
+    /// given one, shifting all elements that follow it.
+    ///
+    /// See [`IndexSet::shift_take`].
+    pub fn shift_take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.shift_remove_full(value).map(|(_, x)| x)
+    }
+
+    /// Remove the value from the set, swapping it with the last element,
+    /// and return it and the index it had.
+    ///
+    /// See [`IndexSet::swap_remove_full`].
+    pub fn swap_remove_full<Q>(&mut self, value: &Q) -> Option<(usize, T)>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map().swap_remove_full(value).map(|(i, x, ())| (i, x))
+    }
+
+    /// Remove the value from the set, shifting all elements that follow it,
+    /// and return it and the index it had.
+    ///
+    /// See [`IndexSet::shift_remove_full`].
+    pub fn shift_remove_full<Q>(&mut self, value: &Q) -> Option<(usize, T)>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map().shift_remove_full(value).map(|(i, x, ())| (i, x))
+    }
+
+    /// Remove the values equal to any of `values`, filling their places with
+    /// the values from the end of the set.
+    ///
+    /// See [`IndexSet::swap_remove_many`].
+    pub fn swap_remove_many<'q, Q, I>(&mut self, values: I) -> Vec<T>
+    where
+        Q: ?Sized + Hash + Equivalent<T> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let removed = self.map().swap_remove_many(values);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+
+    /// Remove the values equal to any of `values`, shifting all elements that
+    /// follow them.
+    ///
+    /// See [`IndexSet::shift_remove_many`].
+    pub fn shift_remove_many<'q, Q, I>(&mut self, values: I) -> Vec<T>
+    where
+        Q: ?Sized + Hash + Equivalent<T> + 'q,
+        I: IntoIterator<Item = &'q Q>,
+    {
+        let removed = self.map().shift_remove_many(values);
+        removed.into_iter().map(|(x, ())| x).collect()
+    }
+}
+
+impl<T, S, O> Extend<T> for Observed<'_, T, S, O>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+    O: IndexObserver,
+{
+    /// Extend the set with all values in the iterable.
+    ///
+    /// See [`IndexSet::extend`].
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iterable: I) {
+        let mut map = self.map();
+        map.extend(iterable.into_iter().map(|x| (x, ())));
+    }
+}
@@ -0,0 +1,106 @@
+use super::IndexSet;
+use crate::map::{self, IndexObserver};
+
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+/// A cursor over an [`Observed`][super::Observed] set, which reports the
+/// index changes of its edits.
+///
+/// This `struct` is created by the
+/// [`Observed::cursor_mut_at`][super::Observed::cursor_mut_at] and
+/// [`Observed::cursor_mut_at_value`][super::Observed::cursor_mut_at_value]
+/// methods. It works like [`CursorMut`][crate::set::CursorMut].
+pub struct ObservedCursorMut<'a, T, S, O> {
+    inner: map::ObservedCursorMut<'a, T, (), S, O>,
+}
+
+impl<T: fmt::Debug, S, O> fmt::Debug for ObservedCursorMut<'_, T, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObservedCursorMut")
+            .field(&self.inner)
+            .finish()
+    }
+}
+
+impl<'a, T, S, O: IndexObserver> ObservedCursorMut<'a, T, S, O> {
+    pub(super) fn new(inner: map::ObservedCursorMut<'a, T, (), S, O>) -> Self {
+        ObservedCursorMut { inner }
+    }
+
+    /// Returns the index of the current value, or `None` at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.inner.index()
+    }
+
+    /// Returns the current value, or `None` at the ghost position.
+    pub fn current(&mut self) -> Option<&T> {
+        Some(self.inner.current()?.0)
+    }
+
+    /// Returns the value after the current one, which is the first value at
+    /// the ghost position.
+    pub fn peek_next(&mut self) -> Option<&T> {
+        Some(self.inner.peek_next()?.0)
+    }
+
+    /// Returns the value before the current one, which is the last value at
+    /// the ghost position.
+    pub fn peek_prev(&mut self) -> Option<&T> {
+        Some(self.inner.peek_prev()?.0)
+    }
+
+    /// Moves the cursor to the next value, or to the ghost position after
+    /// the last value, or from there to the first value.
+    pub fn move_next(&mut self) {
+        self.inner.move_next();
+    }
+
+    /// Moves the cursor to the previous value, or to the ghost position
+    /// before the first value, or from there to the last value.
+    pub fn move_prev(&mut self) {
+        self.inner.move_prev();
+    }
+
+    /// Removes the current value and returns it, by shifting all of the
+    /// values that follow it.
+    ///
+    /// See [`CursorMut::remove_current`][crate::set::CursorMut::remove_current].
+    pub fn remove_current(&mut self) -> Option<T> {
+        Some(self.inner.remove_current()?.0)
+    }
+
+    /// Splits the set after the current value, returning a new set with all
+    /// of the following values, which are reported as removed.
+    ///
+    /// See [`CursorMut::split_after`][crate::set::CursorMut::split_after].
+    pub fn split_after(&mut self) -> IndexSet<T, S>
+    where
+        S: Clone,
+    {
+        IndexSet {
+            map: self.inner.split_after(),
+        }
+    }
+}
+
+impl<'a, T, S, O> ObservedCursorMut<'a, T, S, O>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+    O: IndexObserver,
+{
+    /// Inserts a value before the current one.
+    ///
+    /// See [`CursorMut::insert_before`][crate::set::CursorMut::insert_before].
+    pub fn insert_before(&mut self, value: T) -> bool {
+        self.inner.insert_before(value, ()).is_none()
+    }
+
+    /// Inserts a value after the current one.
+    ///
+    /// See [`CursorMut::insert_after`][crate::set::CursorMut::insert_after].
+    pub fn insert_after(&mut self, value: T) -> bool {
+        self.inner.insert_after(value, ()).is_none()
+    }
+}
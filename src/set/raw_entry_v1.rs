@@ -0,0 +1,450 @@
+//! Opt-in access to the experimental raw entry API for [`IndexSet`].
+//!
+//! This mirrors the [`map::raw_entry_v1`][crate::map::raw_entry_v1] module, with a set's values
+//! standing in for a map's keys. See that module for the caveats of this API.
+
+use crate::map::raw_entry_v1::{self as map, RawEntryApiV1 as _};
+use crate::{Equivalent, IndexSet};
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+
+/// Opt-in access to the experimental raw entry API.
+///
+/// See the [`raw_entry_v1`][self] module documentation for more information.
+pub trait RawEntryApiV1<T, S>: private::Sealed {
+    /// Creates a raw immutable entry builder for the [`IndexSet`].
+    ///
+    /// Raw entries provide the lowest level of control for searching a set.
+    /// They must be manually initialized with a hash and then manually searched.
+    ///
+    /// This is useful for
+    /// * Hash memoization
+    /// * Using a search value that doesn't work with the [`Equivalent`] trait
+    /// * Using custom comparison logic without newtype wrappers
+    ///
+    /// Unless you are in such a situation, higher-level and more foolproof APIs like
+    /// [`get`][IndexSet::get] should be preferred.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::hash::{BuildHasher, Hash};
+    /// use indexmap::set::{IndexSet, RawEntryApiV1};
+    ///
+    /// let set: IndexSet<_> = ["a", "b", "c"].into_iter().collect();
+    ///
+    /// fn compute_hash<T: Hash + ?Sized, S: BuildHasher>(hash_builder: &S, value: &T) -> u64 {
+    ///     use core::hash::Hasher;
+    ///     let mut state = hash_builder.build_hasher();
+    ///     value.hash(&mut state);
+    ///     state.finish()
+    /// }
+    ///
+    /// for v in ["a", "b", "c", "d"] {
+    ///     let hash = compute_hash(set.hasher(), v);
+    ///     let i = set.get_index_of(v);
+    ///     let iv = set.get_full(v);
+    ///
+    ///     assert_eq!(set.raw_entry_v1().from_key(v), set.get(v));
+    ///     assert_eq!(set.raw_entry_v1().from_hash(hash, |q| *q == v), set.get(v));
+    ///     assert_eq!(set.raw_entry_v1().from_hash_full(hash, |q| *q == v), iv);
+    ///     assert_eq!(set.raw_entry_v1().index_from_hash(hash, |q| *q == v), i);
+    /// }
+    /// ```
+    fn raw_entry_v1(&self) -> RawEntryBuilder<'_, T, S>;
+
+    /// Creates a raw entry builder for the [`IndexSet`].
+    ///
+    /// Raw entries provide the lowest level of control for searching and
+    /// manipulating a set. They must be manually initialized with a hash and
+    /// then manually searched. After this, insertions into a vacant entry
+    /// still require an owned value to be provided.
+    ///
+    /// Raw entries are useful for such exotic situations as:
+    ///
+    /// * Hash memoization
+    /// * Deferring the creation of an owned value until it is known to be required
+    /// * Using a search value that doesn't work with the [`Equivalent`] trait
+    /// * Using custom comparison logic without newtype wrappers
+    ///
+    /// Inserting a value with a hash that the set's hasher would not compute, or one that
+    /// compares equal to a value already in the set, leaves the `IndexSet` in an inconsistent
+    /// state which, while memory-safe, will cause the set to produce seemingly random results.
+    /// Higher-level and more foolproof APIs like [`insert`][IndexSet::insert] should be
+    /// preferred when possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::hash::{BuildHasher, Hash};
+    /// use indexmap::set::{IndexSet, RawEntryApiV1};
+    /// use indexmap::set::raw_entry_v1::RawEntryMut;
+    ///
+    /// let mut set: IndexSet<_> = ["a", "b", "c"].into_iter().collect();
+    ///
+    /// fn compute_hash<T: Hash + ?Sized, S: BuildHasher>(hash_builder: &S, value: &T) -> u64 {
+    ///     use core::hash::Hasher;
+    ///     let mut state = hash_builder.build_hasher();
+    ///     value.hash(&mut state);
+    ///     state.finish()
+    /// }
+    ///
+    /// // Existing value (take)
+    /// let hash = compute_hash(set.hasher(), "b");
+    /// match set.raw_entry_mut_v1().from_hash(hash, |q| *q == "b") {
+    ///     RawEntryMut::Vacant(_) => unreachable!(),
+    ///     RawEntryMut::Occupied(view) => {
+    ///         assert_eq!(view.index(), 1);
+    ///         assert_eq!(view.shift_remove(), "b");
+    ///     }
+    /// }
+    /// assert!(set.iter().eq(&["a", "c"]));
+    ///
+    /// // Nonexistent value (insert)
+    /// let hash = compute_hash(set.hasher(), "d");
+    /// match set.raw_entry_mut_v1().from_hash(hash, |q| *q == "d") {
+    ///     RawEntryMut::Occupied(_) => unreachable!(),
+    ///     RawEntryMut::Vacant(view) => {
+    ///         assert_eq!(view.index(), 2);
+    ///         assert_eq!(*view.shift_insert_hashed_nocheck(0, hash, "d"), "d");
+    ///     }
+    /// }
+    /// assert!(set.iter().eq(&["d", "a", "c"]));
+    /// assert_eq!(set.get_index_of("d"), Some(0));
+    /// ```
+    fn raw_entry_mut_v1(&mut self) -> RawEntryBuilderMut<'_, T, S>;
+}
+
+impl<T, S> RawEntryApiV1<T, S> for IndexSet<T, S> {
+    fn raw_entry_v1(&self) -> RawEntryBuilder<'_, T, S> {
+        RawEntryBuilder {
+            inner: self.map.raw_entry_v1(),
+        }
+    }
+
+    fn raw_entry_mut_v1(&mut self) -> RawEntryBuilderMut<'_, T, S> {
+        RawEntryBuilderMut {
+            inner: self.map.raw_entry_mut_v1(),
+        }
+    }
+}
+
+/// A builder for computing where in an [`IndexSet`] a value would be stored.
+///
+/// This `struct` is created by the [`IndexSet::raw_entry_v1`] method, provided by the
+/// [`RawEntryApiV1`] trait. See its documentation for more.
+pub struct RawEntryBuilder<'a, T, S> {
+    inner: map::RawEntryBuilder<'a, T, (), S>,
+}
+
+impl<T, S> fmt::Debug for RawEntryBuilder<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawEntryBuilder").finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, S> RawEntryBuilder<'a, T, S> {
+    /// Access an entry by value.
+    pub fn from_key<Q>(self, value: &Q) -> Option<&'a T>
+    where
+        S: BuildHasher,
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        Some(self.inner.from_key(value)?.0)
+    }
+
+    /// Access an entry by a value and its hash.
+    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, value: &Q) -> Option<&'a T>
+    where
+        Q: ?Sized + Equivalent<T>,
+    {
+        Some(self.inner.from_key_hashed_nocheck(hash, value)?.0)
+    }
+
+    /// Access an entry by hash.
+    pub fn from_hash<F>(self, hash: u64, is_match: F) -> Option<&'a T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        Some(self.inner.from_hash(hash, is_match)?.0)
+    }
+
+    /// Access an entry by hash, including its index.
+    pub fn from_hash_full<F>(self, hash: u64, is_match: F) -> Option<(usize, &'a T)>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let (i, value, _) = self.inner.from_hash_full(hash, is_match)?;
+        Some((i, value))
+    }
+
+    /// Access the index of an entry by hash.
+    pub fn index_from_hash<F>(self, hash: u64, is_match: F) -> Option<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.inner.index_from_hash(hash, is_match)
+    }
+}
+
+/// A builder for computing where in an [`IndexSet`] a value would be stored.
+///
+/// This `struct` is created by the [`IndexSet::raw_entry_mut_v1`] method, provided by the
+/// [`RawEntryApiV1`] trait. See its documentation for more.
+pub struct RawEntryBuilderMut<'a, T, S> {
+    inner: map::RawEntryBuilderMut<'a, T, (), S>,
+}
+
+impl<T, S> fmt::Debug for RawEntryBuilderMut<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawEntryBuilderMut").finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, S> RawEntryBuilderMut<'a, T, S> {
+    /// Access an entry by value.
+    pub fn from_key<Q>(self, value: &Q) -> RawEntryMut<'a, T, S>
+    where
+        S: BuildHasher,
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        RawEntryMut::new(self.inner.from_key(value))
+    }
+
+    /// Access an entry by a value and its hash.
+    pub fn from_key_hashed_nocheck<Q>(self, hash: u64, value: &Q) -> RawEntryMut<'a, T, S>
+    where
+        Q: ?Sized + Equivalent<T>,
+    {
+        RawEntryMut::new(self.inner.from_key_hashed_nocheck(hash, value))
+    }
+
+    /// Access an entry by hash.
+    pub fn from_hash<F>(self, hash: u64, is_match: F) -> RawEntryMut<'a, T, S>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        RawEntryMut::new(self.inner.from_hash(hash, is_match))
+    }
+}
+
+/// Raw entry for an existing value or a vacant location to insert one.
+pub enum RawEntryMut<'a, T, S> {
+    /// Existing slot with an equivalent value.
+    Occupied(RawOccupiedEntryMut<'a, T, S>),
+    /// Vacant slot (no equivalent value in the set).
+    Vacant(RawVacantEntryMut<'a, T, S>),
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for RawEntryMut<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("RawEntryMut");
+        match self {
+            Self::Vacant(v) => tuple.field(v),
+            Self::Occupied(o) => tuple.field(o),
+        };
+        tuple.finish()
+    }
+}
+
+impl<'a, T, S> RawEntryMut<'a, T, S> {
+    fn new(entry: map::RawEntryMut<'a, T, (), S>) -> Self {
+        match entry {
+            map::RawEntryMut::Occupied(inner) => Self::Occupied(RawOccupiedEntryMut { inner }),
+            map::RawEntryMut::Vacant(inner) => Self::Vacant(RawVacantEntryMut { inner }),
+        }
+    }
+
+    /// Return the index where the value exists or may be inserted.
+    #[inline]
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Occupied(entry) => entry.index(),
+            Self::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Inserts the given default value in the entry if it is vacant and returns a reference
+    /// to it. Otherwise a reference to the already existent value is returned.
+    pub fn or_insert(self, default: T) -> &'a T
+    where
+        T: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Self::Occupied(entry) => entry.into_ref(),
+            Self::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of the `call` function in the entry if it is vacant and returns a
+    /// reference to it. Otherwise a reference to the already existent value is returned.
+    pub fn or_insert_with<F>(self, call: F) -> &'a T
+    where
+        F: FnOnce() -> T,
+        T: Hash,
+        S: BuildHasher,
+    {
+        match self {
+            Self::Occupied(entry) => entry.into_ref(),
+            Self::Vacant(entry) => entry.insert(call()),
+        }
+    }
+}
+
+/// A raw view into an occupied entry in an [`IndexSet`].
+/// It is part of the [`RawEntryMut`] enum.
+pub struct RawOccupiedEntryMut<'a, T, S> {
+    inner: map::RawOccupiedEntryMut<'a, T, (), S>,
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for RawOccupiedEntryMut<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawOccupiedEntryMut")
+            .field("value", self.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, S> RawOccupiedEntryMut<'a, T, S> {
+    /// Return the index of the value.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Gets a reference to the entry's value in the set.
+    ///
+    /// Note that this is not the value that was used to find the entry. There may be an
+    /// observable difference if the value type has any distinguishing features outside of `Hash`
+    /// and `Eq`, like extra fields or the memory address of an allocation.
+    pub fn get(&self) -> &T {
+        self.inner.key()
+    }
+
+    /// Converts into a reference to the entry's value in the set,
+    /// with a lifetime bound to the set itself.
+    pub fn into_ref(self) -> &'a T {
+        self.inner.into_key()
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    ///
+    /// The new value must hash and compare equal to the old one.
+    pub fn replace(&mut self, value: T) -> T {
+        self.inner.insert_key(value)
+    }
+
+    /// Remove the value stored in the set for this entry, and return it.
+    ///
+    /// Like [`Vec::swap_remove`][crate::Vec::swap_remove], the value is removed by swapping it
+    /// with the last element of the set and popping it off.
+    /// **This perturbs the position of what used to be the last element!**
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn swap_remove(self) -> T {
+        self.inner.swap_remove_entry().0
+    }
+
+    /// Remove the value stored in the set for this entry, and return it.
+    ///
+    /// Like [`Vec::remove`][crate::Vec::remove], the value is removed by shifting all of the
+    /// elements that follow it, preserving their relative order.
+    /// **This perturbs the index of all of those elements!**
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_remove(self) -> T {
+        self.inner.shift_remove_entry().0
+    }
+
+    /// Moves the position of the entry to a new index
+    /// by shifting all other entries in-between.
+    ///
+    /// This is equivalent to [`IndexSet::move_index`]
+    /// coming `from` the current [`.index()`][Self::index].
+    ///
+    /// ***Panics*** if `to` is out of bounds.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn move_index(self, to: usize) {
+        self.inner.move_index(to);
+    }
+
+    /// Swaps the position of entry with another.
+    ///
+    /// This is equivalent to [`IndexSet::swap_indices`]
+    /// with the current [`.index()`][Self::index] as one of the two being swapped.
+    ///
+    /// ***Panics*** if the `other` index is out of bounds.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn swap_indices(self, other: usize) {
+        self.inner.swap_indices(other);
+    }
+}
+
+/// A view into a vacant raw entry in an [`IndexSet`].
+/// It is part of the [`RawEntryMut`] enum.
+pub struct RawVacantEntryMut<'a, T, S> {
+    inner: map::RawVacantEntryMut<'a, T, (), S>,
+}
+
+impl<T, S> fmt::Debug for RawVacantEntryMut<'_, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawVacantEntryMut").finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, S> RawVacantEntryMut<'a, T, S> {
+    /// Return the index where a value may be inserted.
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Inserts the given value into the set, and returns a reference to it.
+    pub fn insert(self, value: T) -> &'a T
+    where
+        T: Hash,
+        S: BuildHasher,
+    {
+        self.inner.insert(value, ()).0
+    }
+
+    /// Inserts the given value into the set with the provided hash,
+    /// and returns a reference to it.
+    pub fn insert_hashed_nocheck(self, hash: u64, value: T) -> &'a T {
+        self.inner.insert_hashed_nocheck(hash, value, ()).0
+    }
+
+    /// Inserts the given value into the set at the given index,
+    /// shifting others to the right, and returns a reference to it.
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_insert(self, index: usize, value: T) -> &'a T
+    where
+        T: Hash,
+        S: BuildHasher,
+    {
+        self.inner.shift_insert(index, value, ()).0
+    }
+
+    /// Inserts the given value into the set with the provided hash
+    /// at the given index, and returns a reference to it.
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_insert_hashed_nocheck(self, index: usize, hash: u64, value: T) -> &'a T {
+        self.inner
+            .shift_insert_hashed_nocheck(index, hash, value, ())
+            .0
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl<T, S> Sealed for super::IndexSet<T, S> {}
+}
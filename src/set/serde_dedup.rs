@@ -0,0 +1,94 @@
+//! Functions to deserialize an [`IndexSet`] with a policy for duplicate values.
+//!
+//! The default `serde` implementation inserts each value in turn, so a
+//! duplicate is ignored in favor of its first occurrence. The modules here
+//! choose a different [`DuplicatePolicy`], like those for maps in
+//! [`crate::map::serde_dedup`]:
+//!
+//! - [`duplicate_error`]: fail on the first duplicate value, with [`DuplicateError`].
+//! - [`first_wins`]: keep the first value and ignore the rest, with [`FirstWins`].
+//! - [`last_wins_move_to_end`]: keep the last value at the position of the
+//!   last occurrence, with [`LastWinsMoveToEnd`].
+//!
+//! ```
+//! # use indexmap::IndexSet;
+//! # use serde_derive::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! struct Data {
+//!     #[serde(with = "indexmap::set::serde_dedup::duplicate_error")]
+//!     set: IndexSet<i32>,
+//!     // ...
+//! }
+//! ```
+
+use serde::de::{Deserialize, Deserializer};
+
+use core::hash::{BuildHasher, Hash};
+
+use super::IndexSet;
+use crate::serde::IndexSetVisitor;
+
+pub use crate::map::serde_dedup::{DuplicateError, DuplicatePolicy, FirstWins, LastWinsMoveToEnd};
+
+/// Deserializes an [`IndexSet`] with the duplicate value policy `P`.
+///
+/// This is the generic form of the `deserialize` functions in the policy modules.
+pub fn deserialize<'de, P, D, T, S>(deserializer: D) -> Result<IndexSet<T, S>, D::Error>
+where
+    P: DuplicatePolicy,
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_seq(IndexSetVisitor::<T, S, P>::new(usize::MAX))
+}
+
+macro_rules! policy_module {
+    ($(#[$attr:meta])* $name:ident, $policy:ident) => {
+        $(#[$attr])*
+        pub mod $name {
+            use serde::de::{Deserialize, Deserializer};
+            use serde::ser::{Serialize, Serializer};
+
+            use core::hash::{BuildHasher, Hash};
+
+            use super::$policy;
+            use crate::IndexSet;
+
+            /// Serializes an [`IndexSet`] like its default implementation.
+            pub fn serialize<T, S, Se>(set: &IndexSet<T, S>, serializer: Se) -> Result<Se::Ok, Se::Error>
+            where
+                T: Serialize,
+                Se: Serializer,
+            {
+                serializer.collect_seq(set)
+            }
+
+            #[doc = concat!("Deserializes an [`IndexSet`] with the [`", stringify!($policy), "`] policy.")]
+            pub fn deserialize<'de, D, T, S>(deserializer: D) -> Result<IndexSet<T, S>, D::Error>
+            where
+                D: Deserializer<'de>,
+                T: Deserialize<'de> + Eq + Hash,
+                S: Default + BuildHasher,
+            {
+                super::deserialize::<$policy, D, T, S>(deserializer)
+            }
+        }
+    };
+}
+
+policy_module!(
+    /// (De)serialization of an [`IndexSet`], failing on duplicate values.
+    duplicate_error,
+    DuplicateError
+);
+policy_module!(
+    /// (De)serialization of an [`IndexSet`], keeping the first of duplicate values.
+    first_wins,
+    FirstWins
+);
+policy_module!(
+    /// (De)serialization of an [`IndexSet`], moving duplicate values to their last position.
+    last_wins_move_to_end,
+    LastWinsMoveToEnd
+);
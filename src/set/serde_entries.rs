@@ -0,0 +1,210 @@
+//! Functions to serialize and deserialize an [`IndexSet`] as a list of value objects.
+//!
+//! This is the single-field form of [`map::serde_entries`][crate::map::serde_entries],
+//! so in JSON the set looks like `[{"value": 1}, ...]`:
+//!
+//! ```
+//! # use indexmap::IndexSet;
+//! # use serde_derive::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! struct Data {
+//!     #[serde(with = "indexmap::set::serde_entries")]
+//!     set: IndexSet<(i32, i32)>,
+//!     // ...
+//! }
+//! ```
+
+use serde::de::{Deserialize, Deserializer, Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use core::fmt::{self, Formatter};
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+
+use super::IndexSet;
+use crate::map::serde_entries::FieldSeed;
+use crate::util::cautious_capacity;
+
+/// The name of the field of each serialized value.
+///
+/// This is used like [`EntryFields`][crate::map::serde_entries::EntryFields] for maps.
+pub trait ValueField {
+    /// The name of the value field.
+    const VALUE: &'static str;
+
+    #[doc(hidden)]
+    const FIELDS: &'static [&'static str] = &[Self::VALUE];
+}
+
+/// The default [`ValueField`], named `value`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Value;
+
+impl ValueField for Value {
+    const VALUE: &'static str = "value";
+}
+
+/// Serializes an [`IndexSet`] as an ordered sequence of `value` structs.
+pub fn serialize<T, S, Se>(set: &IndexSet<T, S>, serializer: Se) -> Result<Se::Ok, Se::Error>
+where
+    T: Serialize,
+    Se: Serializer,
+{
+    serialize_with_field::<Value, T, S, Se>(set, serializer)
+}
+
+/// Deserializes an [`IndexSet`] from an ordered sequence of `value` structs.
+pub fn deserialize<'de, D, T, S>(deserializer: D) -> Result<IndexSet<T, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+{
+    deserialize_with_field::<Value, D, T, S>(deserializer)
+}
+
+/// Serializes an [`IndexSet`] as an ordered sequence of structs with the field of `F`.
+pub fn serialize_with_field<F, T, S, Se>(
+    set: &IndexSet<T, S>,
+    serializer: Se,
+) -> Result<Se::Ok, Se::Error>
+where
+    F: ValueField,
+    T: Serialize,
+    Se: Serializer,
+{
+    serializer.collect_seq(set.iter().map(|value| ElementRef::<T, F> {
+        value,
+        marker: PhantomData,
+    }))
+}
+
+/// Deserializes an [`IndexSet`] from an ordered sequence of structs with the field of `F`.
+///
+/// Duplicate values are handled like the default `serde` implementation.
+pub fn deserialize_with_field<'de, F, D, T, S>(deserializer: D) -> Result<IndexSet<T, S>, D::Error>
+where
+    F: ValueField,
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_seq(ElementsVisitor::<T, S, F>(PhantomData))
+}
+
+/// A value to serialize as a struct with the field of `F`
+struct ElementRef<'a, T, F> {
+    value: &'a T,
+    marker: PhantomData<F>,
+}
+
+impl<T, F> Serialize for ElementRef<'_, T, F>
+where
+    T: Serialize,
+    F: ValueField,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Element", 1)?;
+        state.serialize_field(F::VALUE, self.value)?;
+        state.end()
+    }
+}
+
+/// Visitor to deserialize a sequence of value structs into an `IndexSet`
+struct ElementsVisitor<T, S, F>(PhantomData<(T, S, F)>);
+
+impl<'de, T, S, F> Visitor<'de> for ElementsVisitor<T, S, F>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+    F: ValueField,
+{
+    type Value = IndexSet<T, S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequence of set elements")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let capacity = cautious_capacity::<T, ()>(seq.size_hint());
+        let mut set = IndexSet::with_capacity_and_hasher(capacity, S::default());
+
+        while let Some(element) = seq.next_element::<Element<T, F>>()? {
+            set.insert(element.value);
+        }
+
+        Ok(set)
+    }
+}
+
+/// A value deserialized from a struct with the field of `F`
+struct Element<T, F> {
+    value: T,
+    marker: PhantomData<F>,
+}
+
+impl<'de, T, F> Deserialize<'de> for Element<T, F>
+where
+    T: Deserialize<'de>,
+    F: ValueField,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Element", F::FIELDS, ElementVisitor(PhantomData))
+    }
+}
+
+struct ElementVisitor<T, F>(PhantomData<(T, F)>);
+
+impl<'de, T, F> Visitor<'de> for ElementVisitor<T, F>
+where
+    T: Deserialize<'de>,
+    F: ValueField,
+{
+    type Value = Element<T, F>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a set element with `{}`", F::VALUE)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        match seq.next_element()? {
+            Some(value) => Ok(Element {
+                value,
+                marker: PhantomData,
+            }),
+            None => Err(A::Error::invalid_length(0, &self)),
+        }
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut value = None;
+        while let Some(field) = map.next_key_seed(FieldSeed(F::FIELDS))? {
+            match field {
+                Some(_) if value.is_none() => value = Some(map.next_value()?),
+                Some(_) => return Err(A::Error::duplicate_field(F::VALUE)),
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(Element {
+            value: value.ok_or_else(|| A::Error::missing_field(F::VALUE))?,
+            marker: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,163 @@
+//! Functions to serialize and deserialize an [`IndexSet`] with the hashes of its values.
+//!
+//! These work like those for maps in [`crate::map::serde_hashed`], with a
+//! sequence of `(hash, value)` elements after the hasher fingerprint:
+//!
+//! ```
+//! # use fxhash::FxBuildHasher;
+//! # use indexmap::IndexSet;
+//! # use serde_derive::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! struct Data {
+//!     #[serde(with = "indexmap::set::serde_hashed")]
+//!     set: IndexSet<String, FxBuildHasher>,
+//!     // ...
+//! }
+//! ```
+
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+
+use core::fmt::{self, Formatter};
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+
+use super::{Bucket, IndexSet};
+use crate::util::{cautious_capacity, fingerprint};
+use crate::vec::Vec;
+use crate::{Entries, HashValue, IndexMap};
+
+/// Serializes an [`IndexSet`] with its hasher fingerprint and the hashes of its values.
+pub fn serialize<T, S, Se>(set: &IndexSet<T, S>, serializer: Se) -> Result<Se::Ok, Se::Error>
+where
+    T: Serialize,
+    S: BuildHasher,
+    Se: Serializer,
+{
+    let mut tuple = serializer.serialize_tuple(2)?;
+    tuple.serialize_element(&fingerprint(set.hasher()))?;
+    tuple.serialize_element(&HashedValues(set.map.as_entries()))?;
+    tuple.end()
+}
+
+/// Deserializes an [`IndexSet`] with its hasher fingerprint and the hashes of its values,
+/// which are only reused if the default hasher has the same fingerprint.
+pub fn deserialize<'de, D, T, S>(deserializer: D) -> Result<IndexSet<T, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+{
+    deserializer.deserialize_tuple(2, HashedVisitor(PhantomData))
+}
+
+/// The values to serialize as `(hash, value)` elements
+struct HashedValues<'a, T>(&'a [Bucket<T>]);
+
+impl<T> Serialize for HashedValues<'_, T>
+where
+    T: Serialize,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: Serializer,
+    {
+        serializer.collect_seq(self.0.iter().map(|b| (b.hash.get(), &b.key)))
+    }
+}
+
+/// Visitor to deserialize the fingerprint and the hashed values of an `IndexSet`
+struct HashedVisitor<T, S>(PhantomData<(T, S)>);
+
+impl<'de, T, S> Visitor<'de> for HashedVisitor<T, S>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+{
+    type Value = IndexSet<T, S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a hashed set")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let saved: u64 = match seq.next_element()? {
+            Some(saved) => saved,
+            None => return Err(A::Error::invalid_length(0, &self)),
+        };
+        let hash_builder = S::default();
+        let seed = HashedValuesSeed {
+            trusted: fingerprint(&hash_builder) == saved,
+            hash_builder,
+            marker: PhantomData,
+        };
+        match seq.next_element_seed(seed)? {
+            Some(set) => Ok(set),
+            None => Err(A::Error::invalid_length(1, &self)),
+        }
+    }
+}
+
+/// Seed to deserialize the `(hash, value)` elements of an `IndexSet`,
+/// using the saved hashes if they are `trusted`
+struct HashedValuesSeed<T, S> {
+    trusted: bool,
+    hash_builder: S,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T, S> DeserializeSeed<'de> for HashedValuesSeed<T, S>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: BuildHasher,
+{
+    type Value = IndexSet<T, S>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T, S> Visitor<'de> for HashedValuesSeed<T, S>
+where
+    T: Deserialize<'de> + Eq + Hash,
+    S: BuildHasher,
+{
+    type Value = IndexSet<T, S>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a sequence of hashed set values")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let capacity = cautious_capacity::<T, ()>(seq.size_hint());
+        if self.trusted {
+            let mut entries = Vec::with_capacity(capacity);
+            while let Some((hash, key)) = seq.next_element::<(u64, T)>()? {
+                let hash = HashValue(hash as usize);
+                entries.push(Bucket {
+                    hash,
+                    key,
+                    value: (),
+                });
+            }
+            let map = IndexMap::from_entries(entries, self.hash_builder);
+            Ok(IndexSet { map })
+        } else {
+            let mut set = IndexSet::with_capacity_and_hasher(capacity, self.hash_builder);
+            while let Some((_, value)) = seq.next_element::<(u64, T)>()? {
+                set.insert(value);
+            }
+            Ok(set)
+        }
+    }
+}
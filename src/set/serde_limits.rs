@@ -0,0 +1,36 @@
+//! Functions to deserialize an [`IndexSet`] with a limit on its values.
+//!
+//! These work like those for maps in [`crate::map::serde_limits`]:
+//!
+//! ```
+//! # use indexmap::IndexSet;
+//! # use serde_derive::Deserialize;
+//! #[derive(Deserialize)]
+//! struct Data {
+//!     #[serde(deserialize_with = "indexmap::set::serde_limits::deserialize_max::<1000, _, _, _>")]
+//!     set: IndexSet<i32>,
+//!     // ...
+//! }
+//! ```
+
+use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+
+use core::hash::{BuildHasher, Hash};
+
+use super::IndexSet;
+
+pub use crate::map::serde_limits::MaxEntries;
+
+/// Deserializes an [`IndexSet`] of at most `N` values.
+///
+/// This fails once the set has more than `N` values, before inserting them.
+pub fn deserialize_max<'de, const N: usize, D, T, S>(
+    deserializer: D,
+) -> Result<IndexSet<T, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Eq + Hash,
+    S: Default + BuildHasher,
+{
+    MaxEntries::<IndexSet<T, S>>::new(N).deserialize(deserializer)
+}
@@ -0,0 +1,17 @@
+//! Seeds to deserialize into an existing [`IndexSet`][crate::IndexSet].
+//!
+//! These work like those for maps in [`crate::map::serde_merge`]:
+//!
+//! ```
+//! use indexmap::set::serde_merge::Merge;
+//! use indexmap::{indexset, IndexSet};
+//! use serde::de::value::{Error, SeqDeserializer};
+//! use serde::de::DeserializeSeed;
+//!
+//! let mut set: IndexSet<i32> = indexset! { 1, 2 };
+//! let input = SeqDeserializer::<_, Error>::new([3, 1].into_iter());
+//! Merge::new(&mut set).deserialize(input).unwrap();
+//! assert_eq!(set, indexset! { 1, 2, 3 });
+//! ```
+
+pub use crate::map::serde_merge::Merge;
@@ -28,7 +28,7 @@ impl<T> Slice<T> {
         unsafe { &*(entries as *const [Bucket<T>] as *const Self) }
     }
 
-    pub(super) fn from_boxed(entries: Box<[Bucket<T>]>) -> Box<Self> {
+    pub(crate) fn from_boxed(entries: Box<[Bucket<T>]>) -> Box<Self> {
         unsafe { Box::from_raw(Box::into_raw(entries) as *mut Self) }
     }
 
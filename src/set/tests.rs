@@ -721,3 +721,210 @@ fn test_partition_point() {
     assert_eq!(b.partition_point(|&x| x < 7), 2);
     assert_eq!(b.partition_point(|&x| x < 8), 3);
 }
+
+#[test]
+fn parts_roundtrip() {
+    let set: IndexSet<u64> = (0..100).map(|i| i * 7).collect();
+    let (entries, hasher) = set.clone().into_parts();
+    assert!(entries.iter().map(|&(_, v)| v).eq(set.clone()));
+
+    let rebuilt = IndexSet::from_parts(entries.clone(), hasher.clone());
+    assert!(rebuilt.iter().eq(&set));
+    assert_eq!(rebuilt.get_index_of(&(50 * 7)), Some(50));
+
+    let rebuilt = IndexSet::from_parts_unchecked(entries, hasher);
+    assert_eq!(rebuilt, set);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "duplicate key"]
+fn from_parts_duplicate_value() {
+    let (mut entries, hasher) = IndexSet::from([1, 2]).into_parts();
+    entries.push(entries[1]);
+    IndexSet::from_parts(entries, hasher);
+}
+
+#[test]
+fn allocation_size_and_stats() {
+    let set: IndexSet<u64> = (0..100).collect();
+    assert_eq!(set.allocation_size(), set.map.allocation_size());
+    assert_eq!(set.stats(), set.map.stats());
+    assert_eq!(set.stats().len(), 100);
+}
+
+#[test]
+fn with_new_hasher() {
+    let set: IndexSet<u32, fnv::FnvBuildHasher> = (0..100).rev().collect();
+    let set = set.with_new_hasher(RandomState::new());
+    set.map.check_parts();
+    assert!(set.iter().copied().eq((0..100).rev()));
+    assert_eq!(set.get_index_of(&0), Some(99));
+}
+
+#[test]
+fn hashed_lookups() {
+    let hasher = RandomState::new();
+    let mut a = IndexSet::with_hasher(hasher.clone());
+    let mut b = IndexSet::with_hasher(hasher.clone());
+    a.extend(0..10);
+    b.extend((0..10).rev());
+
+    let value = crate::Hashed::new(3, &hasher);
+    assert_eq!(a.get_index_of_hashed(value.as_ref()), Some(3));
+    assert_eq!(b.get_index_of_hashed(value.as_ref()), Some(6));
+    assert!(a.contains_hashed(value.as_ref()));
+    assert_eq!(b.get_hashed(value.as_ref()), Some(&3));
+    assert_eq!(a.insert_full_hashed(value), (3, false));
+
+    assert_eq!(a.swap_remove_full_hashed(value.as_ref()), Some((3, 3)));
+    assert_eq!(a[3], 9);
+    assert!(b.shift_remove_hashed(value.as_ref()));
+    assert_eq!(b[6], 2);
+    assert!(!a.swap_remove_hashed(value.as_ref()));
+    assert_eq!(b.shift_remove_full_hashed(value.as_ref()), None);
+    assert!(b.insert_hashed(value));
+    assert_eq!(b.last(), Some(&3));
+}
+
+#[test]
+fn raw_entry() {
+    use raw_entry_v1::RawEntryMut;
+
+    let mut set: IndexSet<String> = ["a", "b", "c"].iter().map(|&s| s.into()).collect();
+    let hasher = set.hasher().clone();
+    let hash = |value: &str| crate::util::hash_key(&hasher, value).get();
+
+    let (hash_b, hash_d) = (hash("b"), hash("d"));
+    let is_b = |v: &String| v == "b";
+    assert_eq!(set.raw_entry_v1().from_hash(hash_b, is_b), Some(&set[1]));
+    assert_eq!(
+        set.raw_entry_v1().from_hash_full(hash_b, is_b),
+        Some((1, &set[1]))
+    );
+    assert_eq!(set.raw_entry_v1().index_from_hash(hash_b, is_b), Some(1));
+    assert_eq!(
+        set.raw_entry_v1().index_from_hash(hash_d, |v| v == "d"),
+        None
+    );
+
+    match set.raw_entry_mut_v1().from_hash(hash_b, is_b) {
+        RawEntryMut::Occupied(entry) => entry.move_index(2),
+        RawEntryMut::Vacant(_) => unreachable!(),
+    }
+    assert!(set.iter().eq(["a", "c", "b"]));
+
+    match set.raw_entry_mut_v1().from_hash(hash_d, |v| v == "d") {
+        RawEntryMut::Occupied(_) => unreachable!(),
+        RawEntryMut::Vacant(entry) => {
+            assert_eq!(entry.index(), 3);
+            assert_eq!(entry.insert_hashed_nocheck(hash_d, "d".into()), "d");
+        }
+    }
+    let hash_e = hash("e");
+    match set.raw_entry_mut_v1().from_hash(hash_e, |v| v == "e") {
+        RawEntryMut::Occupied(_) => unreachable!(),
+        RawEntryMut::Vacant(entry) => {
+            assert_eq!(
+                entry.shift_insert_hashed_nocheck(1, hash_e, "e".into()),
+                "e"
+            );
+        }
+    }
+    assert!(set.iter().eq(["a", "e", "c", "b", "d"]));
+    set.map.check_parts();
+
+    let entry = set.raw_entry_mut_v1().from_key("c");
+    assert_eq!(entry.index(), 2);
+    match entry {
+        RawEntryMut::Occupied(entry) => assert_eq!(entry.swap_remove(), "c"),
+        RawEntryMut::Vacant(_) => unreachable!(),
+    }
+    assert_eq!(
+        set.raw_entry_mut_v1().from_key("f").or_insert("f".into()),
+        "f"
+    );
+    assert!(set.iter().eq(["a", "e", "d", "b", "f"]));
+}
+
+#[test]
+fn cursor() {
+    let mut set: IndexSet<i32> = (0..5).collect();
+    let mut cursor = set.cursor_mut_at_value(&2).unwrap();
+    assert!(cursor.insert_after(10));
+    assert!(!cursor.insert_after(0));
+    assert_eq!(cursor.index(), Some(1));
+    assert_eq!(cursor.peek_next(), Some(&0));
+    cursor.move_next();
+    assert_eq!(cursor.remove_current(), Some(0));
+    assert_eq!(cursor.current(), Some(&10));
+    let rest = cursor.split_after();
+    assert!(set.iter().eq(&[1, 2, 10]));
+    assert!(rest.iter().eq(&[3, 4]));
+    set.map.check_parts();
+    rest.map.check_parts();
+}
+
+#[test]
+fn remove_many() {
+    let mut set: IndexSet<i32> = (0..10).collect();
+    assert_eq!(set.shift_remove_indices(&[8, 2]), [2, 8]);
+    assert_eq!(set.swap_remove_many(&[0, 20]), [0]);
+    assert_eq!(set.shift_remove_many(&[5, 1]), [1, 5]);
+    assert_eq!(set.swap_remove_indices(&[1, 1]), [3]);
+    assert!(set.iter().eq(&[9, 7, 4, 6]));
+    set.map.check_parts();
+}
+
+#[test]
+fn observed() {
+    #[derive(Default)]
+    struct Events(Vec<(char, usize, usize)>);
+
+    impl IndexObserver for Events {
+        fn inserted(&mut self, index: usize) {
+            self.0.push(('+', index, index));
+        }
+
+        fn removed(&mut self, index: usize) {
+            self.0.push(('-', index, index));
+        }
+
+        fn relocated(&mut self, old: usize, new: usize) {
+            self.0.push(('>', old, new));
+        }
+    }
+
+    let mut set: IndexSet<i32> = (0..5).collect();
+    let mut view = set.observed(Events::default());
+
+    // Replacing a value keeps its index.
+    assert_eq!(view.replace_full(2), (2, Some(2)));
+    assert_eq!(view.replace_full(7), (5, None));
+    assert_eq!(view.observer().0, [('+', 5, 5)]);
+
+    view.observer_mut().0.clear();
+    assert!(view.split_off(4).iter().eq(&[4, 7]));
+    assert_eq!(view.observer().0, [('-', 4, 4), ('-', 5, 5)]);
+
+    view.observer_mut().0.clear();
+    let mut cursor = view.cursor_mut_at_value(&1).unwrap();
+    assert!(!cursor.insert_after(3));
+    assert_eq!(cursor.remove_current(), Some(1));
+    let expected = [
+        ('>', 3, 2),
+        ('>', 2, 3),
+        ('-', 1, 1),
+        ('>', 2, 1),
+        ('>', 3, 2),
+    ];
+    assert_eq!(view.observer().0, expected);
+
+    view.observer_mut().0.clear();
+    assert!(view.drain(..1).eq([0]));
+    assert_eq!(view.observer().0, [('-', 0, 0), ('>', 1, 0), ('>', 2, 1)]);
+
+    drop(view);
+    assert!(set.iter().eq(&[3, 2]));
+    set.map.check_parts();
+}
@@ -0,0 +1,128 @@
+//! Append-only collections that can be shared between threads.
+//!
+//! [`ConcurrentIndexMap`] and [`ConcurrentIndexSet`] are insertion-ordered
+//! like [`IndexMap`][crate::IndexMap] and [`IndexSet`][crate::IndexSet], but
+//! they can only grow, and new entries are inserted through a shared reference.
+//! In exchange, the indices of the entries are dense and stable, and lookups by
+//! index never take a lock, which makes them a good fit for interning values
+//! from many threads.
+//!
+//! Lookups by key take a shared read lock on the hash table, which only
+//! waits for writers that are inserting a new key. A writer holds its lock
+//! just to compare keys and append the entry, as the value of a new key is
+//! computed before taking the lock.
+//!
+//! With the `rayon` feature, worker threads can insert through a shared
+//! reference like any other thread. The `ParallelExtend` and
+//! `FromParallelIterator` implementations keep the order of the parallel
+//! iterator instead, so they only produce the items in parallel, and then
+//! insert them one by one on the calling thread.
+//!
+//! ```
+//! use indexmap::sync::ConcurrentIndexSet;
+//! use std::thread;
+//!
+//! let names = ConcurrentIndexSet::new();
+//! thread::scope(|s| {
+//!     for name in ["a", "b", "c", "a"] {
+//!         let names = &names;
+//!         s.spawn(move || names.insert_full(name));
+//!     }
+//! });
+//! assert_eq!(names.len(), 3);
+//!
+//! let (index, inserted) = names.insert_full("b");
+//! assert!(!inserted);
+//! assert_eq!(names.get_index(index), Some(&"b"));
+//! ```
+
+mod core;
+pub mod map;
+pub mod set;
+
+pub use self::map::ConcurrentIndexMap;
+pub use self::set::ConcurrentIndexSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IndexMap, IndexSet};
+    use std::string::{String, ToString};
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn assert_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ConcurrentIndexMap<i32, String>>();
+        assert_send_sync::<ConcurrentIndexSet<i32>>();
+    }
+
+    #[test]
+    fn insert_from_threads() {
+        let set = ConcurrentIndexSet::new();
+        let indices: Vec<Vec<usize>> = thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|t| {
+                    let set = &set;
+                    s.spawn(move || {
+                        (0..1000)
+                            .map(|i| set.insert_full((i * (t + 1)) % 1500).0)
+                            .collect()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert!(set.len() <= 1500);
+        for (t, indices) in indices.into_iter().enumerate() {
+            for (i, index) in indices.into_iter().enumerate() {
+                assert_eq!(set.get_index(index), Some(&((i * (t + 1)) % 1500)));
+            }
+        }
+        for (i, x) in set.iter().enumerate() {
+            assert_eq!(set.get_index_of(x), Some(i));
+        }
+    }
+
+    #[test]
+    fn convert() {
+        let map: IndexMap<_, _> = (0..100).map(|i| (i, i.to_string())).collect();
+        let concurrent = ConcurrentIndexMap::from(map.clone());
+        assert!(concurrent.iter().eq(&map));
+        let (index, value) = concurrent.get_or_insert_full(100, "new".to_string());
+        assert_eq!((index, value.as_str()), (100, "new"));
+        assert_eq!(concurrent.get_or_insert_full(5, String::new()).1, "5");
+
+        let back = IndexMap::from(concurrent);
+        assert_eq!(back.len(), 101);
+        assert!(back.iter().take(100).eq(&map));
+        assert_eq!(back.get_index_of(&100), Some(100));
+
+        let set: IndexSet<_> = (0..40).rev().collect();
+        let concurrent = ConcurrentIndexSet::from(set.clone());
+        assert_eq!(concurrent.get_full(&3), Some((36, &3)));
+        assert!(IndexSet::from(concurrent).iter().eq(&set));
+    }
+
+    #[test]
+    fn reentrant_insert() {
+        let map = ConcurrentIndexMap::new();
+        let (index, &value) = map.get_or_insert_with(1, || {
+            assert_eq!(map.get(&1), None);
+            let (index, &inner) = map.get_or_insert_with(2, || 20);
+            assert_eq!((index, inner), (0, 20));
+            // The same key inserted from inside keeps its value.
+            map.get_or_insert_with(1, || 11).1 + 1
+        });
+        assert_eq!((index, value), (1, 11));
+        assert!(map.iter().eq([(&2, &20), (&1, &11)]));
+
+        // Lookups from other threads don't wait for the value.
+        let (index, &value) = map.get_or_insert_with(3, || {
+            thread::scope(|s| s.spawn(|| *map.get(&1).unwrap()).join().unwrap())
+        });
+        assert_eq!((index, value), (2, 11));
+    }
+}
@@ -0,0 +1,230 @@
+#![allow(unsafe_code)]
+//! This module encapsulates the `unsafe` storage of the concurrent collections.
+//!
+//! The entries are appended to segments that never move once they are
+//! allocated, so readers can access them without locking while a writer
+//! appends more. The hash table of indices is guarded by a lock, and only
+//! the holder of its write lock may append entries.
+
+use crate::{Bucket, Equivalent, HashValue};
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use hashbrown::raw::RawTable;
+use std::sync::{PoisonError, RwLock};
+
+/// The length of the first segment, while each following one doubles it.
+const FIRST_LEN: usize = 32;
+const FIRST_SHIFT: u32 = FIRST_LEN.trailing_zeros();
+const SEGMENTS: usize = (usize::BITS - FIRST_SHIFT) as usize;
+
+/// Return the segment holding `index` and the offset within it.
+#[inline]
+fn locate(index: usize) -> (usize, usize) {
+    let i = index + FIRST_LEN;
+    let segment = (usize::BITS - 1 - i.leading_zeros() - FIRST_SHIFT) as usize;
+    (segment, i - (FIRST_LEN << segment))
+}
+
+#[inline]
+fn segment_len(segment: usize) -> usize {
+    FIRST_LEN << segment
+}
+
+/// Core of the concurrent map that does not depend on S
+pub(crate) struct ConcurrentCore<K, V> {
+    /// indices mapping from the entry hash to its index, whose write lock
+    /// must be held to append entries.
+    indices: RwLock<RawTable<usize>>,
+    /// segments of the entries, which are null until they are needed.
+    segments: [AtomicPtr<Bucket<K, V>>; SEGMENTS],
+    /// the number of initialized entries.
+    len: AtomicUsize,
+    marker: PhantomData<Bucket<K, V>>,
+}
+
+// SAFETY: shared references only give out shared references to the entries,
+// but they can also append entries that are later dropped by another thread.
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for ConcurrentCore<K, V> {}
+
+impl<K, V> Drop for ConcurrentCore<K, V> {
+    fn drop(&mut self) {
+        let len = *self.len.get_mut();
+        for (segment, ptr) in self.segments.iter_mut().enumerate() {
+            let ptr = *ptr.get_mut();
+            if ptr.is_null() {
+                break;
+            }
+            let start = segment_len(segment) - FIRST_LEN;
+            let init = Ord::min(len.saturating_sub(start), segment_len(segment));
+            // SAFETY: the first `init` entries of this segment are initialized,
+            // and the segment was allocated by `alloc_segment` with this length.
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(ptr, init));
+                free_segment(ptr, segment_len(segment));
+            }
+        }
+    }
+}
+
+/// Allocate an uninitialized segment of `len` entries.
+fn alloc_segment<T>(len: usize) -> *mut T {
+    let mut segment = Vec::with_capacity(len);
+    segment.resize_with(len, MaybeUninit::<T>::uninit);
+    Box::into_raw(segment.into_boxed_slice()).cast::<T>()
+}
+
+/// Free a segment without dropping any of its entries.
+///
+/// SAFETY: `ptr` must have been allocated by `alloc_segment` with this `len`.
+unsafe fn free_segment<T>(ptr: *mut T, len: usize) {
+    let slice = ptr::slice_from_raw_parts_mut(ptr.cast::<MaybeUninit<T>>(), len);
+    drop(Box::from_raw(slice));
+}
+
+impl<K, V> ConcurrentCore<K, V> {
+    pub(crate) fn new() -> Self {
+        ConcurrentCore {
+            indices: RwLock::new(RawTable::new()),
+            segments: [(); SEGMENTS].map(|()| AtomicPtr::new(ptr::null_mut())),
+            len: AtomicUsize::new(0),
+            marker: PhantomData,
+        }
+    }
+
+    /// Build the core from entries that are known to have unique keys.
+    pub(crate) fn from_entries(entries: Vec<Bucket<K, V>>) -> Self {
+        let mut core = Self::new();
+        let indices = core
+            .indices
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        indices.reserve(entries.len(), |_| unreachable!());
+        for entry in entries {
+            let hash = entry.hash;
+            // SAFETY: we have exclusive access, and we reserved the capacity.
+            unsafe {
+                let index = Self::push(&core.segments, &core.len, entry);
+                indices.insert_no_grow(hash.get(), index);
+            }
+        }
+        core
+    }
+
+    pub(crate) fn into_entries(mut self) -> Vec<Bucket<K, V>> {
+        let len = *self.len.get_mut();
+        let mut entries = Vec::with_capacity(len);
+        // Forget the entries before moving them out, so `drop` only frees the segments.
+        *self.len.get_mut() = 0;
+        for index in 0..len {
+            let (segment, offset) = locate(index);
+            let ptr = *self.segments[segment].get_mut();
+            // SAFETY: the entry was initialized, and it won't be read again.
+            entries.push(unsafe { ptr.add(offset).read() });
+        }
+        entries
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, index: usize) -> Option<&Bucket<K, V>> {
+        if index < self.len() {
+            let (segment, offset) = locate(index);
+            let ptr = self.segments[segment].load(Ordering::Acquire);
+            // SAFETY: the entries below `len` are initialized and never moved
+            // or mutated, and their segment was published before `len` grew.
+            Some(unsafe { &*ptr.add(offset) })
+        } else {
+            None
+        }
+    }
+
+    fn entry(&self, index: usize) -> &Bucket<K, V> {
+        self.get(index).expect("index out of bounds")
+    }
+
+    /// Return the index where an equivalent key can be found
+    pub(crate) fn get_index_of<Q>(&self, hash: HashValue, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Equivalent<K>,
+    {
+        // The table is never left inconsistent by a panic, so ignore poisoning.
+        let indices = self.indices.read().unwrap_or_else(PoisonError::into_inner);
+        let eq = |&i: &usize| Q::equivalent(key, &self.entry(i).key);
+        indices.get(hash.get(), eq).copied()
+    }
+
+    /// Append an entry unless an equivalent key already exists, returning the
+    /// index of the key and whether it was appended.
+    ///
+    /// The value is computed before taking the write lock, so `value` may use
+    /// the map too. It is dropped if the key was appended in the meantime.
+    pub(crate) fn insert_with<F>(&self, hash: HashValue, key: K, value: F) -> (usize, bool)
+    where
+        K: Eq,
+        F: FnOnce() -> V,
+    {
+        if let Some(index) = self.get_index_of(hash, &key) {
+            return (index, false);
+        }
+        let value = value();
+
+        let mut indices = self.indices.write().unwrap_or_else(PoisonError::into_inner);
+        // Someone else may have appended the key while we were not locked.
+        let eq = |&i: &usize| self.entry(i).key == key;
+        if let Some(&index) = indices.get(hash.get(), eq) {
+            drop(indices);
+            return (index, false);
+        }
+        indices.reserve(1, |&i| self.entry(i).hash.get());
+        let entry = Bucket { hash, key, value };
+        // SAFETY: we hold the write lock, and we reserved the capacity.
+        unsafe {
+            let index = Self::push(&self.segments, &self.len, entry);
+            indices.insert_no_grow(hash.get(), index);
+            (index, true)
+        }
+    }
+
+    /// Append an entry, allocating its segment if needed.
+    ///
+    /// SAFETY: the caller must hold the write lock of the indices, or
+    /// otherwise have exclusive access.
+    unsafe fn push(
+        segments: &[AtomicPtr<Bucket<K, V>>; SEGMENTS],
+        len: &AtomicUsize,
+        entry: Bucket<K, V>,
+    ) -> usize {
+        let index = len.load(Ordering::Relaxed);
+        let (segment, offset) = locate(index);
+        let mut ptr = segments[segment].load(Ordering::Relaxed);
+        if ptr.is_null() {
+            ptr = alloc_segment(segment_len(segment));
+            segments[segment].store(ptr, Ordering::Release);
+        }
+        ptr.add(offset).write(entry);
+        len.store(index + 1, Ordering::Release);
+        index
+    }
+}
+
+#[test]
+fn segment_layout() {
+    assert_eq!(locate(0), (0, 0));
+    assert_eq!(locate(FIRST_LEN - 1), (0, FIRST_LEN - 1));
+    assert_eq!(locate(FIRST_LEN), (1, 0));
+    assert_eq!(locate(3 * FIRST_LEN - 1), (1, 2 * FIRST_LEN - 1));
+    assert_eq!(locate(3 * FIRST_LEN), (2, 0));
+    assert_eq!(
+        locate(usize::MAX - FIRST_LEN),
+        (SEGMENTS - 1, usize::MAX / 2)
+    );
+}
@@ -0,0 +1,426 @@
+//! [`ConcurrentIndexMap`] is an append-only hash table that can be shared
+//! between threads, where the iteration order of the key-value pairs is
+//! independent of the hash values of the keys.
+
+use super::core::ConcurrentCore;
+use crate::map::IndexMap;
+use crate::{Bucket, Entries, Equivalent, HashValue};
+
+use ::core::fmt;
+use ::core::hash::{BuildHasher, Hash, Hasher};
+use ::core::iter::FusedIterator;
+use ::core::ops::Range;
+use std::collections::hash_map::RandomState;
+
+/// An append-only hash map that can be shared between threads, with
+/// consistent order of the key-value pairs.
+///
+/// New keys are inserted through a shared reference, and they keep their
+/// index until the map is dropped. The values cannot be modified, and
+/// nothing can be removed, except by converting back into an [`IndexMap`].
+///
+/// See the [module documentation][crate::sync] for more.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::sync::ConcurrentIndexMap;
+///
+/// let lengths = ConcurrentIndexMap::new();
+/// for word in "a short treatise on fungi".split_whitespace() {
+///     lengths.get_or_insert_with(word, || word.len());
+/// }
+/// assert_eq!(lengths.get_index(1), Some((&"short", &5)));
+/// assert_eq!(lengths.get_full("fungi"), Some((4, &"fungi", &5)));
+/// ```
+pub struct ConcurrentIndexMap<K, V, S = RandomState> {
+    pub(super) core: ConcurrentCore<K, V>,
+    hash_builder: S,
+}
+
+impl<K, V, S> fmt::Debug for ConcurrentIndexMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> ConcurrentIndexMap<K, V> {
+    /// Create a new map. (Does not allocate.)
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S> ConcurrentIndexMap<K, V, S> {
+    /// Create a new map with `hash_builder`. (Does not allocate.)
+    pub fn with_hasher(hash_builder: S) -> Self {
+        ConcurrentIndexMap {
+            core: ConcurrentCore::new(),
+            hash_builder,
+        }
+    }
+
+    /// Return the number of key-value pairs in the map.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn len(&self) -> usize {
+        self.core.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return a reference to the map's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    ///
+    /// The iterator only visits the pairs that were present when it was created.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.core)
+    }
+
+    /// Return an iterator over the keys of the map, in their order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            iter: Iter::new(&self.core),
+        }
+    }
+
+    /// Return an iterator over the values of the map, in their order.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            iter: Iter::new(&self.core),
+        }
+    }
+
+    /// Get a key-value pair by index.
+    ///
+    /// Valid indices are *0 <= index < self.len()*. This never takes a lock.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.core.get(index).map(Bucket::refs)
+    }
+}
+
+impl<K, V, S> ConcurrentIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub(super) fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> HashValue {
+        let mut h = self.hash_builder.build_hasher();
+        key.hash(&mut h);
+        HashValue(h.finish() as usize)
+    }
+
+    /// Return the value of the key, inserting it with `value` if it is not
+    /// present yet, along with the index of the key.
+    ///
+    /// If an equivalent key already exists in the map, it keeps its value,
+    /// and `value` is dropped.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn get_or_insert_full(&self, key: K, value: V) -> (usize, &V) {
+        self.get_or_insert_with(key, || value)
+    }
+
+    /// Return the value of the key, inserting it with the result of `call` if
+    /// it is not present yet, along with the index of the key.
+    ///
+    /// No lock is held while `call` is running, so it may use the map too.
+    /// If the key is inserted by another thread or by `call` in the meantime,
+    /// it keeps that value, and the result of `call` is dropped.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn get_or_insert_with<F>(&self, key: K, call: F) -> (usize, &V)
+    where
+        F: FnOnce() -> V,
+    {
+        let hash = self.hash(&key);
+        let (index, _) = self.core.insert_with(hash, key, call);
+        (index, &self.core.get(index).expect("inserted").value)
+    }
+
+    /// Return `true` if an equivalent to `key` exists in the map.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Return a reference to the value stored for `key`, if it is present,
+    /// else `None`.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.get_full(key).map(|(_, _, value)| value)
+    }
+
+    /// Return item index, key and value
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.get_index_of(key)?;
+        let (key, value) = self.get_index(index)?;
+        Some((index, key, value))
+    }
+
+    /// Return item index, if it exists in the map
+    ///
+    /// Like every lookup by key, this takes a shared read lock on the hash
+    /// table, so it waits while another thread is appending a new key.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        if self.is_empty() {
+            None
+        } else {
+            let hash = self.hash(key);
+            self.core.get_index_of(hash, key)
+        }
+    }
+}
+
+impl<K, V, S> Default for ConcurrentIndexMap<K, V, S>
+where
+    S: Default,
+{
+    /// Return an empty [`ConcurrentIndexMap`]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> From<IndexMap<K, V, S>> for ConcurrentIndexMap<K, V, S> {
+    /// Moves all entries of the map, keeping their order and stored hashes.
+    fn from(map: IndexMap<K, V, S>) -> Self {
+        let IndexMap { core, hash_builder } = map;
+        ConcurrentIndexMap {
+            core: ConcurrentCore::from_entries(core.into_entries()),
+            hash_builder,
+        }
+    }
+}
+
+impl<K, V, S> From<ConcurrentIndexMap<K, V, S>> for IndexMap<K, V, S> {
+    /// Moves all entries of the map, keeping their order and stored hashes.
+    fn from(map: ConcurrentIndexMap<K, V, S>) -> Self {
+        let ConcurrentIndexMap { core, hash_builder } = map;
+        IndexMap::from_entries(core.into_entries(), hash_builder)
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for ConcurrentIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    /// Create a `ConcurrentIndexMap` from the sequence of key-value pairs in
+    /// the iterable.
+    ///
+    /// Unlike [`IndexMap`], the first value of a duplicated key is kept.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iterable: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iterable);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for ConcurrentIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Extend the map with all key-value pairs in the iterable, keeping the
+    /// existing values of duplicated keys.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iterable: I) {
+        for (key, value) in iterable {
+            self.get_or_insert_full(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a ConcurrentIndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`ConcurrentIndexMap`].
+///
+/// This `struct` is created by the [`ConcurrentIndexMap::iter`] method.
+/// See its documentation for more.
+pub struct Iter<'a, K, V> {
+    core: &'a ConcurrentCore<K, V>,
+    range: Range<usize>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    pub(super) fn new(core: &'a ConcurrentCore<K, V>) -> Self {
+        Iter {
+            core,
+            range: 0..core.len(),
+        }
+    }
+
+    fn bucket(&self, index: usize) -> &'a Bucket<K, V> {
+        self.core.get(index).expect("appended entry")
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.range.next()?;
+        Some(self.bucket(index).refs())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth(n)?;
+        Some(self.bucket(index).refs())
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let index = self.range.next_back()?;
+        Some(self.bucket(index).refs())
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            core: self.core,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the keys of a [`ConcurrentIndexMap`].
+///
+/// This `struct` is created by the [`ConcurrentIndexMap::keys`] method.
+/// See its documentation for more.
+pub struct Keys<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Keys {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for Keys<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the values of a [`ConcurrentIndexMap`].
+///
+/// This `struct` is created by the [`ConcurrentIndexMap::values`] method.
+/// See its documentation for more.
+pub struct Values<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Values {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<K, V: fmt::Debug> fmt::Debug for Values<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
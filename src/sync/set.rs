@@ -0,0 +1,281 @@
+//! [`ConcurrentIndexSet`] is an append-only hash set that can be shared
+//! between threads, where the iteration order of the values is independent
+//! of their hash values.
+
+use super::map::{self, ConcurrentIndexMap};
+use crate::set::IndexSet;
+use crate::Equivalent;
+
+use ::core::fmt;
+use ::core::hash::{BuildHasher, Hash};
+use ::core::iter::FusedIterator;
+use std::collections::hash_map::RandomState;
+
+/// An append-only hash set that can be shared between threads, with
+/// consistent order of the values.
+///
+/// New values are inserted through a shared reference, and they keep their
+/// index until the set is dropped. Nothing can be removed, except by
+/// converting back into an [`IndexSet`].
+///
+/// See the [module documentation][crate::sync] for more.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::sync::ConcurrentIndexSet;
+///
+/// let symbols = ConcurrentIndexSet::new();
+/// assert_eq!(symbols.insert_full("x"), (0, true));
+/// assert_eq!(symbols.insert_full("y"), (1, true));
+/// assert_eq!(symbols.insert_full("x"), (0, false));
+/// assert_eq!(symbols.get_index(1), Some(&"y"));
+/// ```
+pub struct ConcurrentIndexSet<T, S = RandomState> {
+    map: ConcurrentIndexMap<T, (), S>,
+}
+
+impl<T, S> fmt::Debug for ConcurrentIndexSet<T, S>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> ConcurrentIndexSet<T> {
+    /// Create a new set. (Does not allocate.)
+    pub fn new() -> Self {
+        ConcurrentIndexSet {
+            map: ConcurrentIndexMap::new(),
+        }
+    }
+}
+
+impl<T, S> ConcurrentIndexSet<T, S> {
+    /// Create a new set with `hash_builder`. (Does not allocate.)
+    pub fn with_hasher(hash_builder: S) -> Self {
+        ConcurrentIndexSet {
+            map: ConcurrentIndexMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Return the number of elements in the set.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return a reference to the set's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    /// Return an iterator over the values of the set, in their order.
+    ///
+    /// The iterator only visits the values that were present when it was created.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.map.keys(),
+        }
+    }
+
+    /// Get a value by index.
+    ///
+    /// Valid indices are *0 <= index < self.len()*. This never takes a lock.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.map.get_index(index).map(|(x, &())| x)
+    }
+}
+
+impl<T, S> ConcurrentIndexSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Insert the value into the set.
+    ///
+    /// If an equivalent item already exists in the set, it returns
+    /// `false` leaving the original value in the set and without
+    /// altering its insertion order. Otherwise, it inserts the new
+    /// item and returns `true`.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn insert(&self, value: T) -> bool {
+        self.insert_full(value).1
+    }
+
+    /// Insert the value into the set, and get its index.
+    ///
+    /// If an equivalent item already exists in the set, it returns
+    /// the index of the existing item and `false`, leaving the
+    /// original value in the set and without altering its insertion
+    /// order. Otherwise, it inserts the new item and returns the index
+    /// of the inserted item and `true`.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn insert_full(&self, value: T) -> (usize, bool) {
+        let hash = self.map.hash(&value);
+        self.map.core.insert_with(hash, value, || ())
+    }
+
+    /// Return `true` if an equivalent to `value` exists in the set.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Return a reference to the value stored in the set, if it is present,
+    /// else `None`.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.get_full(value).map(|(_, x)| x)
+    }
+
+    /// Return item index and value
+    pub fn get_full<Q>(&self, value: &Q) -> Option<(usize, &T)>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.get_full(value).map(|(i, x, &())| (i, x))
+    }
+
+    /// Return item index, if it exists in the set
+    ///
+    /// Like every lookup by value, this takes a shared read lock on the hash
+    /// table, so it waits while another thread is appending a new value.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<T>,
+    {
+        self.map.get_index_of(value)
+    }
+}
+
+impl<T, S> Default for ConcurrentIndexSet<T, S>
+where
+    S: Default,
+{
+    /// Return an empty [`ConcurrentIndexSet`]
+    fn default() -> Self {
+        ConcurrentIndexSet {
+            map: ConcurrentIndexMap::default(),
+        }
+    }
+}
+
+impl<T, S> From<IndexSet<T, S>> for ConcurrentIndexSet<T, S> {
+    /// Moves all values of the set, keeping their order and stored hashes.
+    fn from(set: IndexSet<T, S>) -> Self {
+        ConcurrentIndexSet {
+            map: ConcurrentIndexMap::from(set.map),
+        }
+    }
+}
+
+impl<T, S> From<ConcurrentIndexSet<T, S>> for IndexSet<T, S> {
+    /// Moves all values of the set, keeping their order and stored hashes.
+    fn from(set: ConcurrentIndexSet<T, S>) -> Self {
+        IndexSet {
+            map: set.map.into(),
+        }
+    }
+}
+
+impl<T, S> FromIterator<T> for ConcurrentIndexSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iterable: I) -> Self {
+        let iter = iterable.into_iter().map(|x| (x, ()));
+        ConcurrentIndexSet {
+            map: ConcurrentIndexMap::from_iter(iter),
+        }
+    }
+}
+
+impl<T, S> Extend<T> for ConcurrentIndexSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iterable: I) {
+        let iter = iterable.into_iter().map(|x| (x, ()));
+        self.map.extend(iter);
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a ConcurrentIndexSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the items of a [`ConcurrentIndexSet`].
+///
+/// This `struct` is created by the [`ConcurrentIndexSet::iter`] method.
+/// See its documentation for more.
+pub struct Iter<'a, T> {
+    iter: map::Keys<'a, T, ()>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
@@ -1,5 +1,8 @@
+use core::hash::{BuildHasher, Hash, Hasher};
 use core::ops::{Bound, Range, RangeBounds};
 
+use crate::HashValue;
+
 pub(crate) fn third<A, B, C>(t: (A, B, C)) -> C {
     t.2
 }
@@ -51,3 +54,72 @@ where
     }
     Some(start..end)
 }
+
+/// Reorder `slice` so that the new index `i` holds the old item `order[i]`.
+///
+/// ***Panics*** if `order` is not a permutation of `0..slice.len()`.
+pub(crate) fn permute<T>(slice: &mut [T], order: &[usize]) {
+    assert_eq!(slice.len(), order.len());
+    let mut done = alloc::vec![false; order.len()];
+    for start in 0..order.len() {
+        // Follow each cycle of the permutation, swapping items into place.
+        let mut i = start;
+        while !done[i] {
+            done[i] = true;
+            let next = order[i];
+            if !done[next] {
+                slice.swap(i, next);
+            }
+            i = next;
+        }
+    }
+}
+
+/// A 64-bit finalizer, so that weak hashes still spread over a table.
+#[inline]
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^ (x >> 33)
+}
+
+/// Hash a key like `IndexMap::hash`, while the map's entries are borrowed separately
+pub(crate) fn hash_key<K, S>(hash_builder: &S, key: &K) -> HashValue
+where
+    K: ?Sized + Hash,
+    S: BuildHasher,
+{
+    let mut h = hash_builder.build_hasher();
+    key.hash(&mut h);
+    HashValue(h.finish() as usize)
+}
+
+/// Hash a fixed value, to tell whether two hashers agree.
+///
+/// This is saved with the hashes of the entries, which can only be reused by a
+/// hasher with the same fingerprint.
+#[cfg(any(feature = "serde", feature = "borsh", feature = "rkyv-custom-size"))]
+pub(crate) fn fingerprint<S: BuildHasher>(hash_builder: &S) -> u64 {
+    let mut h = hash_builder.build_hasher();
+    "indexmap".hash(&mut h);
+    HashValue(h.finish() as usize).get()
+}
+
+/// Limit our preallocated capacity from an untrusted length, like a deserializer `size_hint()`.
+///
+/// We do account for the `Bucket` overhead from its saved `hash` field, but we don't count the
+/// `RawTable` allocation or the fact that its raw capacity will be rounded up to a power of two.
+/// The "max" is an arbitrary choice anyway, not something that needs precise adherence.
+///
+/// This is based on the internal `serde::de::size_hint::cautious(hint)` function.
+#[cfg(any(feature = "serde", feature = "borsh"))]
+pub(crate) fn cautious_capacity<K, V>(hint: Option<usize>) -> usize {
+    const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+
+    core::cmp::min(
+        hint.unwrap_or(0),
+        MAX_PREALLOC_BYTES / core::mem::size_of::<crate::Bucket<K, V>>(),
+    )
+}
@@ -1,9 +1,10 @@
 #![cfg(test)]
 
 use fnv::FnvBuildHasher;
+use indexmap::interner::Interner;
 use indexmap::{indexmap, indexset, IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
-use serde_test::{assert_tokens, Token};
+use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
 
 #[test]
 fn test_serde_map() {
@@ -113,3 +114,652 @@ fn test_serde_seq_map() {
         ],
     );
 }
+
+#[test]
+fn test_serde_diff() {
+    let old = indexmap! { 1 => 2, 3 => 4, 5 => 6 };
+    let new = indexmap! { 3 => 4, 1 => 20, 7 => 8 };
+    let diff = old.diff(&new);
+    assert_tokens(
+        &diff,
+        &[
+            Token::Struct {
+                name: "Diff",
+                len: 4,
+            },
+            Token::Str("removed"),
+            Token::Seq { len: Some(1) },
+            Token::I32(5),
+            Token::SeqEnd,
+            Token::Str("changed"),
+            Token::Seq { len: Some(1) },
+            Token::Tuple { len: 2 },
+            Token::I32(1),
+            Token::I32(20),
+            Token::TupleEnd,
+            Token::SeqEnd,
+            Token::Str("moved"),
+            Token::Seq { len: Some(1) },
+            Token::Tuple { len: 2 },
+            Token::U64(0),
+            Token::I32(3),
+            Token::TupleEnd,
+            Token::SeqEnd,
+            Token::Str("inserted"),
+            Token::Seq { len: Some(1) },
+            Token::Tuple { len: 3 },
+            Token::U64(2),
+            Token::I32(7),
+            Token::I32(8),
+            Token::TupleEnd,
+            Token::SeqEnd,
+            Token::StructEnd,
+        ],
+    );
+
+    let mut map = old.clone();
+    map.apply_patch(diff);
+    assert!(map.iter().eq(&new));
+}
+
+#[test]
+fn test_serde_interner() {
+    let interner: Interner = ["b", "", "a"].into_iter().collect();
+    assert_tokens(
+        &interner,
+        &[
+            Token::Seq { len: Some(3) },
+            Token::BorrowedStr("b"),
+            Token::BorrowedStr(""),
+            Token::BorrowedStr("a"),
+            Token::SeqEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_serde_symbols() {
+    use indexmap::interner::Symbol;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Saved {
+        interner: Interner,
+        symbols: Vec<Symbol>,
+    }
+
+    let mut interner = Interner::new();
+    let symbols = ["b", "a", "b"].map(|s| interner.intern(s)).to_vec();
+    let saved = Saved { interner, symbols };
+    assert_tokens(
+        &saved,
+        &[
+            Token::Struct {
+                name: "Saved",
+                len: 2,
+            },
+            Token::Str("interner"),
+            Token::Seq { len: Some(2) },
+            Token::BorrowedStr("b"),
+            Token::BorrowedStr("a"),
+            Token::SeqEnd,
+            Token::Str("symbols"),
+            Token::Seq { len: Some(3) },
+            Token::U32(0),
+            Token::U32(1),
+            Token::U32(0),
+            Token::SeqEnd,
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(saved.interner.resolve(saved.symbols[1]), Some("a"));
+}
+
+#[test]
+fn test_serde_interner_duplicate() {
+    assert_de_tokens_error::<Interner>(
+        &[
+            Token::Seq { len: Some(2) },
+            Token::Str("a"),
+            Token::Str("a"),
+        ],
+        "duplicate interned string \"a\"",
+    );
+}
+
+#[test]
+fn test_serde_map_duplicates() {
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Maps {
+        #[serde(with = "indexmap::map::serde_dedup::first_wins")]
+        first: IndexMap<i32, i32>,
+        #[serde(with = "indexmap::map::serde_dedup::last_wins_move_to_end")]
+        last: IndexMap<i32, i32>,
+    }
+
+    impl PartialEq for Maps {
+        fn eq(&self, other: &Self) -> bool {
+            self.first.iter().eq(&other.first) && self.last.iter().eq(&other.last)
+        }
+    }
+
+    let entries = [
+        Token::Map { len: Some(3) },
+        Token::I32(1),
+        Token::I32(10),
+        Token::I32(2),
+        Token::I32(20),
+        Token::I32(1),
+        Token::I32(11),
+        Token::MapEnd,
+    ];
+    let mut tokens = vec![
+        Token::Struct {
+            name: "Maps",
+            len: 2,
+        },
+        Token::Str("first"),
+    ];
+    tokens.extend_from_slice(&entries);
+    tokens.push(Token::Str("last"));
+    tokens.extend_from_slice(&entries);
+    tokens.push(Token::StructEnd);
+    assert_de_tokens(
+        &Maps {
+            first: indexmap! { 1 => 10, 2 => 20 },
+            last: indexmap! { 2 => 20, 1 => 11 },
+        },
+        &tokens,
+    );
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(transparent)]
+    struct Strict(
+        #[serde(with = "indexmap::map::serde_dedup::duplicate_error")] IndexMap<i32, i32>,
+    );
+
+    assert_de_tokens_error::<Strict>(&entries, "duplicate map key");
+
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(transparent)]
+    struct Last(
+        #[serde(with = "indexmap::map::serde_dedup::last_wins_move_to_end")] IndexMap<i32, i32>,
+    );
+
+    impl PartialEq for Last {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.iter().eq(&other.0)
+        }
+    }
+
+    // Many duplicates, each moving its key after all the others.
+    let pairs: Vec<(i32, i32)> = (0..20_000).map(|i| ((i * 37) % 1000, i)).collect();
+    let mut tokens = vec![Token::Map { len: None }];
+    for &(key, value) in &pairs {
+        tokens.extend([Token::I32(key), Token::I32(value)]);
+    }
+    tokens.push(Token::MapEnd);
+    let mut expected = IndexMap::new();
+    for &(key, value) in &pairs {
+        expected.shift_remove(&key);
+        expected.insert(key, value);
+    }
+    assert_de_tokens(&Last(expected), &tokens);
+}
+
+#[test]
+fn test_serde_set_duplicates() {
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(transparent)]
+    struct LastSet {
+        #[serde(with = "indexmap::set::serde_dedup::last_wins_move_to_end")]
+        set: IndexSet<i32>,
+    }
+
+    impl PartialEq for LastSet {
+        fn eq(&self, other: &Self) -> bool {
+            self.set.iter().eq(&other.set)
+        }
+    }
+
+    let values = [
+        Token::Seq { len: Some(3) },
+        Token::I32(1),
+        Token::I32(2),
+        Token::I32(1),
+        Token::SeqEnd,
+    ];
+    assert_de_tokens(
+        &LastSet {
+            set: indexset! { 2, 1 },
+        },
+        &values,
+    );
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(transparent)]
+    struct Strict(#[serde(with = "indexmap::set::serde_dedup::duplicate_error")] IndexSet<i32>);
+
+    assert_de_tokens_error::<Strict>(&values, "duplicate set value");
+}
+
+#[test]
+fn test_serde_seq_map_duplicates() {
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(transparent)]
+    struct FirstSeq {
+        #[serde(with = "indexmap::map::serde_seq::first_wins")]
+        map: IndexMap<i32, i32>,
+    }
+
+    impl PartialEq for FirstSeq {
+        fn eq(&self, other: &Self) -> bool {
+            self.map.iter().eq(&other.map)
+        }
+    }
+
+    let entries = [
+        Token::Seq { len: Some(2) },
+        Token::Tuple { len: 2 },
+        Token::I32(1),
+        Token::I32(10),
+        Token::TupleEnd,
+        Token::Tuple { len: 2 },
+        Token::I32(1),
+        Token::I32(11),
+        Token::TupleEnd,
+        Token::SeqEnd,
+    ];
+    assert_de_tokens(
+        &FirstSeq {
+            map: indexmap! { 1 => 10 },
+        },
+        &entries,
+    );
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(transparent)]
+    struct Strict(#[serde(with = "indexmap::map::serde_seq::duplicate_error")] IndexMap<i32, i32>);
+
+    assert_de_tokens_error::<Strict>(&entries, "duplicate map key");
+}
+
+#[test]
+fn test_serde_max_entries() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Limited {
+        #[serde(
+            deserialize_with = "indexmap::map::serde_limits::deserialize_max::<2, _, _, _, _>"
+        )]
+        map: IndexMap<i32, i32>,
+        #[serde(deserialize_with = "indexmap::set::serde_limits::deserialize_max::<2, _, _, _>")]
+        set: IndexSet<i32>,
+        #[serde(deserialize_with = "indexmap::map::serde_seq::deserialize_max::<2, _, _, _, _>")]
+        seq: IndexMap<i32, i32>,
+    }
+
+    let map = [
+        Token::Map { len: None },
+        Token::I32(1),
+        Token::I32(2),
+        Token::I32(3),
+        Token::I32(4),
+        Token::MapEnd,
+    ];
+    let set = [
+        Token::Seq { len: None },
+        Token::I32(1),
+        Token::I32(2),
+        Token::SeqEnd,
+    ];
+    let seq = [
+        Token::Seq { len: None },
+        Token::Tuple { len: 2 },
+        Token::I32(1),
+        Token::I32(2),
+        Token::TupleEnd,
+        Token::SeqEnd,
+    ];
+    let tokens = |map: &[Token], set: &[Token], seq: &[Token]| {
+        let mut tokens = vec![Token::Struct {
+            name: "Limited",
+            len: 3,
+        }];
+        tokens.push(Token::Str("map"));
+        tokens.extend_from_slice(map);
+        tokens.push(Token::Str("set"));
+        tokens.extend_from_slice(set);
+        tokens.push(Token::Str("seq"));
+        tokens.extend_from_slice(seq);
+        tokens.push(Token::StructEnd);
+        tokens
+    };
+
+    assert_de_tokens(
+        &Limited {
+            map: indexmap! { 1 => 2, 3 => 4 },
+            set: indexset! { 1, 2 },
+            seq: indexmap! { 1 => 2 },
+        },
+        &tokens(&map, &set, &seq),
+    );
+
+    // Fail right after reading one entry too many.
+    let mut long_map = tokens(&map, &set, &seq);
+    long_map.truncate(7);
+    long_map.extend_from_slice(&[Token::I32(5), Token::I32(6)]);
+    assert_de_tokens_error::<Limited>(&long_map, "map has more than 2 entries");
+
+    let mut long_set = tokens(&map, &set, &seq);
+    long_set.truncate(12);
+    long_set.push(Token::I32(3));
+    assert_de_tokens_error::<Limited>(&long_set, "set has more than 2 values");
+}
+
+#[test]
+fn test_serde_in_place() {
+    use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+
+    let input =
+        |entries: Vec<(i32, &'static str)>| MapDeserializer::<_, Error>::new(entries.into_iter());
+    let check = |map: &mut IndexMap<i32, String>, entries: &[(i32, &'static str)]| {
+        IndexMap::deserialize_in_place(input(entries.to_vec()), map).unwrap();
+        let expected = IndexMap::<i32, String>::deserialize(input(entries.to_vec())).unwrap();
+        assert_eq!(*map, expected);
+        assert!(map.iter().eq(&expected));
+        for (key, value) in &expected {
+            assert_eq!(map.get(key), Some(value));
+        }
+    };
+
+    let mut map = IndexMap::with_capacity(10);
+    check(&mut map, &[(1, "a"), (2, "b"), (3, "c"), (4, "d")]);
+    let capacity = map.capacity();
+    check(&mut map, &[(4, "e"), (1, "f"), (4, "g"), (5, "h")]);
+    check(&mut map, &[(2, "i"), (2, "j")]);
+    check(
+        &mut map,
+        &[(1, "a"), (2, "b"), (3, "c"), (4, "d"), (5, "e")],
+    );
+    check(&mut map, &[]);
+    assert_eq!(map.capacity(), capacity);
+
+    // An error leaves a valid map with some of the entries.
+    let mut map = indexmap! { 1 => 1, 2 => 2, 3 => 3 };
+    let bad = MapDeserializer::<_, Error>::new(vec![(3, 3), (4, -1)].into_iter());
+    assert!(IndexMap::<i32, u32>::deserialize_in_place(bad, &mut map).is_err());
+    assert_eq!(map, indexmap! { 3 => 3 });
+
+    let mut set = indexset! { 1, 2, 3 };
+    let input = SeqDeserializer::<_, Error>::new(vec![3, 4, 3, 5, 6].into_iter());
+    IndexSet::deserialize_in_place(input, &mut set).unwrap();
+    assert!(set.iter().eq(&[3, 4, 5, 6]));
+    assert_eq!(set.get_index_of(&6), Some(3));
+
+    let mut map = indexmap! { 1 => 1, 2 => 2, 3 => 3 };
+    let input =
+        SeqDeserializer::<_, Error>::new(vec![vec![4, 4], vec![4, 5], vec![1, 1]].into_iter());
+    indexmap::map::serde_seq::deserialize_in_place(input, &mut map).unwrap();
+    assert_eq!(map, indexmap! { 4 => 5, 1 => 1 });
+    assert!(map.keys().eq(&[4, 1]));
+    assert_eq!(map.get(&1), Some(&1));
+}
+
+#[test]
+fn test_serde_entries() {
+    use indexmap::map::serde_entries::EntryFields;
+
+    struct Short;
+
+    impl EntryFields for Short {
+        const KEY: &'static str = "k";
+        const VALUE: &'static str = "v";
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Entries {
+        #[serde(with = "indexmap::map::serde_entries")]
+        map: IndexMap<(i32, i32), bool>,
+        #[serde(
+            serialize_with = "indexmap::map::serde_entries::serialize_with_fields::<Short, _, _, _, _>",
+            deserialize_with = "indexmap::map::serde_entries::deserialize_with_fields::<Short, _, _, _, _>"
+        )]
+        short: IndexMap<i32, i32>,
+        #[serde(with = "indexmap::set::serde_entries")]
+        set: IndexSet<i32>,
+    }
+
+    let value = Entries {
+        map: indexmap! { (3, 4) => true, (1, 2) => false },
+        short: indexmap! { 5 => 6 },
+        set: indexset! { 7, 8 },
+    };
+    assert_tokens(
+        &value,
+        &[
+            Token::Struct {
+                name: "Entries",
+                len: 3,
+            },
+            Token::Str("map"),
+            Token::Seq { len: Some(2) },
+            Token::Struct {
+                name: "Entry",
+                len: 2,
+            },
+            Token::Str("key"),
+            Token::Tuple { len: 2 },
+            Token::I32(3),
+            Token::I32(4),
+            Token::TupleEnd,
+            Token::Str("value"),
+            Token::Bool(true),
+            Token::StructEnd,
+            Token::Struct {
+                name: "Entry",
+                len: 2,
+            },
+            Token::Str("key"),
+            Token::Tuple { len: 2 },
+            Token::I32(1),
+            Token::I32(2),
+            Token::TupleEnd,
+            Token::Str("value"),
+            Token::Bool(false),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::Str("short"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "Entry",
+                len: 2,
+            },
+            Token::Str("k"),
+            Token::I32(5),
+            Token::Str("v"),
+            Token::I32(6),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::Str("set"),
+            Token::Seq { len: Some(2) },
+            Token::Struct {
+                name: "Element",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::I32(7),
+            Token::StructEnd,
+            Token::Struct {
+                name: "Element",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::I32(8),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::StructEnd,
+        ],
+    );
+
+    // Fields may come in any order, or as a sequence, and unknown ones are ignored.
+    assert_de_tokens(
+        &Entries {
+            map: indexmap! { (1, 2) => true },
+            short: indexmap! { 5 => 6, 7 => 8 },
+            set: indexset! {},
+        },
+        &[
+            Token::Struct {
+                name: "Entries",
+                len: 3,
+            },
+            Token::Str("map"),
+            Token::Seq { len: None },
+            Token::Map { len: None },
+            Token::Str("value"),
+            Token::Bool(true),
+            Token::Str("extra"),
+            Token::Unit,
+            Token::Str("key"),
+            Token::Tuple { len: 2 },
+            Token::I32(1),
+            Token::I32(2),
+            Token::TupleEnd,
+            Token::MapEnd,
+            Token::SeqEnd,
+            Token::Str("short"),
+            Token::Seq { len: None },
+            Token::Seq { len: Some(2) },
+            Token::I32(5),
+            Token::I32(6),
+            Token::SeqEnd,
+            Token::Struct {
+                name: "Entry",
+                len: 2,
+            },
+            Token::Str("v"),
+            Token::I32(8),
+            Token::Str("k"),
+            Token::I32(7),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::Str("set"),
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::StructEnd,
+        ],
+    );
+
+    assert_de_tokens_error::<Entries>(
+        &[
+            Token::Struct {
+                name: "Entries",
+                len: 3,
+            },
+            Token::Str("map"),
+            Token::Seq { len: None },
+            Token::Map { len: None },
+            Token::Str("value"),
+            Token::Bool(true),
+            Token::MapEnd,
+        ],
+        "missing field `key`",
+    );
+}
+
+#[test]
+fn test_serde_hashed() {
+    use std::cell::Cell;
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hash, Hasher};
+
+    thread_local!(static HASHES: Cell<usize> = const { Cell::new(0) });
+
+    #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+    #[serde(transparent)]
+    struct Counted(i32);
+
+    impl Hash for Counted {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            HASHES.with(|hashes| hashes.set(hashes.get() + 1));
+            self.0.hash(state);
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Hashed<S: BuildHasher + Default> {
+        #[serde(with = "indexmap::map::serde_hashed")]
+        map: IndexMap<Counted, i32, S>,
+        #[serde(with = "indexmap::set::serde_hashed")]
+        set: IndexSet<Counted, S>,
+    }
+
+    // Compare in order without hashing, unlike `IndexMap` itself.
+    impl<S: BuildHasher + Default> PartialEq for Hashed<S> {
+        fn eq(&self, other: &Self) -> bool {
+            self.map.iter().eq(&other.map) && self.set.iter().eq(&other.set)
+        }
+    }
+
+    let hash = |value: &dyn Fn(&mut dyn Hasher)| {
+        let mut h = FnvBuildHasher::default().build_hasher();
+        value(&mut h);
+        h.finish()
+    };
+    let fingerprint = hash(&|h| "indexmap".hash(&mut &mut *h));
+    let key = |i: i32| hash(&|h| i.hash(&mut &mut *h));
+
+    let value = Hashed::<FnvBuildHasher> {
+        map: [(Counted(1), 10), (Counted(2), 20)].into_iter().collect(),
+        set: [Counted(3)].into_iter().collect(),
+    };
+    let tokens = |fingerprint: u64| {
+        vec![
+            Token::Struct {
+                name: "Hashed",
+                len: 2,
+            },
+            Token::Str("map"),
+            Token::Tuple { len: 2 },
+            Token::U64(fingerprint),
+            Token::Seq { len: Some(2) },
+            Token::Tuple { len: 3 },
+            Token::U64(key(1)),
+            Token::I32(1),
+            Token::I32(10),
+            Token::TupleEnd,
+            Token::Tuple { len: 3 },
+            Token::U64(key(2)),
+            Token::I32(2),
+            Token::I32(20),
+            Token::TupleEnd,
+            Token::SeqEnd,
+            Token::TupleEnd,
+            Token::Str("set"),
+            Token::Tuple { len: 2 },
+            Token::U64(fingerprint),
+            Token::Seq { len: Some(1) },
+            Token::Tuple { len: 2 },
+            Token::U64(key(3)),
+            Token::I32(3),
+            Token::TupleEnd,
+            Token::SeqEnd,
+            Token::TupleEnd,
+            Token::StructEnd,
+        ]
+    };
+    assert_tokens(&value, &tokens(fingerprint));
+
+    // A matching hasher reuses the saved hashes without hashing the keys.
+    HASHES.with(|hashes| hashes.set(0));
+    assert_de_tokens(&value, &tokens(fingerprint));
+    assert_eq!(HASHES.with(Cell::get), 0);
+
+    // Any other hasher hashes the keys again.
+    let value = Hashed::<RandomState> {
+        map: [(Counted(1), 10), (Counted(2), 20)].into_iter().collect(),
+        set: [Counted(3)].into_iter().collect(),
+    };
+    HASHES.with(|hashes| hashes.set(0));
+    assert_de_tokens(&value, &tokens(fingerprint));
+    assert!(HASHES.with(Cell::get) > 0);
+}
@@ -1,4 +1,4 @@
-use indexmap::{IndexMap, IndexSet};
+use indexmap::{Hashed, IndexMap, IndexSet};
 use itertools::Itertools;
 
 use quickcheck::Arbitrary;
@@ -19,7 +19,8 @@ use std::hash::Hash;
 use std::ops::Bound;
 use std::ops::Deref;
 
-use indexmap::map::Entry;
+use indexmap::map::{Entry, MutableKeys};
+use indexmap::map::{ObservedEntry, ObservedRawEntryMut};
 use std::collections::hash_map::Entry as StdEntry;
 
 fn set<'a, T: 'a, I>(iter: I) -> HashSet<T>
@@ -61,7 +62,7 @@ macro_rules! quickcheck_limit {
                     let mut quickcheck = QuickCheck::new();
                     if cfg!(miri) {
                         quickcheck = quickcheck
-                            .gen(Gen::new(10))
+                            .rng(Gen::new(10))
                             .tests(10)
                             .max_tests(100);
                     }
@@ -690,6 +691,41 @@ quickcheck_limit! {
         let mapv = Vec::from_iter(map);
         assert_eq!(answer, mapv);
     }
+
+    fn diff_apply_patch(old: Large<Vec<(i8, i8)>>, new: Large<Vec<(i8, i8)>>) -> bool {
+        let old: IndexMap<_, _> = IndexMap::from_iter(old.to_vec());
+        let new: IndexMap<_, _> = IndexMap::from_iter(new.to_vec());
+        let diff = old.diff(&new);
+        assert!(diff.moved().windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(diff.inserted().windows(2).all(|w| w[0].0 < w[1].0));
+
+        let mut map = old.clone();
+        map.apply_patch(diff);
+        itertools::assert_equal(&map, &new);
+        old.diff(&old).is_empty()
+    }
+
+    fn diff_apply_patch_permuted(keys: Large<Vec<i8>>, rotate: usize, swaps: Vec<(u8, u8)>) -> bool {
+        let old = indexmap(keys.iter());
+        let mut new = old.clone();
+        if !new.is_empty() {
+            let len = new.len();
+            for _ in 0..rotate % len {
+                new.move_index(0, len - 1);
+            }
+            for &(a, b) in &swaps {
+                new.swap_indices(a as usize % len, b as usize % len);
+            }
+        }
+        let diff = old.diff(&new);
+        assert!(diff.removed().is_empty() && diff.changed().is_empty());
+        assert!(diff.inserted().is_empty());
+
+        let mut map = old.clone();
+        map.apply_patch(diff);
+        itertools::assert_equal(&map, &new);
+        true
+    }
 }
 
 fn assert_sorted_by_key<I, Key, X>(iterable: I, key: Key)
@@ -757,3 +793,339 @@ where
         Box::new((**self).shrink().map(Large))
     }
 }
+
+#[derive(Default)]
+struct IndexRecorder {
+    inserted: Vec<usize>,
+    removed: Vec<usize>,
+    relocated: Vec<(usize, usize)>,
+}
+
+impl indexmap::map::IndexObserver for IndexRecorder {
+    fn inserted(&mut self, index: usize) {
+        self.inserted.push(index);
+    }
+
+    fn removed(&mut self, index: usize) {
+        self.removed.push(index);
+    }
+
+    fn relocated(&mut self, old: usize, new: usize) {
+        self.relocated.push((old, new));
+    }
+}
+
+impl IndexRecorder {
+    /// Apply the recorded events to a shadow of the keys, then reset.
+    fn replay<K: Copy + Debug + Eq>(&mut self, shadow: &mut Vec<K>, keys: &[K]) {
+        let mut new = vec![None; keys.len()];
+        let mut moved = vec![false; shadow.len()];
+        for &i in &self.removed {
+            assert!(!std::mem::replace(&mut moved[i], true));
+        }
+        for &(old, i) in &self.relocated {
+            assert!(!std::mem::replace(&mut moved[old], true));
+            assert!(new[i].replace(shadow[old]).is_none());
+        }
+        for (i, &key) in shadow.iter().enumerate() {
+            if !moved[i] {
+                assert!(new[i].replace(key).is_none());
+            }
+        }
+        for &i in &self.inserted {
+            assert!(new[i].replace(keys[i]).is_none());
+        }
+        *shadow = new.into_iter().map(Option::unwrap).collect();
+        *self = Self::default();
+    }
+}
+
+quickcheck_limit! {
+    fn observed_map_ops(ops: Vec<(u8, u8, u8)>) -> bool {
+        let mut map = IndexMap::new();
+        let mut recorder = IndexRecorder::default();
+        let mut shadow = Vec::new();
+        for &(op, a, b) in &ops {
+            let len = map.len();
+            // An existing key can only move within bounds.
+            let insert_len = if map.contains_key(&a) { len } else { len + 1 };
+            if op % 37 == 29 && len > 0 {
+                // Edit a key in place, which the next `rehash_keys` must account for.
+                *map.get_index_mut2(a as usize % len).unwrap().0 = b;
+                shadow = map.keys().copied().collect();
+            }
+            let mut view = map.observed(&mut recorder);
+            match op % 37 {
+                0 | 1 => { view.insert(a, b); }
+                2 => { view.shift_insert(b as usize % insert_len, a, b); }
+                3 => { view.swap_remove(&a); }
+                4 => { view.shift_remove(&a); }
+                5 if len > 0 => view.move_index(a as usize % len, b as usize % len),
+                6 if len > 0 => view.swap_indices(a as usize % len, b as usize % len),
+                7 => view.retain(|k, _| k % 3 != a % 3),
+                8 => view.reverse(),
+                9 => view.sort_by(|_, v1, _, v2| v1.cmp(v2)),
+                10 => view.sort_unstable_keys(),
+                11 => view.truncate(a as usize % (len + 1)),
+                12 => {
+                    let (a, b) = (a as usize % (len + 1), b as usize % (len + 1));
+                    view.drain(a.min(b)..a.max(b));
+                }
+                13 => { view.pop(); }
+                14 => { view.entry(a).and_modify(|v| *v = v.wrapping_add(1)).or_insert(b); }
+                15 => match view.entry(a) {
+                    ObservedEntry::Occupied(entry) => { entry.swap_remove_entry(); }
+                    ObservedEntry::Vacant(entry) => { entry.shift_insert(b as usize % (len + 1), b); }
+                },
+                16 => match view.entry(a) {
+                    ObservedEntry::Occupied(entry) => { entry.shift_remove(); }
+                    ObservedEntry::Vacant(entry) => { entry.insert(b); }
+                },
+                17 => match view.entry(a) {
+                    ObservedEntry::Occupied(entry) if b % 2 == 0 => entry.move_index(b as usize % len),
+                    ObservedEntry::Occupied(entry) => entry.swap_indices(b as usize % len),
+                    ObservedEntry::Vacant(entry) => { entry.insert(b); }
+                },
+                18 => match view.raw_entry_mut_v1().from_key(&a) {
+                    ObservedRawEntryMut::Occupied(entry) if b % 2 == 0 => { entry.shift_remove_entry(); }
+                    ObservedRawEntryMut::Occupied(entry) => entry.move_index(b as usize % len),
+                    ObservedRawEntryMut::Vacant(entry) => { entry.shift_insert(b as usize % (len + 1), a, b); }
+                },
+                19 => match view.raw_entry_mut_v1().from_key(&a) {
+                    ObservedRawEntryMut::Occupied(entry) => { entry.swap_remove(); }
+                    ObservedRawEntryMut::Vacant(entry) => { entry.insert(a, b); }
+                },
+                20 => {
+                    let (x, y) = (a as usize % (len + 1), b as usize % (len + 1));
+                    view.splice(x.min(y)..x.max(y), [(a, b), (b, a)]);
+                }
+                21 => {
+                    let mut other = IndexMap::from([(b, a), (a.wrapping_add(1), b)]);
+                    view.append(&mut other);
+                }
+                22 => view.sort_by_cached_key(|k, v| k ^ v),
+                23 => { view.insert_before(b as usize % (len + 1), a, b); }
+                24 => { view.swap_remove_entry(&a); }
+                25 => { view.shift_remove_entry(&a); }
+                26 if len > 0 => { view.swap_remove_indices(&[a as usize % len, b as usize % len]); }
+                26 => { view.shift_remove_many(&[a, b]); }
+                27 if len > 0 => { view.shift_remove_indices(&[a as usize % len, b as usize % len]); }
+                27 => { view.swap_remove_many(&[a, b]); }
+                28 => { view.split_off(a as usize % (len + 1)); }
+                29 => { view.rehash_keys(); }
+                30 => { let _ = view.rename_key(&a, b); }
+                31 if len > 0 => { let _ = view.replace_key(a as usize % len, b); }
+                32 => {
+                    let mut cursor = view.cursor_mut_at(a as usize % (len + 1));
+                    if b % 8 >= 4 {
+                        cursor.move_next();
+                    }
+                    match b % 4 {
+                        0 => { cursor.insert_before(b, a); }
+                        1 => { cursor.insert_after(b, a); }
+                        2 => { cursor.remove_current(); }
+                        _ => { cursor.split_after(); }
+                    }
+                }
+                33 => {
+                    if let Some(mut cursor) = view.cursor_mut_at_key(&a) {
+                        if b % 2 == 0 {
+                            cursor.insert_after(b, a);
+                        } else {
+                            cursor.insert_before(b, a);
+                        }
+                    }
+                }
+                34 if len > 0 => {
+                    let entry = view.get_index_entry(a as usize % len).unwrap();
+                    match b % 4 {
+                        0 => { entry.swap_remove(); }
+                        1 => { entry.shift_remove(); }
+                        2 => entry.move_index(b as usize % len),
+                        _ => entry.swap_indices(b as usize % len),
+                    }
+                }
+                35 => {
+                    let hashed = Hashed::new(a, view.hasher());
+                    match b % 4 {
+                        0 => { view.insert_full_hashed(hashed, b); }
+                        1 => { view.entry_hashed(hashed).or_insert(b); }
+                        2 => { view.swap_remove_full_hashed(hashed.as_ref()); }
+                        _ => { view.shift_remove_full_hashed(hashed.as_ref()); }
+                    }
+                }
+                36 => {
+                    let mut other = (*view).clone();
+                    other.swap_remove(&a);
+                    other.insert(b, a);
+                    other.reverse();
+                    let patch = view.diff(&other);
+                    view.apply_patch(patch);
+                    assert!(view.iter().eq(&other));
+                }
+                _ => { view.drain(..a as usize % (len + 1)); }
+            }
+            let keys: Vec<u8> = map.keys().copied().collect();
+            recorder.replay(&mut shadow, &keys);
+            if matches!(op % 37, 30 | 31) {
+                // Renaming a key keeps its index, so there is nothing to report.
+                shadow = keys.clone();
+            }
+            assert_eq!(shadow, keys);
+        }
+        true
+    }
+
+    fn observed_set_ops(ops: Vec<(u8, u8)>) -> bool {
+        let mut set = IndexSet::new();
+        let mut recorder = IndexRecorder::default();
+        let mut shadow = Vec::new();
+        for &(op, a) in &ops {
+            let len = set.len();
+            let sorted = set.iter().tuple_windows().all(|(a, b)| a < b);
+            let mut view = set.observed(&mut recorder);
+            match op % 14 {
+                0 | 1 => { view.insert(a); }
+                2 if sorted => { view.insert_sorted(a); }
+                3 => { view.swap_remove(&a); }
+                4 => { view.shift_take(&a); }
+                5 if len > 0 => view.move_index(a as usize % len, 0),
+                6 => { view.insert_before(op as usize % (len + 1), a); }
+                7 => {
+                    let at = op as usize % (len + 1);
+                    view.splice(at..len.min(at + 2), [a, a.wrapping_add(1)]);
+                }
+                8 => { view.shift_remove_many(&[a, a.wrapping_add(1)]); }
+                9 => { view.replace_full(a); }
+                10 => { view.split_off(a as usize % (len + 1)); }
+                11 => {
+                    let mut cursor = view.cursor_mut_at(op as usize % (len + 1));
+                    match a % 4 {
+                        0 => { cursor.insert_before(a); }
+                        1 => { cursor.insert_after(a); }
+                        2 => { cursor.remove_current(); }
+                        _ => { cursor.split_after(); }
+                    }
+                }
+                12 => {
+                    if let Some(mut cursor) = view.cursor_mut_at_value(&a) {
+                        cursor.insert_after(op);
+                    }
+                }
+                _ => view.sort_by_cached_key(|&x| x ^ a),
+            }
+            let keys: Vec<u8> = set.iter().copied().collect();
+            recorder.replay(&mut shadow, &keys);
+            assert_eq!(shadow, keys);
+        }
+        true
+    }
+}
+
+quickcheck_limit! {
+    fn transaction_ops(initial: Vec<(u8, u8)>, ops: Vec<(u8, u8, u8)>, commit: bool) -> bool {
+        let mut map: IndexMap<u8, u8> = initial.into_iter().collect();
+        let original = map.clone();
+        let mut expected = map.clone();
+        let mut tx = map.checkpoint();
+        for &(op, a, b) in &ops {
+            let len = tx.len();
+            let insert_len = if tx.contains_key(&a) { len } else { len + 1 };
+            match op % 13 {
+                0 | 1 => assert_eq!(tx.insert(a, b).copied(), expected.insert(a, b)),
+                2 => {
+                    let i = b as usize % insert_len;
+                    assert_eq!(tx.shift_insert(i, a, b).copied(), expected.shift_insert(i, a, b));
+                }
+                3 => assert_eq!(tx.swap_remove(&a).copied(), expected.swap_remove(&a)),
+                4 => assert_eq!(tx.shift_remove(&a).copied(), expected.shift_remove(&a)),
+                5 if len > 0 => {
+                    let (from, to) = (a as usize % len, b as usize % len);
+                    tx.move_index(from, to);
+                    expected.move_index(from, to);
+                }
+                6 if len > 0 => {
+                    let (a, b) = (a as usize % len, b as usize % len);
+                    tx.swap_indices(a, b);
+                    expected.swap_indices(a, b);
+                }
+                7 => {
+                    tx.retain(|k, _| k % 3 != a % 3);
+                    expected.retain(|k, _| k % 3 != a % 3);
+                }
+                8 => {
+                    tx.reverse();
+                    expected.reverse();
+                }
+                9 => {
+                    tx.sort_by(|_, v1, _, v2| v1.cmp(v2));
+                    expected.sort_by(|_, v1, _, v2| v1.cmp(v2));
+                }
+                10 => {
+                    if let Some(v) = tx.get_mut(&a) {
+                        *v = b;
+                    }
+                    if let Some(v) = expected.get_mut(&a) {
+                        *v = b;
+                    }
+                }
+                11 => {
+                    let len = a as usize % (len + 1);
+                    tx.truncate(len);
+                    expected.truncate(len);
+                }
+                _ => assert_eq!(tx.pop().map(|(&k, &v)| (k, v)), expected.pop()),
+            }
+            assert!(tx.iter().eq(&expected));
+        }
+        if commit {
+            tx.commit();
+        } else {
+            tx.rollback();
+            expected = original;
+        }
+        assert!(map.iter().eq(&expected));
+        for (i, k) in map.keys().enumerate() {
+            assert_eq!(map.get_index_of(k), Some(i));
+        }
+        true
+    }
+}
+
+/// A hasher that only keeps the lowest bits, so many keys share a hash.
+#[derive(Default)]
+struct WeakHasher(u64);
+
+impl std::hash::Hasher for WeakHasher {
+    fn finish(&self) -> u64 {
+        self.0 & 0xf
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0.wrapping_mul(31).wrapping_add(u64::from(b));
+        }
+    }
+}
+
+quickcheck_limit! {
+    fn frozen_lookups(keys: Large<Vec<i16>>, probes: Vec<i16>) -> bool {
+        let map: IndexMapFnv<i16, usize> = keys.iter().map(|&k| (k, k as usize)).collect();
+        let frozen = map.clone().freeze();
+        assert!(frozen.iter().eq(&map));
+        for k in keys.iter().chain(&probes) {
+            assert_eq!(frozen.get_full(k), map.get_full(k));
+        }
+        frozen.thaw() == map
+    }
+
+    fn frozen_lookups_weak_hash(keys: Vec<u16>, probes: Vec<u16>) -> bool {
+        let map: IndexMap<u16, (), BuildHasherDefault<WeakHasher>> =
+            keys.iter().map(|&k| (k, ())).collect();
+        let frozen = map.clone().freeze();
+        for k in keys.iter().chain(&probes) {
+            assert_eq!(frozen.get_index_of(k), map.get_index_of(k));
+        }
+        frozen.thaw().iter().eq(&map)
+    }
+}